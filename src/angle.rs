@@ -0,0 +1,267 @@
+use crate::{
+    error::Error,
+    number::{integer, Number},
+    token::Function,
+};
+
+/// The unit the trig functions take and the inverse trig functions return angles in
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum AngleMode {
+    #[default]
+    Radians,
+    Degrees,
+    /// 400 to a full turn, 100 to a right angle
+    Gradians,
+}
+
+impl AngleMode {
+    /// Looks up a mode by name, e.g. `rad`, `deg` or `grad`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "rad" | "radians" => Some(AngleMode::Radians),
+            "deg" | "degrees" => Some(AngleMode::Degrees),
+            "grad" | "gradians" | "gon" => Some(AngleMode::Gradians),
+            _ => None,
+        }
+    }
+
+    // A full turn in the unit of the mode, radians have no whole number of them
+    fn turn(self) -> Option<i64> {
+        match self {
+            AngleMode::Radians => None,
+            AngleMode::Degrees => Some(360),
+            AngleMode::Gradians => Some(400),
+        }
+    }
+}
+
+fn pi<N: Number>() -> Result<N, Error> {
+    N::constant("pi").ok_or(Error::NotSupported)
+}
+
+// A half turn is π radians
+fn to_radians_in<N: Number>(x: &N, turn: i64) -> Result<N, Error> {
+    x.checked_mul(&pi()?)?.checked_div(&integer(turn / 2)?)
+}
+
+fn from_radians_in<N: Number>(x: &N, turn: i64) -> Result<N, Error> {
+    x.checked_mul(&integer(turn / 2)?)?.checked_div(&pi()?)
+}
+
+/// Converts an angle in the unit of the mode to radians
+pub fn to_radians<N: Number>(x: &N, mode: AngleMode) -> Result<N, Error> {
+    match mode.turn() {
+        None => Ok(x.clone()),
+        Some(turn) => to_radians_in(x, turn),
+    }
+}
+
+/// Converts an angle in radians to the unit of the mode
+pub fn from_radians<N: Number>(x: &N, mode: AngleMode) -> Result<N, Error> {
+    match mode.turn() {
+        None => Ok(x.clone()),
+        Some(turn) => from_radians_in(x, turn),
+    }
+}
+
+/// One degree in the unit of the mode, what the `°` postfix multiplies by
+pub fn degree<N: Number>(mode: AngleMode) -> Result<N, Error> {
+    match mode.turn() {
+        None => pi::<N>()?.checked_div(&integer(180)?),
+        Some(turn) => integer::<N>(turn)?.checked_div(&integer(360)?),
+    }
+}
+
+// Where an angle sits in the turn, in 24ths, when it is a whole number of them.
+// Those are the multiples of 15°, where the trig functions have simple exact values.
+fn twenty_fourths<N: Number>(x: &N, turn: i64) -> Option<i64> {
+    let k = x
+        .checked_mul(&integer(24).ok()?)
+        .ok()?
+        .checked_div(&integer(turn).ok()?)
+        .ok()?;
+    if !k.floor().ok()?.cmp_real(&k).ok()?.is_eq() {
+        return None;
+    }
+    let cycles = k.checked_div(&integer(24).ok()?).ok()?.floor().ok()?;
+    let k = k
+        .checked_sub(&cycles.checked_mul(&integer(24).ok()?).ok()?)
+        .ok()?;
+    (0..24).find(|i| {
+        let i = integer::<N>(*i);
+        matches!(i.and_then(|i| k.cmp_real(&i)), Ok(o) if o.is_eq())
+    })
+}
+
+fn fraction<N: Number>(numer: i64, denom: i64) -> Result<N, Error> {
+    integer::<N>(numer)?.checked_div(&integer(denom)?)
+}
+
+// An exact result in the representation of the argument,
+// so decimals give decimals and exact fractions give exact fractions
+fn exact<N: Number>(x: &N, numer: i64, denom: i64) -> Result<N, Error> {
+    let zero = x.checked_mul(&integer(0)?)?;
+    let numer = zero.checked_add(&integer(numer)?)?;
+    numer.checked_div(&zero.checked_add(&integer(denom)?)?)
+}
+
+// The exact sine of k 24ths of a turn, if it is a simple fraction
+fn exact_sin(k: i64) -> Option<(i64, i64)> {
+    match k {
+        0 | 12 => Some((0, 1)),
+        2 | 10 => Some((1, 2)),
+        6 => Some((1, 1)),
+        14 | 22 => Some((-1, 2)),
+        18 => Some((-1, 1)),
+        _ => None,
+    }
+}
+
+#[derive(Copy, Clone)]
+enum Trig {
+    Sin,
+    Cos,
+    Tan,
+    Ctan,
+}
+
+// Sine, cosine, tangent or cotangent of an angle in a unit with `turn` to a full turn.
+// The angle is reduced to one turn before it is converted, and multiples of 15°
+// with a simple exact result give it, so `sin(30°)` is exactly 1/2.
+fn trig<N: Number>(x: &N, turn: i64, f: Trig) -> Result<N, Error> {
+    if let Some(k) = twenty_fourths(x, turn) {
+        let value = match f {
+            Trig::Sin => exact_sin(k),
+            Trig::Cos => exact_sin((k + 6) % 24),
+            Trig::Tan => match k {
+                0 | 12 => Some((0, 1)),
+                3 | 15 => Some((1, 1)),
+                9 | 21 => Some((-1, 1)),
+                6 | 18 => return Err(Error::DomainError),
+                _ => None,
+            },
+            Trig::Ctan => match k {
+                0 | 12 => return Err(Error::DomainError),
+                3 | 15 => Some((1, 1)),
+                9 | 21 => Some((-1, 1)),
+                6 | 18 => Some((0, 1)),
+                _ => None,
+            },
+        };
+        if let Some((numer, denom)) = value {
+            return exact(x, numer, denom);
+        }
+    }
+    let reduced = match x.checked_div(&integer(turn)?)?.floor() {
+        Ok(turns) => x.checked_sub(&turns.checked_mul(&integer(turn)?)?)?,
+        // Complex angles are not reduced, only converted
+        Err(Error::ExpectedReal) => x.clone(),
+        Err(e) => return Err(e),
+    };
+    let radians = to_radians_in(&reduced, turn)?;
+    match f {
+        Trig::Sin => radians.sin(),
+        Trig::Cos => radians.cos(),
+        Trig::Tan => radians.tan(),
+        Trig::Ctan => radians
+            .cos()?
+            .checked_div(&radians.sin()?)
+            .map_err(|_| Error::DomainError),
+    }
+}
+
+// The angle of an inverse trig function in a unit with `turn` to a full turn,
+// exact when the argument is one of the simple values in `table`, given in 24ths of a turn
+fn inverse<N: Number>(
+    x: &N,
+    turn: i64,
+    table: &[((i64, i64), i64)],
+    f: fn(&N) -> Result<N, Error>,
+) -> Result<N, Error> {
+    for ((numer, denom), k) in table {
+        let value = fraction::<N>(*numer, *denom)?;
+        if matches!(x.cmp_real(&value), Ok(o) if o.is_eq()) {
+            return exact(x, k * turn, 24);
+        }
+    }
+    from_radians_in(&f(x)?, turn)
+}
+
+const ASIN: &[((i64, i64), i64)] = &[
+    ((0, 1), 0),
+    ((1, 2), 2),
+    ((-1, 2), -2),
+    ((1, 1), 6),
+    ((-1, 1), -6),
+];
+const ACOS: &[((i64, i64), i64)] = &[
+    ((1, 1), 0),
+    ((1, 2), 4),
+    ((0, 1), 6),
+    ((-1, 2), 8),
+    ((-1, 1), 12),
+];
+const ATAN: &[((i64, i64), i64)] = &[((0, 1), 0), ((1, 1), 3), ((-1, 1), -3)];
+
+fn atan2<N: Number>(y: &N, x: &N, turn: i64) -> Result<N, Error> {
+    let zero = integer::<N>(0)?;
+    let half = integer::<N>(turn / 2)?;
+    let atan = |t: &N| inverse(t, turn, ATAN, N::atan);
+    match x.cmp_real(&zero)? {
+        o if o.is_gt() => atan(&y.checked_div(x)?),
+        o if o.is_lt() => {
+            let angle = atan(&y.checked_div(x)?)?;
+            if y.cmp_real(&zero)?.is_lt() {
+                angle.checked_sub(&half)
+            } else {
+                angle.checked_add(&half)
+            }
+        }
+        _ => y.sign()?.checked_mul(&fraction(turn, 4)?),
+    }
+}
+
+fn reciprocal<N: Number>(x: N) -> Result<N, Error> {
+    integer::<N>(1)?
+        .checked_div(&x)
+        .map_err(|_| Error::DomainError)
+}
+
+// The trig functions for a unit with `TURN` to a full turn
+fn in_unit<N: Number, const TURN: i64>(name: &str) -> Option<Function<N>> {
+    Some(match name {
+        "sin" => Function::OneParam(|x: N| trig(&x, TURN, Trig::Sin)),
+        "cos" => Function::OneParam(|x: N| trig(&x, TURN, Trig::Cos)),
+        "tan" => Function::OneParam(|x: N| trig(&x, TURN, Trig::Tan)),
+        "sec" => Function::OneParam(|x: N| reciprocal(trig(&x, TURN, Trig::Cos)?)),
+        "csc" => Function::OneParam(|x: N| reciprocal(trig(&x, TURN, Trig::Sin)?)),
+        "ctan" => Function::OneParam(|x: N| trig(&x, TURN, Trig::Ctan)),
+        "asin" => Function::OneParam(|x: N| inverse(&x, TURN, ASIN, N::asin)),
+        "acos" => Function::OneParam(|x: N| inverse(&x, TURN, ACOS, N::acos)),
+        "atan" => Function::OneParam(|x: N| inverse(&x, TURN, ATAN, N::atan)),
+        "atan2" => Function::TwoParam(|x: N, y: N| atan2(&y, &x, TURN)),
+        _ => return None,
+    })
+}
+
+/// The version of a trig function for the angle mode, if it differs from the radian one
+/// ## Example
+/// ```rust
+/// use calc::{angle::AngleMode, Context};
+///
+/// let mut context = Context::new();
+/// context.options.angle = AngleMode::Degrees;
+/// assert_eq!(context.eval("sin(30)").unwrap(), context.eval("0.5").unwrap());
+/// assert_eq!(context.eval("atan(1)").unwrap(), context.eval("45").unwrap());
+/// assert_eq!(context.eval("cos(400)").unwrap(), context.eval("cos(40)").unwrap());
+/// // The `°` postfix works in every mode, in radians it goes through π
+/// context.options.angle = AngleMode::Radians;
+/// assert_eq!(context.eval("round(sin(30°), 9)").unwrap(), context.eval("0.5").unwrap());
+/// ```
+pub fn function<N: Number>(name: &str, mode: AngleMode) -> Option<Function<N>> {
+    match mode {
+        AngleMode::Radians => None,
+        AngleMode::Degrees => in_unit::<N, 360>(name),
+        AngleMode::Gradians => in_unit::<N, 400>(name),
+    }
+}
@@ -0,0 +1,538 @@
+//! Arbitrary precision numbers, enabled with the `bigdecimal` feature.
+//!
+//! Sums, differences, products and integer powers of integers are exact,
+//! so `2^100` keeps all of its digits, whatever the precision, up to a million bits.
+//! Everything else is rounded to the number of significant digits set with
+//! `set_precision`, and rounded results with more than ten times that many digits
+//! before the point are an overflow. The integer functions like `gcd` and `modpow`
+//! work on them too.
+
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    str::FromStr,
+    sync::atomic::{AtomicU64, Ordering as AtomicOrdering},
+};
+
+use bigdecimal::BigDecimal;
+use num_bigint::BigInt;
+use num_traits::{Signed, ToPrimitive, Zero};
+use once_cell::sync::Lazy;
+
+use crate::{
+    error::Error,
+    integer,
+    number::Number,
+    parse::NumberMode,
+    token::{functions, Function},
+};
+
+/// Significant digits results are rounded to, unless changed with `set_precision`
+pub const DEFAULT_PRECISION: u64 = 50;
+
+// Extra digits intermediate results are computed with
+const GUARD_DIGITS: u64 = 10;
+
+// Largest integer exponent, anything above gives numbers too big to be useful
+const MAX_EXPONENT: i64 = 100_000;
+
+// Rounded results with more than this many times the precision in digits before the point
+// are an overflow, they are too big to be useful and too slow to compute with
+const MAGNITUDE_LIMIT: u64 = 10;
+
+// Most bits of an exact integer power
+const MAX_BITS: f64 = 1_048_576.0;
+
+static PRECISION: AtomicU64 = AtomicU64::new(DEFAULT_PRECISION);
+
+static FUNCTIONS: Lazy<HashMap<&str, Function<BigDecimal>>> = Lazy::new(|| {
+    let mut map = functions();
+    map.extend(integer_functions());
+    map
+});
+
+/// Sets the number of significant digits of inexact results
+/// ## Example
+/// ```rust
+/// use bigdecimal::BigDecimal;
+/// use calc::{big, Context};
+///
+/// big::set_precision(30);
+/// let context = Context::<BigDecimal>::default();
+/// assert_eq!(context.eval("1/3").unwrap().to_string(), "0.333333333333333333333333333333");
+/// big::set_precision(big::DEFAULT_PRECISION);
+/// ```
+pub fn set_precision(digits: u64) {
+    PRECISION.store(digits.max(1), AtomicOrdering::Relaxed);
+}
+
+/// The number of significant digits of inexact results
+pub fn precision() -> u64 {
+    PRECISION.load(AtomicOrdering::Relaxed)
+}
+
+// The logarithm of a nonzero number, close enough to tell its size
+fn log10(x: &BigDecimal) -> f64 {
+    let (int, _) = x.abs().into_bigint_and_exponent();
+    let shift = x.digits() as i64 - 1;
+    let mantissa = BigDecimal::new(int, shift).to_f64().unwrap_or(1.0);
+    x.order_of_magnitude() as f64 + mantissa.log10()
+}
+
+// Whether a power of ten is past the magnitude of the largest results
+fn too_large(magnitude: i128) -> bool {
+    magnitude > i128::from(precision()) * i128::from(MAGNITUDE_LIMIT)
+}
+
+impl Number for BigDecimal {
+    fn from_literal(s: &str, _: NumberMode) -> Result<Self, Error> {
+        BigDecimal::from_str(s).map_err(|_| Error::NumberParseError)
+    }
+
+    fn from_integer(n: BigInt, _: NumberMode) -> Result<Self, Error> {
+        Ok(BigDecimal::from(n))
+    }
+
+    fn constant(name: &str) -> Option<Self> {
+        let p = precision();
+        match name {
+            "e" => Some(round(&exp(&BigDecimal::from(1), p + GUARD_DIGITS), p)),
+            "pi" | "π" => Some(round(&pi(p + GUARD_DIGITS), p)),
+            _ => None,
+        }
+    }
+
+    fn function(name: &str) -> Option<Function<Self>> {
+        FUNCTIONS.get(name).cloned()
+    }
+
+    fn checked_add(&self, rhs: &Self) -> Result<Self, Error> {
+        Ok(exact(self + rhs))
+    }
+
+    fn checked_sub(&self, rhs: &Self) -> Result<Self, Error> {
+        Ok(exact(self - rhs))
+    }
+
+    fn checked_mul(&self, rhs: &Self) -> Result<Self, Error> {
+        Ok(exact(self * rhs))
+    }
+
+    fn checked_div(&self, rhs: &Self) -> Result<Self, Error> {
+        if rhs.is_zero() {
+            return Err(Error::DivisionByZero);
+        }
+        // Keep quotients of integers exact when they divide evenly
+        if self.is_integer() && rhs.is_integer() {
+            let (a, b) = (integer(self), integer(rhs));
+            if (&a % &b).is_zero() {
+                return Ok(BigDecimal::from(a / b));
+            }
+        }
+        let p = precision();
+        Ok(round(&div(self, rhs, p + GUARD_DIGITS), p))
+    }
+
+    fn checked_pow(&self, rhs: &Self) -> Result<Self, Error> {
+        let p = precision();
+        if rhs.is_integer() {
+            let n = rhs.to_i64().ok_or(Error::Overflow)?;
+            if n.abs() > MAX_EXPONENT {
+                return Err(Error::Overflow);
+            }
+            if n < 0 && self.is_zero() {
+                return Err(Error::DivisionByZero);
+            }
+            // The power of ten of the result is n times the logarithm of the base,
+            // and exact powers are bounded by their bits instead
+            let magnitude = if self.is_zero() {
+                0.0
+            } else {
+                n as f64 * log10(self)
+            };
+            let exact = self.is_integer() && n >= 0;
+            if (exact && magnitude * std::f64::consts::LOG2_10 > MAX_BITS)
+                || (!exact && too_large(magnitude.ceil() as i128))
+            {
+                return Err(Error::Overflow);
+            }
+            let power = powi(self, n.unsigned_abs(), p + GUARD_DIGITS);
+            return Ok(if n < 0 {
+                round(&div(&BigDecimal::from(1), &power, p + GUARD_DIGITS), p)
+            } else if self.is_integer() {
+                power
+            } else {
+                round(&power, p)
+            });
+        }
+        if self.is_zero() {
+            return if rhs.is_positive() {
+                Ok(BigDecimal::zero())
+            } else {
+                Err(Error::DivisionByZero)
+            };
+        }
+        if self.is_negative() {
+            return Err(Error::DomainError);
+        }
+        let wp = p + GUARD_DIGITS;
+        let exponent = (rhs * ln(self, wp)).with_prec(wp);
+        Ok(round(&exp(&exponent, wp), p))
+    }
+
+    fn sqrt(&self) -> Result<Self, Error> {
+        let p = precision();
+        let root = self.sqrt_with_context(&context(p + GUARD_DIGITS));
+        root.map(|r| round(&r, p)).ok_or(Error::DomainError)
+    }
+
+    fn cbrt(&self) -> Result<Self, Error> {
+        let p = precision();
+        Ok(round(
+            &self.cbrt_with_context(&context(p + GUARD_DIGITS)),
+            p,
+        ))
+    }
+
+    fn abs(&self) -> Result<Self, Error> {
+        Ok(BigDecimal::abs(self))
+    }
+
+    fn sin(&self) -> Result<Self, Error> {
+        let p = precision();
+        Ok(round(&sin_cos(self, p + GUARD_DIGITS)?.0, p))
+    }
+
+    fn cos(&self) -> Result<Self, Error> {
+        let p = precision();
+        Ok(round(&sin_cos(self, p + GUARD_DIGITS)?.1, p))
+    }
+
+    fn tan(&self) -> Result<Self, Error> {
+        let p = precision();
+        let (sin, cos) = sin_cos(self, p + GUARD_DIGITS)?;
+        if cos.is_zero() {
+            return Err(Error::DomainError);
+        }
+        Ok(round(&div(&sin, &cos, p + GUARD_DIGITS), p))
+    }
+
+    fn cmp_real(&self, other: &Self) -> Result<Ordering, Error> {
+        Ok(self.cmp(other))
+    }
+
+    fn exp(&self) -> Result<Self, Error> {
+        // e^x overflows anything useful well before this
+        if self.abs() > MAX_EXPONENT {
+            return Err(Error::Overflow);
+        }
+        let p = precision();
+        Ok(round(&exp(self, p + GUARD_DIGITS), p))
+    }
+
+    fn ln(&self) -> Result<Self, Error> {
+        if !self.is_positive() {
+            return Err(Error::DomainError);
+        }
+        let p = precision();
+        Ok(round(&ln(self, p + GUARD_DIGITS), p))
+    }
+
+    fn atan(&self) -> Result<Self, Error> {
+        let p = precision();
+        Ok(round(&atan(self, p + GUARD_DIGITS), p))
+    }
+
+    fn floor(&self) -> Result<Self, Error> {
+        Ok(self.with_scale_round(0, bigdecimal::RoundingMode::Floor))
+    }
+}
+
+fn context(digits: u64) -> bigdecimal::Context {
+    bigdecimal::Context::default()
+        .with_prec(digits.max(1))
+        .unwrap()
+}
+
+// Round to the given number of significant digits, without trailing zeros
+fn round(x: &BigDecimal, digits: u64) -> BigDecimal {
+    x.with_prec(digits).normalized()
+}
+
+// Integers stay exact, anything else is rounded to the precision
+fn exact(x: BigDecimal) -> BigDecimal {
+    let p = precision();
+    if x.is_integer() || x.digits() <= p {
+        x
+    } else {
+        round(&x, p)
+    }
+}
+
+// The digits of an integral BigDecimal as a BigInt
+fn integer(x: &BigDecimal) -> BigInt {
+    x.with_scale(0).into_bigint_and_exponent().0
+}
+
+// An argument of the integer functions, anything with a fractional part is an error
+fn to_integer(x: &BigDecimal) -> Result<BigInt, Error> {
+    if x.is_integer() {
+        Ok(integer(x))
+    } else {
+        Err(Error::ExpectedInteger)
+    }
+}
+
+// The integer arguments of a variadic function, which needs at least one
+fn integers(args: Vec<BigDecimal>) -> Result<Vec<BigInt>, Error> {
+    if args.is_empty() {
+        return Err(Error::NotEnoughArgs);
+    }
+    args.iter().map(to_integer).collect()
+}
+
+// Exactly `COUNT` integer arguments, for the functions with more than two parameters
+fn arguments<const COUNT: usize>(args: Vec<BigDecimal>) -> Result<[BigInt; COUNT], Error> {
+    if args.len() < COUNT {
+        return Err(Error::NotEnoughArgs);
+    } else if args.len() > COUNT {
+        return Err(Error::TooManyArgs);
+    }
+    let integers = args
+        .iter()
+        .map(to_integer)
+        .collect::<Result<Vec<_>, Error>>()?;
+    Ok(integers.try_into().unwrap())
+}
+
+// The combinatorics and number theory functions of `token::INTEGER_FUNCTIONS`,
+// except `factor`, whose list of factors these numbers can't hold
+fn integer_functions() -> HashMap<&'static str, Function<BigDecimal>> {
+    let mut map = HashMap::new();
+    map.insert(
+        "gcd",
+        Function::Variadic(|args| Ok(integer::gcd(&integers(args)?).into()), 0),
+    );
+    map.insert(
+        "lcm",
+        Function::Variadic(|args| Ok(integer::lcm(&integers(args)?).into()), 0),
+    );
+    let ncr = Function::TwoParam(|k: BigDecimal, n: BigDecimal| {
+        integer::ncr(&to_integer(&n)?, &to_integer(&k)?).map(BigDecimal::from)
+    });
+    map.insert("ncr", ncr.clone());
+    map.insert("choose", ncr);
+    map.insert(
+        "npr",
+        Function::TwoParam(|k: BigDecimal, n: BigDecimal| {
+            integer::npr(&to_integer(&n)?, &to_integer(&k)?).map(BigDecimal::from)
+        }),
+    );
+    map.insert(
+        "isprime",
+        Function::OneParam(
+            |n: BigDecimal| Ok(u8::from(integer::is_prime(&to_integer(&n)?)).into()),
+        ),
+    );
+    map.insert(
+        "nextprime",
+        Function::OneParam(|n: BigDecimal| Ok(integer::next_prime(&to_integer(&n)?).into())),
+    );
+    map.insert(
+        "totient",
+        Function::OneParam(|n: BigDecimal| {
+            integer::totient(&to_integer(&n)?).map(BigDecimal::from)
+        }),
+    );
+    map.insert(
+        "modpow",
+        Function::Variadic(
+            |args| {
+                let [base, exponent, modulus] = arguments(args)?;
+                integer::modpow(&base, &exponent, &modulus).map(BigDecimal::from)
+            },
+            0,
+        ),
+    );
+    map.insert(
+        "modinv",
+        Function::TwoParam(|m: BigDecimal, a: BigDecimal| {
+            integer::modinv(&to_integer(&a)?, &to_integer(&m)?).map(BigDecimal::from)
+        }),
+    );
+    map
+}
+
+fn div(a: &BigDecimal, b: &BigDecimal, digits: u64) -> BigDecimal {
+    let ctx = context(digits);
+    a.mul_with_context(&b.inverse_with_context(&ctx), &ctx)
+}
+
+// Absolute tolerance of 10^-digits, to stop series
+fn epsilon(digits: u64) -> BigDecimal {
+    BigDecimal::new(BigInt::from(1), digits as i64)
+}
+
+// Power by squaring, exact for integers
+fn powi(x: &BigDecimal, mut n: u64, digits: u64) -> BigDecimal {
+    let limit = |y: BigDecimal| {
+        if x.is_integer() {
+            y
+        } else {
+            y.with_prec(digits)
+        }
+    };
+    let mut result = BigDecimal::from(1);
+    let mut base = x.clone();
+    while n > 0 {
+        if n & 1 == 1 {
+            result = limit(&result * &base);
+        }
+        n >>= 1;
+        if n > 0 {
+            base = limit(base.square());
+        }
+    }
+    result
+}
+
+// Machin's formula, π = 16 atan(1/5) - 4 atan(1/239)
+fn pi(digits: u64) -> BigDecimal {
+    // atan(1/n) = 1/n - 1/(3n³) + 1/(5n⁵) - …
+    let atan_inv = |n: u32| {
+        let eps = epsilon(digits + 2);
+        let n2 = BigDecimal::from(n * n);
+        let mut power = div(&BigDecimal::from(1), &BigDecimal::from(n), digits + 2);
+        let mut sum = BigDecimal::zero();
+        let mut k = 0u64;
+        while power > eps {
+            let term = div(&power, &BigDecimal::from(2 * k + 1), digits + 2);
+            sum = if k.is_multiple_of(2) {
+                sum + term
+            } else {
+                sum - term
+            };
+            power = div(&power, &n2, digits + 2);
+            k += 1;
+        }
+        sum
+    };
+    (atan_inv(5) * BigDecimal::from(16) - atan_inv(239) * BigDecimal::from(4)).with_prec(digits)
+}
+
+// Taylor series, after halving the argument until it is small and squaring the result back
+fn exp(x: &BigDecimal, digits: u64) -> BigDecimal {
+    let half = BigDecimal::new(BigInt::from(5), 1);
+    let mut r = x.clone();
+    let mut halvings = 0u64;
+    while r.abs() > half {
+        r = r.half();
+        halvings += 1;
+    }
+    // Every squaring loses a bit of precision
+    let wp = digits + halvings / 3 + 2;
+    let eps = epsilon(wp);
+    let mut sum = BigDecimal::from(1);
+    let mut term = BigDecimal::from(1);
+    let mut n = 1u64;
+    while term.abs() > eps {
+        term = div(&(&term * &r), &BigDecimal::from(n), wp);
+        sum += &term;
+        n += 1;
+    }
+    for _ in 0..halvings {
+        sum = sum.square().with_prec(wp);
+    }
+    sum.with_prec(digits)
+}
+
+// Natural logarithm of a positive number, x = m × 10^k with 1 <= m < 10,
+// so ln(x) = ln(m) + k ln(10), and ln(m) is found with Newton's method on `exp`
+fn ln(x: &BigDecimal, digits: u64) -> BigDecimal {
+    let wp = digits + 2;
+    let ln_small = |m: &BigDecimal| {
+        let eps = epsilon(wp);
+        let mut y = BigDecimal::from_str(&m.to_f64().unwrap_or(1.0).ln().to_string()).unwrap();
+        for _ in 0..100 {
+            let e = exp(&y, wp);
+            let delta = div(&((m - &e) * BigDecimal::from(2)), &(m + &e), wp);
+            y += &delta;
+            if delta.abs() <= eps {
+                break;
+            }
+        }
+        y
+    };
+    let (int, scale) = x.as_bigint_and_exponent();
+    let shift = x.digits() as i64 - 1;
+    let m = BigDecimal::new(int, shift);
+    let k = shift - scale;
+    let mut result = ln_small(&m);
+    if k != 0 {
+        result += ln_small(&BigDecimal::from(10)) * BigDecimal::from(k);
+    }
+    result.with_prec(digits)
+}
+
+// Sine and cosine with the argument reduced to [-π, π], using their Taylor series
+fn sin_cos(x: &BigDecimal, digits: u64) -> Result<(BigDecimal, BigDecimal), Error> {
+    // Large arguments need more digits of π for the reduction
+    let magnitude = x.order_of_magnitude().max(0) as u64;
+    if too_large(magnitude.into()) {
+        return Err(Error::Overflow);
+    }
+    let wp = digits + magnitude + 2;
+    let two_pi = pi(wp).double();
+    let turns = div(x, &two_pi, wp).round(0);
+    let r = (x - &turns * &two_pi).with_prec(wp);
+    let eps = epsilon(wp);
+    let r2 = r.square().with_prec(wp);
+    let (mut sin, mut cos) = (r.clone(), BigDecimal::from(1));
+    let (mut sin_term, mut cos_term) = (r, BigDecimal::from(1));
+    let mut n = 1u64;
+    while sin_term.abs() > eps || cos_term.abs() > eps {
+        // Each term is the previous one times -r² / ((n + 1)(n + 2)) for the sine,
+        // and times -r² / (n(n + 1)) for the cosine
+        sin_term = -div(&(&sin_term * &r2), &BigDecimal::from((n + 1) * (n + 2)), wp);
+        cos_term = -div(&(&cos_term * &r2), &BigDecimal::from(n * (n + 1)), wp);
+        sin += &sin_term;
+        cos += &cos_term;
+        n += 2;
+    }
+    Ok((sin.with_prec(digits), cos.with_prec(digits)))
+}
+
+// Taylor series, x - x³/3 + x⁵/5 - …, after shrinking the argument:
+// arguments above 1 use atan(x) = π/2 - atan(1/x), and then
+// atan(x) = 2 atan(x / (1 + √(1 + x²))) halves it until it is small
+fn atan(x: &BigDecimal, digits: u64) -> BigDecimal {
+    let wp = digits + 4;
+    let one = BigDecimal::from(1);
+    if x.abs() > one {
+        let half_pi = pi(wp).half();
+        let rest = atan(&div(&one, &x.abs(), wp), wp);
+        let result = half_pi - rest;
+        return if x.is_negative() { -result } else { result }.with_prec(digits);
+    }
+    let small = BigDecimal::new(BigInt::from(1), 1);
+    let mut r = x.clone();
+    let mut doublings = 0u32;
+    while r.abs() > small {
+        let root = (&one + r.square())
+            .sqrt_with_context(&context(wp))
+            .unwrap_or_else(|| one.clone());
+        r = div(&r, &(&one + root), wp);
+        doublings += 1;
+    }
+    let eps = epsilon(wp);
+    let r2 = r.square().with_prec(wp);
+    let mut power = r.clone();
+    let mut sum = r;
+    let mut n = 1u64;
+    while power.abs() > eps {
+        power = -(&power * &r2).with_prec(wp);
+        sum += div(&power, &BigDecimal::from(2 * n + 1), wp);
+        n += 1;
+    }
+    (sum * BigDecimal::from(1u64 << doublings)).with_prec(digits)
+}
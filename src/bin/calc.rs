@@ -1,39 +1,30 @@
-use calc::*;
-use rust_decimal::Decimal;
+use calc::{
+    angle::AngleMode,
+    calculus, constants,
+    context::Context,
+    diff,
+    expr::Expr,
+    finance,
+    format::{format_value, ComplexFormat, FormatOptions, FractionFormat, Radix},
+    integer,
+    interval::Interval,
+    parse::NumberMode,
+    plot::{self, Style, SvgOptions, TerminalOptions},
+    roots,
+    simplify::simplify,
+    uncertain::Uncertain,
+    units, Number,
+};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
 use std::{
-    collections::HashMap,
     io::{stdin, stdout, BufRead, BufReader, BufWriter, Write},
+    ops::RangeInclusive,
 };
 
 const RESTRICTED_CHARS: &[char] = &[
     ' ', '\t', '\n', '+', '-', '/', '*', '^', '%', '!', '~', '&', '|', '<', '>', '=',
 ];
 
-fn calculate(s: &str, variables: Option<&HashMap<String, Decimal>>) -> Result<Decimal, Error> {
-    let tokens = parse_str(s, variables);
-    let tokens = match tokens {
-        Ok(tokens) => tokens,
-        Err(err) => {
-            return Err(err);
-        }
-    };
-    let rpn = shunting_yard(tokens);
-    let rpn = match rpn {
-        Ok(rpn) => rpn,
-        Err(err) => {
-            return Err(err);
-        }
-    };
-    let result = evaluate_rpn(rpn);
-    let result = match result {
-        Ok(result) => result,
-        Err(err) => {
-            return Err(err);
-        }
-    };
-    Ok(result)
-}
-
 fn main() {
     // Lock and buffer both stdin and stdout
     let stdout = stdout();
@@ -42,53 +33,507 @@ fn main() {
     let stdin_locked = stdin.lock();
     let mut stdout = BufWriter::new(stdout_locked);
     let mut stdin = BufReader::new(stdin_locked);
-    let mut variables: HashMap<String, Decimal> = HashMap::new();
+    let mut context = Context::new();
+    let mut format = FormatOptions::default();
+    let mut plot_options = TerminalOptions::default();
+    // Interval evaluation, with its own variables
+    let mut intervals: Option<Context<Interval>> = None;
+    // Uncertainty propagation, with its own variables
+    let mut uncertain: Option<Context<Uncertain>> = None;
+    // Arbitrary precision evaluation, with its own variables
+    #[cfg(feature = "bigdecimal")]
+    let mut big: Option<Context<bigdecimal::BigDecimal>> = None;
     loop {
         write!(&mut stdout, ">").ok();
         stdout.flush().ok();
         let mut input = String::new();
-        stdin.read_line(&mut input).ok();
+        if let Ok(0) | Err(_) = stdin.read_line(&mut input) {
+            break;
+        }
         let input = input.trim_start().trim_end().to_string();
         // Handle inbuilt functions
         if input == "exit" || input == "quit" {
             break;
         } else if input == "list" {
-            for (key, value) in variables.iter() {
+            for (key, value) in context.variables.iter() {
+                writeln!(&mut stdout, "{} = {}", key, format_value(value, &format)).ok();
+            }
+            #[cfg(feature = "bigdecimal")]
+            for (key, value) in big.iter().flat_map(|big| big.variables.iter()) {
                 writeln!(&mut stdout, "{} = {}", key, value).ok();
             }
-        } else if input.as_str().contains('=') {
-            let mut iter = input.split('=');
-            let variable_name = iter.next().unwrap().to_string();
-            let variable_name = variable_name.trim_start().trim_end();
-            for ch in RESTRICTED_CHARS {
-                if variable_name.contains(*ch) {
-                    writeln!(&mut stdout, "Invalid variable name.").ok();
-                    continue;
+        } else if input == "constants" {
+            for constant in constants::CONSTANTS {
+                let value = format!("{} {}", constant.value, constant.unit);
+                writeln!(
+                    &mut stdout,
+                    "{:<11} {:<36} {}",
+                    constant.name,
+                    value.trim_end(),
+                    constant.description
+                )
+                .ok();
+            }
+        } else if let Some(name) = input.strip_prefix(":base") {
+            // Change the base results are written in
+            match Radix::from_name(name.trim()) {
+                Some(r) => format.radix = r,
+                None => {
+                    writeln!(&mut stdout, "Unknown base, use one of: dec, hex, oct, bin.").ok();
                 }
             }
-            let variable_value = iter.next().unwrap().to_string();
-            if iter.next().is_some() {
-                writeln!(&mut stdout, "Invalid assignment.").ok();
-                continue;
+        } else if let Some(style) = input.strip_prefix(":plot") {
+            // Draw plots with braille dots or with ASCII characters
+            match style.trim() {
+                "braille" => plot_options.style = Style::Braille,
+                "ascii" => plot_options.style = Style::Ascii,
+                _ => {
+                    writeln!(&mut stdout, "Use :plot braille or :plot ascii.").ok();
+                }
             }
-            let variable_value = match calculate(&variable_value, Some(&variables)) {
-                Ok(variable_value) => variable_value,
-                Err(err) => {
-                    writeln!(&mut stdout, "{}", err).ok();
-                    continue;
+        } else if input == ":polar" {
+            format.complex = ComplexFormat::Polar;
+        } else if input == ":rect" {
+            format.complex = ComplexFormat::Rectangular;
+        } else if let Some(mode) = input.strip_prefix(':').and_then(AngleMode::from_name) {
+            // The unit of angles, `:deg`, `:rad` or `:grad`, in every context
+            context.options.angle = mode;
+            if let Some(intervals) = &mut intervals {
+                intervals.options.angle = mode;
+            }
+            if let Some(uncertain) = &mut uncertain {
+                uncertain.options.angle = mode;
+            }
+            #[cfg(feature = "bigdecimal")]
+            if let Some(big) = &mut big {
+                big.options.angle = mode;
+            }
+        } else if let Some(flag) = input.strip_prefix(":strict") {
+            // Turn implicit multiplication off or back on
+            match flag.trim() {
+                "on" => context.options.implicit_multiplication = false,
+                "off" => context.options.implicit_multiplication = true,
+                _ => {
+                    writeln!(&mut stdout, "Use :strict on or :strict off.").ok();
+                }
+            }
+        } else if let Some(flag) = input.strip_prefix(":exact") {
+            // Read numbers as exact fractions
+            match flag.trim() {
+                "on" => context.options.mode = NumberMode::Rational,
+                "off" => context.options.mode = NumberMode::Decimal,
+                _ => {
+                    writeln!(&mut stdout, "Use :exact on or :exact off.").ok();
                 }
+            }
+        } else if let Some(name) = input.strip_prefix(":fraction") {
+            // Change how exact fractions are written
+            match name.trim() {
+                "improper" => format.fraction = FractionFormat::Improper,
+                "mixed" => format.fraction = FractionFormat::Mixed,
+                "decimal" => format.fraction = FractionFormat::Decimal,
+                _ => {
+                    writeln!(&mut stdout, "Use :fraction improper, mixed or decimal.").ok();
+                }
+            }
+        } else if cfg!(feature = "bigdecimal") && input.starts_with(":big") {
+            #[cfg(feature = "bigdecimal")]
+            match input[4..].trim() {
+                "on" => big = Some(alternative(&context)),
+                "off" => big = None,
+                _ => {
+                    writeln!(&mut stdout, "Use :big on or :big off.").ok();
+                }
+            }
+        } else if cfg!(feature = "bigdecimal") && input.starts_with(":precision") {
+            // Significant digits of arbitrary precision results
+            #[cfg(feature = "bigdecimal")]
+            match input[10..].trim().parse::<u64>() {
+                Ok(digits) if digits > 0 => calc::big::set_precision(digits),
+                _ => {
+                    writeln!(&mut stdout, "Use :precision <digits>.").ok();
+                }
+            }
+        } else if let Some(flag) = input.strip_prefix(":interval") {
+            // Evaluate with intervals, binding variables with `x ∈ [1.9, 2.1]`
+            match flag.trim() {
+                "on" => intervals = Some(alternative(&context)),
+                "off" => intervals = None,
+                _ => {
+                    writeln!(&mut stdout, "Use :interval on or :interval off.").ok();
+                }
+            }
+        } else if let Some(flag) = input.strip_prefix(":uncertainty") {
+            // Evaluate values with uncertainties, like `9.81 ± 0.02`
+            match flag.trim() {
+                "on" => uncertain = Some(alternative(&context)),
+                "off" => uncertain = None,
+                _ => {
+                    writeln!(&mut stdout, "Use :uncertainty on or :uncertainty off.").ok();
+                }
+            }
+        } else if let Some(path) = input.strip_prefix(":units") {
+            // Load unit definitions from a file, one `name = definition` per line
+            let message = match std::fs::read_to_string(path.trim()) {
+                Ok(source) => match units::load(&source) {
+                    Ok(count) => format!("Loaded {} units.", count),
+                    Err(err) => err.to_string(),
+                },
+                Err(err) => format!("Cannot read {}: {}", path.trim(), err),
             };
-            variables.insert(variable_name.to_string(), variable_value);
-        } else {
-            let result = calculate(&input, Some(&variables));
-            match result {
-                Ok(result) => {
-                    writeln!(&mut stdout, "{}", result).ok();
+            writeln!(&mut stdout, "{}", message).ok();
+        } else if let Some(definition) = input.strip_prefix(":unit") {
+            // Define a single unit, `:unit furlong = 201.168 m`
+            let result = match definition.split_once('=') {
+                Some((name, value)) => units::define(name.trim(), value.trim()),
+                None => Err(calc::Error::InvalidUnitDefinition(
+                    definition.trim().to_string(),
+                )),
+            };
+            if let Err(err) = result {
+                writeln!(&mut stdout, "{}", err).ok();
+            }
+        } else if let Some(args) = input
+            .strip_prefix("amort")
+            .filter(|rest| rest.trim_start().starts_with('('))
+        {
+            // The schedule of a loan, `amort(principal, rate, n)` with the rate per period
+            match amortization(&context, args) {
+                Ok(schedule) => {
+                    writeln!(
+                        &mut stdout,
+                        "{:>6} {:>14} {:>14} {:>14} {:>16}",
+                        "Period", "Payment", "Interest", "Principal", "Balance"
+                    )
+                    .ok();
+                    for row in &schedule {
+                        writeln!(
+                            &mut stdout,
+                            "{:>6} {:>14} {:>14} {:>14} {:>16}",
+                            row.period,
+                            row.payment.round_dp(2),
+                            row.interest.round_dp(2),
+                            row.principal.round_dp(2),
+                            row.balance.round_dp(2)
+                        )
+                        .ok();
+                    }
+                    let interest: Decimal = schedule.iter().map(|r| r.interest).sum();
+                    writeln!(&mut stdout, "Total interest: {}", interest.round_dp(2)).ok();
                 }
                 Err(err) => {
                     writeln!(&mut stdout, "{}", err).ok();
                 }
             }
+        } else if let Some(args) = input
+            .strip_prefix("diff")
+            .filter(|rest| rest.trim_start().starts_with('('))
+        {
+            // The symbolic derivative, `diff(x^2 * sin(x), x)`
+            match derivative(&context, args) {
+                Ok(derivative) => writeln!(&mut stdout, "{}", derivative).ok(),
+                Err(err) => writeln!(&mut stdout, "{}", err).ok(),
+            };
+        } else if let Some(args) = input
+            .strip_prefix("solve")
+            .filter(|rest| is_equation(&context, rest))
+        {
+            // The roots of an equation, `solve(x^2 = 2, x)`, near a guess or in an interval
+            match solutions(&context, args) {
+                Ok((var, roots)) if roots.is_empty() => {
+                    writeln!(&mut stdout, "No solutions for {}", var).ok()
+                }
+                Ok((var, roots)) => roots
+                    .iter()
+                    .try_for_each(|root| writeln!(&mut stdout, "{} = {}", var, root))
+                    .ok(),
+                Err(err) => writeln!(&mut stdout, "{}", err).ok(),
+            };
+        } else if let Some(args) = input
+            .strip_prefix("integrate")
+            .filter(|rest| arguments(rest).is_ok_and(|args| args.len() == 4))
+        {
+            // A whole line integral is written with its error estimate, `integrate(sin(x), x, 0, pi)`
+            let integral = exactly(args).and_then(|[body, var, from, to]| {
+                calculus::integral(&context, body, var, from, to)
+            });
+            match integral {
+                Ok((area, error)) => {
+                    // The estimate only needs its first digits, `± 4.8e-16`
+                    let error = match error.as_real().ok().and_then(|e| e.to_f64()) {
+                        Some(e) if e != 0.0 => format!("{:.1e}", e),
+                        _ => "0".to_string(),
+                    };
+                    writeln!(&mut stdout, "{} ± {}", format_value(&area, &format), error).ok()
+                }
+                Err(err) => writeln!(&mut stdout, "{}", err).ok(),
+            };
+        } else if let Some(args) = input
+            .strip_prefix("simplify")
+            .filter(|rest| rest.trim_start().starts_with('('))
+        {
+            // The expression written more simply, `simplify(x * x + 2x - x)` is `x^2 + x`
+            let simplified = exactly(args)
+                .and_then(|[expr]| Expr::parse(expr, &context.options))
+                .map(|expr| simplify(&expr));
+            match simplified {
+                Ok(simplified) => writeln!(&mut stdout, "{}", simplified).ok(),
+                Err(err) => writeln!(&mut stdout, "{}", err).ok(),
+            };
+        } else if let Some(args) = input
+            .strip_prefix("plot")
+            .filter(|rest| rest.starts_with([' ', '(']))
+        {
+            // A chart of expressions in a variable, `plot sin(x), cos(x), x, -pi, pi`,
+            // or an SVG image of it with `> plot.svg` after
+            let file = args
+                .rsplit_once('>')
+                .filter(|(_, path)| path.trim().ends_with(".svg"));
+            let output = match file {
+                Some((args, path)) => {
+                    let svg = plot_arguments(&context, args).and_then(|(exprs, var, range)| {
+                        let options = SvgOptions::default();
+                        plot::plot_svg(&exprs, &var, range, &context.variables, &options)
+                    });
+                    match svg.map(|svg| std::fs::write(path.trim(), svg)) {
+                        Ok(Ok(())) => format!("Wrote {}.\n", path.trim()),
+                        Ok(Err(err)) => format!("Cannot write {}: {}\n", path.trim(), err),
+                        Err(err) => format!("{}\n", err),
+                    }
+                }
+                None => plot_arguments(&context, args)
+                    .and_then(|(exprs, var, range)| {
+                        plot::plot_terminal(&exprs, &var, range, &context.variables, &plot_options)
+                    })
+                    .unwrap_or_else(|err| format!("{}\n", err)),
+            };
+            write!(&mut stdout, "{}", output).ok();
+        } else if let Some(expr) = input
+            .strip_prefix("factor")
+            .filter(|rest| rest.starts_with([' ', '(']))
+        {
+            // The prime factorization of an integer, `factor 360` is `2^3 × 3^2 × 5`
+            let factors = context
+                .eval(expr)
+                .and_then(|n| integer::to_integer(&n))
+                .and_then(|n| integer::factor(&n));
+            match factors {
+                Ok(factors) => writeln!(&mut stdout, "{}", factors).ok(),
+                Err(err) => writeln!(&mut stdout, "{}", err).ok(),
+            };
+        } else {
+            #[cfg(feature = "bigdecimal")]
+            if let Some(big) = &mut big {
+                if let Some(output) = execute(big, &input, |x| x.to_string()) {
+                    writeln!(&mut stdout, "{}", output).ok();
+                }
+                continue;
+            }
+            let output = if let Some(intervals) = &mut intervals {
+                execute(intervals, &interval_binding(&input), |x| x.to_string())
+            } else if let Some(uncertain) = &mut uncertain {
+                execute(uncertain, &input, |x| x.to_string())
+            } else {
+                execute(&mut context, &input, |x| format_value(x, &format))
+            };
+            if let Some(output) = output {
+                writeln!(&mut stdout, "{}", output).ok();
+            }
+        }
+    }
+}
+
+// The arguments of a call like `(a, b)`, split at the commas that are not inside brackets
+fn arguments(args: &str) -> Result<Vec<&str>, calc::Error> {
+    let args = args
+        .trim()
+        .strip_prefix('(')
+        .and_then(|args| args.strip_suffix(')'))
+        .ok_or(calc::Error::UnbalancedParens)?;
+    Ok(split(args))
+}
+
+// Splits a list like `a, b` at the commas that are not inside brackets
+fn split(args: &str) -> Vec<&str> {
+    let mut arguments = Vec::new();
+    let (mut depth, mut start) = (0, 0);
+    for (i, c) in args.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                arguments.push(&args[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    arguments.push(&args[start..]);
+    arguments
+}
+
+// Exactly `COUNT` arguments
+fn exactly<const COUNT: usize>(args: &str) -> Result<[&str; COUNT], calc::Error> {
+    let args = arguments(args)?;
+    let count = args.len();
+    args.try_into().map_err(|_| {
+        if count < COUNT {
+            calc::Error::NotEnoughArgs
+        } else {
+            calc::Error::TooManyArgs
+        }
+    })
+}
+
+// Evaluates the arguments of `amort(principal, rate, n)` and computes the schedule
+fn amortization(context: &Context, args: &str) -> Result<Vec<finance::Installment>, calc::Error> {
+    let [principal, rate, n] = exactly(args)?.map(|arg| context.eval(arg));
+    let (principal, rate, n) = (principal?.as_real()?, rate?.as_real()?, n?.as_real()?);
+    let n = n
+        .to_u32()
+        .filter(|_| n.fract().is_zero())
+        .ok_or(calc::Error::ExpectedInteger)?;
+    finance::amortization(principal, rate, n)
+}
+
+// Differentiates the expression of `diff(expr, x)` with respect to the variable, simplified
+fn derivative(context: &Context, args: &str) -> Result<Expr, calc::Error> {
+    let [expr, var] = exactly(args)?;
+    let expr = Expr::parse(expr, &context.options)?;
+    diff::derivative(&expr, &variable(context, var)?).map(|d| simplify(&d))
+}
+
+// Whether `solve(...)` is an equation in a variable rather than the linear system `solve(A, b)`,
+// which it is when it has an `=` or its second argument is a name without a value
+fn is_equation(context: &Context, args: &str) -> bool {
+    let Ok(args) = arguments(args) else {
+        return false;
+    };
+    let unbound = |arg: &str| match Expr::parse(arg, &context.options) {
+        Ok(Expr::Variable(name)) => !context.variables.contains_key(&name),
+        _ => false,
+    };
+    args[0].contains('=') || args.get(1).is_some_and(|arg| unbound(arg))
+}
+
+// The name of the variable of `solve`, `diff` or `plot`, an error for anything else
+fn variable(context: &Context, arg: &str) -> Result<String, calc::Error> {
+    match Expr::parse(arg, &context.options)? {
+        Expr::Variable(name) => Ok(name),
+        _ => Err(calc::Error::ExpectedVariable(arg.trim().to_string())),
+    }
+}
+
+// Solves `solve(equation, x)` near 0, `solve(equation, x, guess)` near the guess,
+// or `solve(equation, x, from, to)` for all the roots in between
+fn solutions(context: &Context, args: &str) -> Result<(String, Vec<Decimal>), calc::Error> {
+    let args = arguments(args)?;
+    let (equation, var, bounds) = match &args[..] {
+        [equation, var, bounds @ ..] if bounds.len() <= 2 => (equation, var.trim(), bounds),
+        [_] => return Err(calc::Error::NotEnoughArgs),
+        _ => return Err(calc::Error::TooManyArgs),
+    };
+    let equation = roots::equation(equation, &context.options)?;
+    let var = variable(context, var)?;
+    let bounds = bounds
+        .iter()
+        .map(|arg| context.eval(arg)?.as_real())
+        .collect::<Result<Vec<_>, calc::Error>>()?;
+    let roots = match bounds[..] {
+        [from, to] => roots::roots(&equation, &var, from, to, &context.variables)?,
+        [guess] => vec![roots::root(&equation, &var, guess, &context.variables)?],
+        _ => vec![roots::root(
+            &equation,
+            &var,
+            Decimal::ZERO,
+            &context.variables,
+        )?],
+    };
+    Ok((var, roots))
+}
+
+// The arguments of `plot sin(x), cos(x), x, -pi, pi`, the expressions, the variable and its range,
+// written with or without brackets around them
+fn plot_arguments(
+    context: &Context,
+    args: &str,
+) -> Result<(Vec<Expr>, String, RangeInclusive<f64>), calc::Error> {
+    let args = match split(args)[..] {
+        [_] => arguments(args)?,
+        _ => split(args),
+    };
+    let [exprs @ .., var, from, to] = &args[..] else {
+        return Err(calc::Error::NotEnoughArgs);
+    };
+    if exprs.is_empty() {
+        return Err(calc::Error::NotEnoughArgs);
+    }
+    let var = variable(context, var)?;
+    let bound = |arg: &str| -> Result<f64, calc::Error> {
+        let x = context.eval(arg)?.as_real()?;
+        x.to_f64().ok_or(calc::Error::DomainError)
+    };
+    let exprs = exprs
+        .iter()
+        .map(|expr| Expr::parse(expr, &context.options))
+        .collect::<Result<Vec<_>, calc::Error>>()?;
+    Ok((exprs, var, bound(from)?..=bound(to)?))
+}
+
+// A context for another number type, in the angle mode of the main one
+fn alternative<N>(context: &Context) -> Context<N> {
+    let mut alternative = Context::default();
+    alternative.options.angle = context.options.angle;
+    alternative
+}
+
+// Binds a variable for an assignment, otherwise evaluates the expression.
+// Returns what should be written out.
+fn execute<N: Number>(
+    context: &mut Context<N>,
+    input: &str,
+    show: impl Fn(&N) -> String,
+) -> Option<String> {
+    let Some((variable_name, variable_value)) = input.split_once('=') else {
+        return Some(match context.eval(input) {
+            Ok(result) => show(&result),
+            Err(err) => err.to_string(),
+        });
+    };
+    let variable_name = variable_name.trim();
+    if variable_name.contains(RESTRICTED_CHARS) {
+        return Some("Invalid variable name.".to_string());
+    }
+    // Constants are read before variables, so a variable of the same name would never be seen
+    if N::constant(variable_name).is_some() || constants::lookup(variable_name).is_some() {
+        return Some(format!(
+            "{} is a constant and cannot be assigned.",
+            variable_name
+        ));
+    }
+    if variable_value.contains('=') {
+        return Some("Invalid assignment.".to_string());
+    }
+    match context.eval(variable_value) {
+        Ok(value) => {
+            context.set(variable_name, value);
+            None
+        }
+        Err(err) => Some(err.to_string()),
+    }
+}
+
+// Turns `x ∈ [1.9, 2.1]` into the assignment `x = interval(1.9, 2.1)`
+fn interval_binding(input: &str) -> String {
+    match input.split_once('∈') {
+        Some((name, range)) => {
+            let range = range.trim();
+            let range = range
+                .strip_prefix('[')
+                .and_then(|r| r.strip_suffix(']'))
+                .unwrap_or(range);
+            format!("{} = interval({})", name.trim(), range)
         }
+        None => input.to_string(),
     }
 }
@@ -0,0 +1,312 @@
+//! Sums, products and integrals over a bound variable, like `sum(k^2, k, 1, 100)`.
+//! The first argument is not evaluated with the call, it is parsed on its own
+//! and evaluated with the variable bound to each value.
+
+use std::collections::HashMap;
+
+use crate::{
+    context::Context,
+    error::Error,
+    execute::evaluate_rpn,
+    number::{integer, Number},
+    parse::{parse_as, ParseOptions},
+    rpn::shunting_yard,
+    token::Token,
+};
+
+// The most terms of a sum or product, so a typo in a bound can't hang the calculator
+const MAX_TERMS: usize = 100_000;
+// The most times the integrand is evaluated, and intervals are halved, before settling for the error estimate
+const MAX_EVALUATIONS: usize = 100_000;
+const MAX_DEPTH: usize = 40;
+// The relative tolerance of integrals, 10^-15
+const TOLERANCE_DIGITS: i64 = 15;
+
+/// The sum of `f(k)` for `k` from `from` to `to` in steps of 1, 0 if there are none
+pub fn sum<N: Number>(
+    mut f: impl FnMut(&N) -> Result<N, Error>,
+    from: N,
+    to: N,
+) -> Result<N, Error> {
+    terms(from, to, integer(0)?, |total, k| total.checked_add(&f(k)?))
+}
+
+/// The product of `f(k)` for `k` from `from` to `to` in steps of 1, 1 if there are none
+pub fn prod<N: Number>(
+    mut f: impl FnMut(&N) -> Result<N, Error>,
+    from: N,
+    to: N,
+) -> Result<N, Error> {
+    terms(from, to, integer(1)?, |total, k| total.checked_mul(&f(k)?))
+}
+
+fn terms<N: Number>(
+    from: N,
+    to: N,
+    mut total: N,
+    mut next: impl FnMut(N, &N) -> Result<N, Error>,
+) -> Result<N, Error> {
+    let one = integer::<N>(1)?;
+    let mut k = from;
+    for _ in 0..MAX_TERMS {
+        if k.cmp_real(&to)?.is_gt() {
+            return Ok(total);
+        }
+        total = next(total, &k)?;
+        k = k.checked_add(&one)?;
+    }
+    match k.cmp_real(&to)?.is_gt() {
+        true => Ok(total),
+        false => Err(Error::TooManyTerms(MAX_TERMS)),
+    }
+}
+
+// A piece of the interval of an integral, with the integrand at its ends and middle
+// and its Simpson's rule estimate
+struct Piece<N> {
+    a: N,
+    b: N,
+    fa: N,
+    fm: N,
+    fb: N,
+    area: N,
+}
+
+struct Integrator<'a, N> {
+    f: &'a mut dyn FnMut(&N) -> Result<N, Error>,
+    evaluations: usize,
+}
+
+/// The integral of `f` from `a` to `b` and an estimate of its error, with adaptive Simpson's rule.
+/// The intervals are halved until the estimate is within 10^-15 of the integral,
+/// or it gives up on halving them and the error estimate is larger.
+/// ## Example
+/// ```rust
+/// use calc::{calculus::integrate, Context, Number, Value};
+///
+/// let context = Context::new();
+/// let (a, b) = (context.eval("0").unwrap(), context.eval("pi").unwrap());
+/// let (area, error) = integrate(|x: &Value| x.sin(), a, b).unwrap();
+/// assert_eq!(area.round(&context.eval("12").unwrap()).unwrap(), context.eval("2").unwrap());
+/// assert!(error.as_real().unwrap() < context.eval("1e-15").unwrap().as_real().unwrap());
+/// assert_eq!(context.eval("sum(k^2, k, 1, 100)").unwrap().to_string(), "338350");
+/// assert_eq!(context.eval("prod(k, k, 1, 5) + sum(1, 2, 3)").unwrap().to_string(), "126");
+/// assert_eq!(context.eval("round(integrate(x^2, x, 0, 3), 12)").unwrap().to_string(), "9");
+/// ```
+pub fn integrate<N: Number>(
+    mut f: impl FnMut(&N) -> Result<N, Error>,
+    a: N,
+    b: N,
+) -> Result<(N, N), Error> {
+    let mut integrator = Integrator {
+        f: &mut f,
+        evaluations: 0,
+    };
+    let m = midpoint(&a, &b)?;
+    let (fa, fm, fb) = (integrator.at(&a)?, integrator.at(&m)?, integrator.at(&b)?);
+    let area = simpson(&a, &b, &fa, &fm, &fb)?;
+    let scale = area.abs()?;
+    let one = integer::<N>(1)?;
+    let scale = if scale.cmp_real(&one)?.is_gt() {
+        scale
+    } else {
+        one
+    };
+    let tolerance = integer::<N>(10)?
+        .checked_pow(&integer(-TOLERANCE_DIGITS)?)?
+        .checked_mul(&scale)?;
+    let piece = Piece {
+        a,
+        b,
+        fa,
+        fm,
+        fb,
+        area,
+    };
+    integrator.adapt(piece, &tolerance, MAX_DEPTH)
+}
+
+fn midpoint<N: Number>(a: &N, b: &N) -> Result<N, Error> {
+    a.checked_add(b)?.checked_div(&integer(2)?)
+}
+
+// Simpson's rule, `(b - a) / 6 * (f(a) + 4 f(m) + f(b))`
+fn simpson<N: Number>(a: &N, b: &N, fa: &N, fm: &N, fb: &N) -> Result<N, Error> {
+    let sum = fa
+        .checked_add(&fm.checked_mul(&integer(4)?)?)?
+        .checked_add(fb)?;
+    b.checked_sub(a)?
+        .checked_div(&integer(6)?)?
+        .checked_mul(&sum)
+}
+
+impl<N: Number> Integrator<'_, N> {
+    fn at(&mut self, x: &N) -> Result<N, Error> {
+        self.evaluations += 1;
+        (self.f)(x)
+    }
+
+    // The integral over the piece and its error estimate, halving it until both halves agree with it
+    fn adapt(&mut self, piece: Piece<N>, tolerance: &N, depth: usize) -> Result<(N, N), Error> {
+        let Piece {
+            a,
+            b,
+            fa,
+            fm,
+            fb,
+            area,
+        } = piece;
+        let m = midpoint(&a, &b)?;
+        let (lm, rm) = (midpoint(&a, &m)?, midpoint(&m, &b)?);
+        let (flm, frm) = (self.at(&lm)?, self.at(&rm)?);
+        let left = simpson(&a, &m, &fa, &flm, &fm)?;
+        let right = simpson(&m, &b, &fm, &frm, &fb)?;
+        // The difference to the whole is 15 times the error of the halves
+        let fifteen = integer::<N>(15)?;
+        let correction = left
+            .checked_add(&right)?
+            .checked_sub(&area)?
+            .checked_div(&fifteen)?;
+        let error = correction.abs()?;
+        if depth == 0 || self.evaluations >= MAX_EVALUATIONS || error.cmp_real(tolerance)?.is_le() {
+            let total = left.checked_add(&right)?.checked_add(&correction)?;
+            return Ok((total, error));
+        }
+        let half = tolerance.checked_div(&integer(2)?)?;
+        let (left, left_error) = self.adapt(
+            Piece {
+                a,
+                b: m.clone(),
+                fa,
+                fm: flm,
+                fb: fm.clone(),
+                area: left,
+            },
+            &half,
+            depth - 1,
+        )?;
+        let (right, right_error) = self.adapt(
+            Piece {
+                a: m,
+                b,
+                fa: fm,
+                fm: frm,
+                fb,
+                area: right,
+            },
+            &half,
+            depth - 1,
+        )?;
+        Ok((
+            left.checked_add(&right)?,
+            left_error.checked_add(&right_error)?,
+        ))
+    }
+}
+
+// The value of `body` with the variable bound to `x`, the other names in the context.
+// The body is parsed once, and the values of the variable put in its places for each `x`.
+fn bound<'a, N: Number>(
+    context: &Context<N>,
+    body: &'a str,
+    var: &'a str,
+) -> Result<impl FnMut(&N) -> Result<N, Error> + 'a, Error> {
+    let mut context = context.clone();
+    let compiled = compile(&context, body, var)?;
+    Ok(move |x: &N| match &compiled {
+        Some(Compiled { rpn, places }) => {
+            let mut rpn = rpn.clone();
+            for i in places {
+                rpn[*i] = Token::Number(x.clone());
+            }
+            evaluate_rpn(rpn)
+        }
+        None => {
+            context.set(var, x.clone());
+            context.eval(body)
+        }
+    })
+}
+
+// A body in postfix order and the places of the variable in it
+struct Compiled<N> {
+    rpn: Vec<Token<N>>,
+    places: Vec<usize>,
+}
+
+// The body compiled, with the places of the variable found by parsing it with two values of it.
+// A body with a sum of its own is evaluated while it is parsed, so it is parsed again
+// for each value instead.
+fn compile<N: Number>(
+    context: &Context<N>,
+    body: &str,
+    var: &str,
+) -> Result<Option<Compiled<N>>, Error> {
+    let nested = body
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .any(|word| matches!(word, "sum" | "prod" | "integrate"));
+    if nested {
+        return Ok(None);
+    }
+    let parse = |x: N| {
+        let mut context = context.clone();
+        context.set(var, x);
+        shunting_yard(parse_as(body, Some(&context.variables), &context.options)?)
+    };
+    let (zero, one) = (integer::<N>(0)?, integer::<N>(1)?);
+    let (a, b) = (parse(zero.clone())?, parse(one.clone())?);
+    if a.len() != b.len() {
+        return Ok(None);
+    }
+    let mut places = Vec::new();
+    for (i, (x, y)) in a.iter().zip(&b).enumerate() {
+        match (x, y) {
+            _ if x == y => {}
+            (Token::Number(x), Token::Number(y)) if *x == zero && *y == one => places.push(i),
+            _ => return Ok(None),
+        }
+    }
+    Ok(Some(Compiled { rpn: a, places }))
+}
+
+/// The integral of `body` in `var` from `from` to `to` and its error estimate,
+/// the arguments of `integrate(x^2, x, 0, 1)` evaluated in the context
+pub fn integral<N: Number>(
+    context: &Context<N>,
+    body: &str,
+    var: &str,
+    from: &str,
+    to: &str,
+) -> Result<(N, N), Error> {
+    let (from, to) = (context.eval(from)?, context.eval(to)?);
+    integrate(bound(context, body, var.trim())?, from, to)
+}
+
+// The value of `sum`, `prod` or `integrate` with the arguments `(body, var, from, to)`
+pub(crate) fn special_form<N: Number>(
+    name: &str,
+    args: &[String],
+    variables: Option<&HashMap<String, N>>,
+    options: &ParseOptions,
+) -> Result<N, Error> {
+    let [body, var, from, to] = args else {
+        return Err(Error::NotEnoughArgs);
+    };
+    // A constant can't be bound, `sum(i^2, i, 1, 3)` would mean something else with it
+    if N::constant(var.trim()).is_some() {
+        return Err(Error::ExpectedVariable(var.trim().to_string()));
+    }
+    let context = Context {
+        variables: variables.cloned().unwrap_or_default(),
+        options: *options,
+    };
+    if name == "integrate" {
+        return integral(&context, body, var, from, to).map(|(area, _)| area);
+    }
+    let (from, to) = (context.eval(from)?, context.eval(to)?);
+    let f = bound(&context, body, var.trim())?;
+    match name {
+        "prod" => prod(f, from, to),
+        _ => sum(f, from, to),
+    }
+}
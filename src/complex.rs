@@ -0,0 +1,286 @@
+use std::fmt::{Display, Formatter};
+
+use rust_decimal::{prelude::*, MathematicalOps};
+
+use crate::error::Error;
+
+/// A complex number with `Decimal` parts
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Complex {
+    pub re: Decimal,
+    pub im: Decimal,
+}
+
+impl Complex {
+    /// The imaginary unit
+    pub const I: Complex = Complex::new(Decimal::ZERO, Decimal::ONE);
+
+    pub const fn new(re: Decimal, im: Decimal) -> Self {
+        Self { re, im }
+    }
+
+    /// Builds a complex number from its modulus and argument (in radians)
+    pub fn from_polar(r: Decimal, theta: Decimal) -> Result<Self, Error> {
+        Ok(Self::new(
+            mul(r, theta.checked_cos().ok_or(Error::Overflow)?)?,
+            mul(r, theta.checked_sin().ok_or(Error::Overflow)?)?,
+        ))
+    }
+
+    pub fn conj(&self) -> Self {
+        Self::new(self.re, -self.im)
+    }
+
+    /// The modulus `|z|`
+    pub fn abs(&self) -> Result<Decimal, Error> {
+        if self.im.is_zero() {
+            return Ok(self.re.abs());
+        }
+        if self.re.is_zero() {
+            return Ok(self.im.abs());
+        }
+        // Scale first so the squares don't overflow
+        let scale = self.re.abs().max(self.im.abs());
+        let (re, im) = (self.re / scale, self.im / scale);
+        let sum = re * re + im * im;
+        mul(sum.sqrt().ok_or(Error::DomainError)?, scale)
+    }
+
+    /// The argument, in radians in `(-π, π]`
+    pub fn arg(&self) -> Decimal {
+        atan2(self.im, self.re)
+    }
+
+    pub fn checked_add(&self, rhs: &Self) -> Result<Self, Error> {
+        Ok(Self::new(add(self.re, rhs.re)?, add(self.im, rhs.im)?))
+    }
+
+    pub fn checked_sub(&self, rhs: &Self) -> Result<Self, Error> {
+        Ok(Self::new(sub(self.re, rhs.re)?, sub(self.im, rhs.im)?))
+    }
+
+    pub fn checked_mul(&self, rhs: &Self) -> Result<Self, Error> {
+        Ok(Self::new(
+            sub(mul(self.re, rhs.re)?, mul(self.im, rhs.im)?)?,
+            add(mul(self.re, rhs.im)?, mul(self.im, rhs.re)?)?,
+        ))
+    }
+
+    pub fn checked_div(&self, rhs: &Self) -> Result<Self, Error> {
+        if rhs.re.is_zero() && rhs.im.is_zero() {
+            return Err(Error::DivisionByZero);
+        }
+        // Divide through by the larger part of the divisor first (Smith's method), so
+        // neither a large divisor nor a small one has its square overflow or round to 0
+        if rhs.re.abs() >= rhs.im.abs() {
+            let ratio = div(rhs.im, rhs.re)?;
+            let denom = add(rhs.re, mul(rhs.im, ratio)?)?;
+            Ok(Self::new(
+                div(add(self.re, mul(self.im, ratio)?)?, denom)?,
+                div(sub(self.im, mul(self.re, ratio)?)?, denom)?,
+            ))
+        } else {
+            let ratio = div(rhs.re, rhs.im)?;
+            let denom = add(mul(rhs.re, ratio)?, rhs.im)?;
+            Ok(Self::new(
+                div(add(mul(self.re, ratio)?, self.im)?, denom)?,
+                div(sub(mul(self.im, ratio)?, self.re)?, denom)?,
+            ))
+        }
+    }
+
+    /// Raises to a complex power, using the principal branch of the logarithm.
+    /// Integer powers are computed exactly by repeated multiplication.
+    pub fn checked_pow(&self, rhs: &Self) -> Result<Self, Error> {
+        if rhs.im.is_zero() && rhs.re.fract().is_zero() {
+            if let Some(n) = rhs.re.to_i64() {
+                return self.powi(n);
+            }
+        }
+        if self.re.is_zero() && self.im.is_zero() {
+            return if rhs.re > Decimal::ZERO {
+                Ok(*self)
+            } else {
+                Err(Error::DivisionByZero)
+            };
+        }
+        rhs.checked_mul(&self.ln()?)?.exp()
+    }
+
+    fn powi(&self, n: i64) -> Result<Self, Error> {
+        let one = Self::new(Decimal::ONE, Decimal::ZERO);
+        let power = |mut exp: u64| {
+            let mut result = one;
+            let mut base = *self;
+            while exp > 0 {
+                if exp & 1 == 1 {
+                    result = result.checked_mul(&base)?;
+                }
+                exp >>= 1;
+                if exp > 0 {
+                    base = base.checked_mul(&base)?;
+                }
+            }
+            Ok(result)
+        };
+        let zero = |z: &Self| z.re.is_zero() && z.im.is_zero();
+        if n >= 0 {
+            return power(n as u64);
+        }
+        match power(n.unsigned_abs()) {
+            // The inverse of a power too large for `Decimal` is too small for it, so 0
+            Err(Error::Overflow) => Ok(Self::new(Decimal::ZERO, Decimal::ZERO)),
+            // and that of a power too small for it is too large, unless the base is 0
+            Ok(p) if zero(&p) && !zero(self) => Err(Error::Overflow),
+            p => one.checked_div(&p?),
+        }
+    }
+
+    pub fn exp(&self) -> Result<Self, Error> {
+        let r = crate::number::exp_decimal(self.re)?;
+        Self::from_polar(r, self.im)
+    }
+
+    /// The principal natural logarithm
+    pub fn ln(&self) -> Result<Self, Error> {
+        let r = self.abs()?;
+        if r.is_zero() {
+            return Err(Error::DomainError);
+        }
+        Ok(Self::new(
+            r.checked_ln().ok_or(Error::Overflow)?,
+            self.arg(),
+        ))
+    }
+
+    /// The principal square root
+    pub fn sqrt(&self) -> Result<Self, Error> {
+        let r = self.abs()?;
+        let re = (r.checked_add(self.re).ok_or(Error::Overflow)? / Decimal::TWO)
+            .sqrt()
+            .ok_or(Error::DomainError)?;
+        let mut im = (r.checked_sub(self.re).ok_or(Error::Overflow)? / Decimal::TWO)
+            .sqrt()
+            .ok_or(Error::DomainError)?;
+        if self.im.is_sign_negative() {
+            im = -im;
+        }
+        Ok(Self::new(re, im))
+    }
+
+    pub fn sin(&self) -> Result<Self, Error> {
+        let (sinh, cosh) = sinh_cosh(self.im)?;
+        Ok(Self::new(
+            mul(self.re.checked_sin().ok_or(Error::Overflow)?, cosh)?,
+            mul(self.re.checked_cos().ok_or(Error::Overflow)?, sinh)?,
+        ))
+    }
+
+    pub fn cos(&self) -> Result<Self, Error> {
+        let (sinh, cosh) = sinh_cosh(self.im)?;
+        Ok(Self::new(
+            mul(self.re.checked_cos().ok_or(Error::Overflow)?, cosh)?,
+            -mul(self.re.checked_sin().ok_or(Error::Overflow)?, sinh)?,
+        ))
+    }
+
+    pub fn tan(&self) -> Result<Self, Error> {
+        self.sin()?
+            .checked_div(&self.cos()?)
+            .map_err(|_| Error::DomainError)
+    }
+}
+
+impl From<Decimal> for Complex {
+    fn from(re: Decimal) -> Self {
+        Self::new(re, Decimal::ZERO)
+    }
+}
+
+impl Display for Complex {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}", format_rectangular(self, |x| x.to_string()))
+    }
+}
+
+// Writes `a + bi`, leaving out the parts that are zero
+pub(crate) fn format_rectangular(z: &Complex, part: impl Fn(Decimal) -> String) -> String {
+    let im = match z.im.abs() {
+        x if x.is_one() => "i".to_string(),
+        x => format!("{}i", part(x)),
+    };
+    if z.re.is_zero() {
+        if z.im.is_sign_negative() {
+            format!("-{}", im)
+        } else {
+            im
+        }
+    } else if z.im.is_sign_negative() {
+        format!("{} - {}", part(z.re), im)
+    } else {
+        format!("{} + {}", part(z.re), im)
+    }
+}
+
+// Hyperbolic sine and cosine, computed together from one exponential
+fn sinh_cosh(x: Decimal) -> Result<(Decimal, Decimal), Error> {
+    if x.is_zero() {
+        return Ok((Decimal::ZERO, Decimal::ONE));
+    }
+    let e = x.checked_exp().ok_or(Error::Overflow)?;
+    let inv = div(Decimal::ONE, e)?;
+    let sinh = e.checked_sub(inv).ok_or(Error::Overflow)? / Decimal::TWO;
+    let cosh = e.checked_add(inv).ok_or(Error::Overflow)? / Decimal::TWO;
+    Ok((sinh, cosh))
+}
+
+/// Four quadrant arctangent of `y / x` in `Decimal` precision
+pub(crate) fn atan2(y: Decimal, x: Decimal) -> Decimal {
+    if y.is_zero() {
+        return if x.is_sign_negative() {
+            Decimal::PI
+        } else {
+            Decimal::ZERO
+        };
+    }
+    if x.is_zero() {
+        return if y.is_sign_negative() {
+            -Decimal::HALF_PI
+        } else {
+            Decimal::HALF_PI
+        };
+    }
+    let scale = x.abs().max(y.abs());
+    let (x, y) = (x / scale, y / scale);
+    // Start from the f64 result and refine it with Newton's method on
+    // x·sin(t) - y·cos(t) = 0 to get the rest of the digits
+    let mut t = Decimal::from_f64(y.to_f64().unwrap().atan2(x.to_f64().unwrap())).unwrap();
+    for _ in 0..2 {
+        let (sin, cos) = (t.sin(), t.cos());
+        let d = x * cos + y * sin;
+        if d.is_zero() {
+            break;
+        }
+        t -= (x * sin - y * cos) / d;
+    }
+    t
+}
+
+fn add(a: Decimal, b: Decimal) -> Result<Decimal, Error> {
+    a.checked_add(b).ok_or(Error::Overflow)
+}
+
+fn sub(a: Decimal, b: Decimal) -> Result<Decimal, Error> {
+    a.checked_sub(b).ok_or(Error::Overflow)
+}
+
+fn mul(a: Decimal, b: Decimal) -> Result<Decimal, Error> {
+    a.checked_mul(b).ok_or(Error::Overflow)
+}
+
+fn div(a: Decimal, b: Decimal) -> Result<Decimal, Error> {
+    if b.is_zero() {
+        return Err(Error::DivisionByZero);
+    }
+    a.checked_div(b).ok_or(Error::Overflow)
+}
@@ -0,0 +1,195 @@
+use crate::{
+    error::Error,
+    execute::evaluate_rpn,
+    number::Number,
+    parse::{parse_as, NumberMode, ParseOptions},
+    rpn::shunting_yard,
+};
+
+/// A named constant of the library, like `phys.c`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Constant {
+    /// The name with its namespace, `phys.` or `math.`
+    pub name: &'static str,
+    /// The value as an expression, so it is as precise as the number type it is computed with
+    pub value: &'static str,
+    /// The unit of the value, empty for plain numbers
+    pub unit: &'static str,
+    pub description: &'static str,
+}
+
+impl Constant {
+    /// Computes the constant with the given number type.
+    /// Constants with a unit need a type that has units, like `Value`.
+    pub fn value<N: Number>(&self, mode: NumberMode) -> Result<N, Error> {
+        let options = ParseOptions {
+            mode,
+            ..Default::default()
+        };
+        let eval = |s: &str| evaluate_rpn(shunting_yard(parse_as::<N>(s, None, &options)?)?);
+        let value = eval(self.value)?;
+        if self.unit.is_empty() {
+            return Ok(value);
+        }
+        let unit = eval(self.unit).map_err(|_| Error::NotSupported)?;
+        value.checked_mul(&unit)
+    }
+}
+
+// Namespaces keep the constants apart from variables
+const NAMESPACES: &[&str] = &["phys", "math"];
+
+/// The constants of the library.
+/// Physical constants are the CODATA 2018 values, the first seven of them are exact.
+/// A few are given in `eV` because their SI values are too small for `Decimal`.
+pub const CONSTANTS: &[Constant] = &[
+    Constant {
+        name: "phys.c",
+        value: "299792458",
+        unit: "m/s",
+        description: "Speed of light in vacuum",
+    },
+    Constant {
+        name: "phys.h",
+        value: "4.135667696 × 10^-15",
+        unit: "eV·s",
+        description: "Planck constant",
+    },
+    Constant {
+        name: "phys.hbar",
+        value: "6.582119569 × 10^-16",
+        unit: "eV·s",
+        description: "Reduced Planck constant, h/2π",
+    },
+    Constant {
+        name: "phys.e",
+        value: "1.602176634 × 10^-19",
+        unit: "C",
+        description: "Elementary charge",
+    },
+    Constant {
+        name: "phys.k_B",
+        value: "8.617333262 × 10^-5",
+        unit: "eV/K",
+        description: "Boltzmann constant",
+    },
+    Constant {
+        name: "phys.N_A",
+        value: "6.02214076 × 10^23",
+        unit: "mol^-1",
+        description: "Avogadro constant",
+    },
+    Constant {
+        name: "phys.R",
+        value: "8.314462618",
+        unit: "J/(mol·K)",
+        description: "Molar gas constant, N_A k_B",
+    },
+    Constant {
+        name: "phys.G",
+        value: "6.67430 × 10^-11",
+        unit: "m^3/(kg·s^2)",
+        description: "Newtonian constant of gravitation",
+    },
+    Constant {
+        name: "phys.g_n",
+        value: "9.80665",
+        unit: "m/s^2",
+        description: "Standard acceleration of gravity",
+    },
+    Constant {
+        name: "phys.F",
+        value: "96485.33212",
+        unit: "C/mol",
+        description: "Faraday constant, N_A e",
+    },
+    Constant {
+        name: "phys.eps0",
+        value: "8.8541878128 × 10^-12",
+        unit: "F/m",
+        description: "Vacuum electric permittivity",
+    },
+    Constant {
+        name: "phys.mu0",
+        value: "1.25663706212 × 10^-6",
+        unit: "N/A^2",
+        description: "Vacuum magnetic permeability",
+    },
+    Constant {
+        name: "phys.sigma",
+        value: "5.670374419 × 10^-8",
+        unit: "W/(m^2·K^4)",
+        description: "Stefan–Boltzmann constant",
+    },
+    Constant {
+        name: "phys.a0",
+        value: "5.29177210903 × 10^-11",
+        unit: "m",
+        description: "Bohr radius",
+    },
+    Constant {
+        name: "phys.alpha",
+        value: "7.2973525693 × 10^-3",
+        unit: "",
+        description: "Fine-structure constant",
+    },
+    Constant {
+        name: "math.pi",
+        value: "π",
+        unit: "",
+        description: "Ratio of a circle's circumference to its diameter",
+    },
+    Constant {
+        name: "math.tau",
+        value: "2π",
+        unit: "",
+        description: "Full turn in radians, 2π",
+    },
+    Constant {
+        name: "math.e",
+        value: "e",
+        unit: "",
+        description: "Base of the natural logarithm",
+    },
+    Constant {
+        name: "math.phi",
+        value: "(1 + sqrt(5))/2",
+        unit: "",
+        description: "Golden ratio",
+    },
+    Constant {
+        name: "math.sqrt2",
+        value: "sqrt(2)",
+        unit: "",
+        description: "Square root of 2",
+    },
+    Constant {
+        name: "math.gamma",
+        value: "0.57721566490153286060651209008240243104215933593992",
+        unit: "",
+        description: "Euler–Mascheroni constant",
+    },
+];
+
+/// Looks up a constant by its full name, like `phys.c` or `math.tau`
+/// ## Example
+/// ```rust
+/// use calc::{constants, parse::NumberMode, Context, Value};
+///
+/// let c = constants::lookup("phys.c").unwrap();
+/// assert_eq!(c.description, "Speed of light in vacuum");
+/// let value = c.value::<Value>(NumberMode::Decimal).unwrap();
+/// assert_eq!(value.to_string(), "299792458 m/s");
+/// let context = Context::new();
+/// assert_eq!(context.eval("phys.c * 1 s to km").unwrap().to_string(), "299792.458 km");
+/// let tau = Context::<f64>::default().eval("math.tau").unwrap();
+/// assert_eq!(tau, std::f64::consts::TAU);
+/// ```
+pub fn lookup(name: &str) -> Option<&'static Constant> {
+    CONSTANTS.iter().find(|c| c.name == name)
+}
+
+/// Whether the name is the namespace of some constants, like `phys`
+pub fn is_namespace(name: &str) -> bool {
+    NAMESPACES.contains(&name)
+}
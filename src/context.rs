@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+
+use crate::{
+    error::Error, execute::evaluate_rpn, number::Number, parse::parse_as, parse::ParseOptions,
+    rpn::shunting_yard, value::Value,
+};
+
+/// An evaluation context, keeping the variables and the options
+/// expressions are parsed with between evaluations.
+/// It works with `Value` by default, or with any other `Number`.
+/// ## Example
+/// ```rust
+/// use calc::{context::Context, parse::NumberMode};
+///
+/// let mut context = Context::new();
+/// context.options.mode = NumberMode::Rational;
+/// let third = context.eval("1/3").unwrap();
+/// context.set("x", third);
+/// assert_eq!(context.eval("x * 3").unwrap().to_string(), "1");
+/// assert_eq!(context.eval("1/4 + 1/3").unwrap().to_string(), "7/12");
+/// ```
+#[derive(Clone, Debug)]
+pub struct Context<N = Value> {
+    pub variables: HashMap<String, N>,
+    pub options: ParseOptions,
+}
+
+impl Context {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<N> Default for Context<N> {
+    fn default() -> Self {
+        Self {
+            variables: HashMap::new(),
+            options: ParseOptions::default(),
+        }
+    }
+}
+
+impl<N: Number> Context<N> {
+    /// Parses and evaluates an expression
+    pub fn eval(&self, s: &str) -> Result<N, Error> {
+        let tokens = parse_as(s, Some(&self.variables), &self.options)?;
+        evaluate_rpn(shunting_yard(tokens)?)
+    }
+
+    /// Binds a variable
+    pub fn set(&mut self, name: &str, value: N) {
+        self.variables.insert(name.to_string(), value);
+    }
+}
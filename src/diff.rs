@@ -0,0 +1,143 @@
+use crate::{
+    error::Error,
+    expr::{add, call, div, is_number, mul, neg, number, pow, sub, Expr},
+    op::BinOp,
+};
+
+/// The derivative of an expression with respect to a variable.
+/// Numbers are folded and operations that do nothing are left out,
+/// the piecewise constant functions like `floor` have a derivative of 0.
+/// ## Example
+/// ```rust
+/// use calc::{diff::derivative, expr::Expr, ParseOptions};
+///
+/// let d = |s: &str| {
+///     let expr = Expr::parse(s, &ParseOptions::default()).unwrap();
+///     derivative(&expr, "x").map(|d| d.to_string())
+/// };
+/// assert_eq!(d("x^2 * sin(x)").unwrap(), "2 * x * sin(x) + x^2 * cos(x)");
+/// assert_eq!(d("exp(3x)").unwrap(), "3 * exp(3 * x)");
+/// assert_eq!(d("a * x + b").unwrap(), "a");
+/// assert_eq!(d("max(x, 1)").unwrap(), "(1 + sign(x - 1)) / 2");
+/// assert_eq!(d("gcd(x, 4)").unwrap_err().to_string(), "Cannot differentiate gcd");
+/// ```
+pub fn derivative(expr: &Expr, var: &str) -> Result<Expr, Error> {
+    if !expr.depends_on(var) {
+        return Ok(number(0));
+    }
+    match expr {
+        Expr::Number(_) | Expr::Constant(_) => Ok(number(0)),
+        Expr::Variable(_) => Ok(number(1)),
+        Expr::Binary(op, a, b) => binary(*op, a, b, var),
+        Expr::Call(name, args) => function(name, args, var),
+    }
+}
+
+fn binary(op: BinOp, a: &Expr, b: &Expr, var: &str) -> Result<Expr, Error> {
+    let (da, db) = (derivative(a, var)?, derivative(b, var)?);
+    let (a, b) = (a.clone(), b.clone());
+    Ok(match op {
+        BinOp::Add => add(da, db),
+        BinOp::Sub => sub(da, db),
+        BinOp::Mul => add(mul(da, b), mul(a, db)),
+        BinOp::Div if is_number(&db, 0) => div(da, b),
+        BinOp::Div => div(sub(mul(da, b.clone()), mul(a, db)), pow(b, number(2))),
+        // The power rule for constant exponents
+        BinOp::Pow if is_number(&db, 0) => mul(mul(b.clone(), pow(a, sub(b, number(1)))), da),
+        BinOp::Pow if is_number(&da, 0) => mul(mul(pow(a.clone(), b), ln(a)), db),
+        // `a^b` is `e^(b ln(a))` for everything else
+        BinOp::Pow => mul(
+            pow(a.clone(), b.clone()),
+            add(mul(db, ln(a.clone())), div(mul(b, da), a)),
+        ),
+        // The operations that are linear in each side
+        BinOp::MatMul => add(
+            Expr::Binary(op, Box::new(da), Box::new(b)),
+            Expr::Binary(op, Box::new(a), Box::new(db)),
+        ),
+        BinOp::Index | BinOp::Convert => Expr::Binary(op, Box::new(da), Box::new(b)),
+        BinOp::PlusMinus => return Err(Error::NotDifferentiable("±".to_string())),
+    })
+}
+
+// The natural logarithm, which is 1 for `e`
+fn ln(x: Expr) -> Expr {
+    match &x {
+        Expr::Constant(name) if name == "e" => number(1),
+        _ => call("ln", vec![x]),
+    }
+}
+
+fn sqrt(x: Expr) -> Expr {
+    call("sqrt", vec![x])
+}
+
+// The derivative of a call, by the chain rule
+fn function(name: &str, args: &[Expr], var: &str) -> Result<Expr, Error> {
+    let derivatives = args
+        .iter()
+        .map(|arg| derivative(arg, var))
+        .collect::<Result<Vec<_>, Error>>()?;
+    let one = |f: &str, u: &Expr| call(f, vec![u.clone()]);
+    let outer = match (name, args) {
+        // Functions that are linear in their arguments
+        ("deg" | "rad" | "re" | "im" | "conj" | "sum" | "mean" | "transpose", _) => {
+            return Ok(call(name, derivatives));
+        }
+        ("dot" | "cross", [a, b]) => {
+            let (da, db) = (derivatives[0].clone(), derivatives[1].clone());
+            let left = call(name, vec![da, b.clone()]);
+            let right = call(name, vec![a.clone(), db]);
+            return Ok(add(left, right));
+        }
+        // Piecewise constant, so the derivative is 0 wherever it exists
+        ("sign" | "floor" | "ceil" | "trunc" | "round" | "len" | "count", _) => {
+            return Ok(number(0));
+        }
+        // `max(a, b)` is `(a + b + |a - b|) / 2`, and `min` the same with `-|a - b|`
+        ("max" | "min", [a, b]) => {
+            let spread = call("abs", vec![sub(a.clone(), b.clone())]);
+            let sum = match name {
+                "max" => add(add(a.clone(), b.clone()), spread),
+                _ => sub(add(a.clone(), b.clone()), spread),
+            };
+            return derivative(&div(sum, number(2)), var);
+        }
+        ("log", [base, x]) => {
+            let quotient = div(ln(x.clone()), ln(base.clone()));
+            return derivative(&quotient, var);
+        }
+        ("atan2", [y, x]) => {
+            let (dy, dx) = (derivatives[0].clone(), derivatives[1].clone());
+            let numerator = sub(mul(x.clone(), dy), mul(y.clone(), dx));
+            let denominator = add(pow(x.clone(), number(2)), pow(y.clone(), number(2)));
+            return Ok(div(numerator, denominator));
+        }
+        ("sin", [u]) => one("cos", u),
+        ("cos", [u]) => neg(one("sin", u)),
+        ("tan", [u]) => pow(one("sec", u), number(2)),
+        ("ctan", [u]) => neg(pow(one("csc", u), number(2))),
+        ("sec", [u]) => mul(one("sec", u), one("tan", u)),
+        ("csc", [u]) => neg(mul(one("csc", u), one("ctan", u))),
+        ("asin", [u]) => div(number(1), sqrt(sub(number(1), pow(u.clone(), number(2))))),
+        ("acos", [u]) => neg(div(
+            number(1),
+            sqrt(sub(number(1), pow(u.clone(), number(2)))),
+        )),
+        ("atan", [u]) => div(number(1), add(number(1), pow(u.clone(), number(2)))),
+        ("sinh", [u]) => one("cosh", u),
+        ("cosh", [u]) => one("sinh", u),
+        ("tanh", [u]) => div(number(1), pow(one("cosh", u), number(2))),
+        ("asinh", [u]) => div(number(1), sqrt(add(pow(u.clone(), number(2)), number(1)))),
+        ("acosh", [u]) => div(number(1), sqrt(sub(pow(u.clone(), number(2)), number(1)))),
+        ("atanh", [u]) => div(number(1), sub(number(1), pow(u.clone(), number(2)))),
+        ("exp", [u]) => one("exp", u),
+        ("ln", [u]) => div(number(1), u.clone()),
+        ("log10", [u]) => div(number(1), mul(u.clone(), ln(number(10)))),
+        ("sqrt", [u]) => div(number(1), mul(number(2), sqrt(u.clone()))),
+        ("cbrt", [u]) => div(number(1), mul(number(3), pow(one("cbrt", u), number(2)))),
+        ("abs", [u]) => one("sign", u),
+        _ => return Err(Error::NotDifferentiable(name.to_string())),
+    };
+    Ok(mul(outer, derivatives[0].clone()))
+}
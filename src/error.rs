@@ -20,20 +20,108 @@ pub enum Error {
     NumberParseError,
     /// Not enough arguments to functions
     NotEnoughArgs,
+    /// More arguments than a function takes
+    TooManyArgs,
     /// Unexpected token error
     UnexpectedToken,
+    /// A digit that is not valid in the base of the literal, e.g. `0b102`
+    InvalidDigit(char, u32),
+    /// A `_` digit separator that is not between two digits
+    MisplacedSeparator,
+    /// Function argument outside of the function's domain, e.g. `ctan(0)`
+    DomainError,
+    /// Division by zero
+    DivisionByZero,
+    /// The result is too large to be represented
+    Overflow,
+    /// A complex number was given where only real numbers are allowed
+    ExpectedReal,
+    /// A list where a single number is needed, like `[1, 2] < 3`
+    ExpectedNumber,
+    /// A single number where a list is needed, like `3[0]`
+    ExpectedList,
+    /// Lists or matrices of sizes that don't fit together, like `[1, 2] + [1, 2, 3]`
+    DimensionMismatch(String, String),
+    /// An index past the end of a list, with the length of the list
+    IndexOutOfRange(String, usize),
+    /// A matrix that is not square where one is needed, like `det([[1, 2, 3], [4, 5, 6]])`
+    NotSquare(String),
+    /// A polynomial division that leaves the remainder
+    Remainder(String),
+    /// A matrix without an inverse, like `inv([[1, 2], [2, 4]])`
+    SingularMatrix,
+    /// A number with a fractional part was given to an integer function, like `gcd(1.5, 3)`
+    ExpectedInteger,
+    /// An iterative solver that did not find a solution, like `irr` of a strange cash flow
+    NoConvergence,
+    /// Something other than the name of a variable where one is needed, like the `2` of `diff(x^2, 2)`
+    ExpectedVariable(String),
+    /// Solving for a variable the equation doesn't have, like `solve(x^2 = 2, y)`
+    MissingVariable(String),
+    /// A function or operator without a symbolic derivative, like `gcd` or `±`
+    NotDifferentiable(String),
+    /// An operation the number type has no meaning for, like `±` on plain numbers
+    NotSupported,
+    /// Adding, subtracting or comparing quantities of different dimensions, like `1 m + 1 s`
+    IncompatibleUnits(&'static str, String, String),
+    /// Converting a quantity to a unit of another dimension, like `1 m to s`
+    CannotConvert(String, String),
+    /// A quantity with a unit where a plain number is needed, like `sin(1 m)`
+    UnitsNotAllowed(String),
+    /// A unit definition that is not `name = expression`
+    InvalidUnitDefinition(String),
+    /// `∞` with a number type that has no infinity, like the default `Decimal`
+    NoInfinity,
+    /// An equation that holds for every value of the variable, like `solve(x = x, x, 0, 1)`
+    Identity,
+    /// A sum or product of more terms than the limit, like `sum(k, k, 1, 10^9)`
+    TooManyTerms(usize),
 }
 
 impl Debug for Error {
     fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
-        match *self {
+        match self {
             Error::UnknownToken => write!(f, "Unknown token"),
             Error::InvalidToken => write!(f, "Invalid token"),
             Error::UnbalancedParens => write!(f, "Unbalanced parentheses"),
             Error::UnfinishedExpr => write!(f, "Unfinished expression"),
             Error::NumberParseError => write!(f, "Number parse error"),
             Error::NotEnoughArgs => write!(f, "Not enough arguments"),
+            Error::TooManyArgs => write!(f, "Too many arguments"),
             Error::UnexpectedToken => write!(f, "Unexpected token"),
+            Error::InvalidDigit(c, radix) => {
+                write!(f, "Invalid digit '{}' in base {} literal", c, radix)
+            }
+            Error::MisplacedSeparator => write!(f, "Digit separator must be between digits"),
+            Error::DomainError => write!(f, "Argument out of domain"),
+            Error::DivisionByZero => write!(f, "Division by zero"),
+            Error::Overflow => write!(f, "Overflow"),
+            Error::ExpectedReal => write!(f, "Expected a real number"),
+            Error::ExpectedNumber => write!(f, "Expected a number, got a list"),
+            Error::ExpectedList => write!(f, "Expected a list"),
+            Error::DimensionMismatch(a, b) => write!(f, "Dimensions {} and {} don't match", a, b),
+            Error::IndexOutOfRange(i, len) => {
+                write!(f, "Index {} is out of range for {} elements", i, len)
+            }
+            Error::NotSquare(size) => write!(f, "Expected a square matrix, got {}", size),
+            Error::SingularMatrix => write!(f, "The matrix is singular"),
+            Error::Remainder(r) => write!(f, "The division leaves a remainder of {}", r),
+            Error::ExpectedInteger => write!(f, "Expected an integer"),
+            Error::NoConvergence => write!(f, "The solver did not converge"),
+            Error::ExpectedVariable(s) => write!(f, "Expected a variable, got {}", s),
+            Error::MissingVariable(name) => write!(f, "The equation has no variable {}", name),
+            Error::NotDifferentiable(name) => write!(f, "Cannot differentiate {}", name),
+            Error::NotSupported => write!(f, "Not supported by this kind of number"),
+            Error::IncompatibleUnits(op, a, b) => write!(f, "Cannot {} {} and {}", op, a, b),
+            Error::CannotConvert(a, b) => write!(f, "Cannot convert {} to {}", a, b),
+            Error::UnitsNotAllowed(unit) => write!(f, "Expected a plain number, got {}", unit),
+            Error::InvalidUnitDefinition(line) => write!(f, "Invalid unit definition '{}'", line),
+            Error::Identity => write!(f, "Every value is a solution"),
+            Error::TooManyTerms(limit) => write!(f, "More than {} terms", limit),
+            Error::NoInfinity => write!(
+                f,
+                "∞ needs a mode with an infinity, like f64, `:interval on` or `:uncertainty on`"
+            ),
         }
     }
 }
@@ -1,22 +1,20 @@
 use std::collections::VecDeque;
 
-use rust_decimal::{Decimal, MathematicalOps};
-
-use crate::{error::Error, token::Function, Token};
+use crate::{error::Error, number::Number, token::Function, Token};
 
 /// This function evalutes a rpn expression
-/// and returns a result.
+/// and returns a result, for any number type.
 /// ## Example
 /// ```rust
-/// use calc::{execute::evaluate_rpn, token::Token::*, op::OPERATORS};
+/// use calc::{execute::evaluate_rpn, token::{Token, Token::*}, op::OPERATORS};
 /// use rust_decimal::prelude::*;
-/// 
-/// let tokens = vec![Number(Decimal::from_f64(1.0).unwrap()), Number(Decimal::from_f64(2.0).unwrap()), Operator(*OPERATORS.get(&'+').unwrap())];
+///
+/// let tokens: Vec<Token> = vec![Number(Decimal::from_f64(1.0).unwrap().into()), Number(Decimal::from_f64(2.0).unwrap().into()), Operator(*OPERATORS.get(&'+').unwrap())];
 /// let result = evaluate_rpn(tokens).unwrap();
 /// assert_eq!(result, Decimal::from_f64(3.0).unwrap());
 /// ```
-pub fn evaluate_rpn(queue: Vec<Token>) -> Result<Decimal, Error> {
-    let mut stack: VecDeque<Decimal> = VecDeque::with_capacity(queue.len());
+pub fn evaluate_rpn<N: Number>(queue: Vec<Token<N>>) -> Result<N, Error> {
+    let mut stack: VecDeque<N> = VecDeque::with_capacity(queue.len());
     for token in queue {
         if let Token::Operator(op) = token {
             // If there are less than 2 elements, return an error
@@ -25,14 +23,7 @@ pub fn evaluate_rpn(queue: Vec<Token>) -> Result<Decimal, Error> {
             }
             let first = stack.pop_back().unwrap();
             let second = stack.pop_back().unwrap();
-            use crate::op::BinOp;
-            let result = match op.op {
-                BinOp::Add => first + second,
-                BinOp::Sub => second - first,
-                BinOp::Div => second / first,
-                BinOp::Mul => first * second,
-                BinOp::Pow => second.powd(first),
-            };
+            let result = op.op.apply(&second, &first)?;
             stack.push_back(result);
         } else if let Token::Function(f) = token {
             // Apply the function to elements from the stack
@@ -42,7 +33,8 @@ pub fn evaluate_rpn(queue: Vec<Token>) -> Result<Decimal, Error> {
                         return Err(Error::NotEnoughArgs);
                     }
                     let arg = stack.pop_back().unwrap();
-                    let a = f(arg);
+                    // Lists apply the function to each element
+                    let a = arg.map(f)?;
                     stack.push_back(a);
                 }
                 Function::TwoParam(f) => {
@@ -51,9 +43,16 @@ pub fn evaluate_rpn(queue: Vec<Token>) -> Result<Decimal, Error> {
                     }
                     let first = stack.pop_back().unwrap();
                     let second = stack.pop_back().unwrap();
-                    let a = f(first, second);
+                    let a = f(first, second)?;
                     stack.push_back(a);
                 }
+                Function::Variadic(f, count) => {
+                    if stack.len() < count {
+                        return Err(Error::NotEnoughArgs);
+                    }
+                    let args = stack.split_off(stack.len() - count);
+                    stack.push_back(f(args.into())?);
+                }
             }
         } else if let Token::Number(n) = token {
             // If the token is a number, push it to the stack
@@ -0,0 +1,490 @@
+//! Expressions kept as trees instead of being evaluated right away,
+//! for the symbolic features like differentiation.
+//! They are parsed by the same parser as numbers, with `Expr` as the number type,
+//! so every operation builds a node instead of computing a result.
+
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fmt::{Display, Formatter},
+};
+
+use num_bigint::BigInt;
+use num_traits::Signed;
+use once_cell::sync::Lazy;
+use rust_decimal::Decimal;
+
+use crate::{
+    angle::AngleMode,
+    error::Error,
+    execute::evaluate_rpn,
+    format::{format_value, FormatOptions},
+    integer,
+    number::Number,
+    op::BinOp,
+    parse::{parse_as, NumberMode, ParseOptions},
+    rpn::shunting_yard,
+    token::{Function, CONSTANTS},
+    value::Value,
+};
+
+/// An expression tree
+/// ## Example
+/// ```rust
+/// use calc::{expr::Expr, ParseOptions, Value};
+/// use rust_decimal::Decimal;
+/// use std::collections::HashMap;
+///
+/// let expr = Expr::parse("2x^2 + sin(x)", &ParseOptions::default()).unwrap();
+/// assert_eq!(expr.to_string(), "2 * x^2 + sin(x)");
+/// assert!(expr.depends_on("x"));
+/// let x = HashMap::from([("x".to_string(), Value::from(Decimal::from(3)))]);
+/// let value = Expr::parse("x^2 - 1", &ParseOptions::default()).unwrap().eval(&x).unwrap();
+/// assert_eq!(value, Decimal::from(8));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    /// A number, like `2` or `0.5`
+    Number(Value),
+    /// An inbuilt constant, like `pi`
+    Constant(String),
+    /// A variable, any name that is not a constant or a function
+    Variable(String),
+    /// A binary operation, like `x + 1`
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    /// A call of an inbuilt function with its arguments in order, like `sin(x)`
+    Call(String, Vec<Expr>),
+}
+
+impl Expr {
+    /// Parses an expression. The names that are not constants or functions are variables.
+    /// Angles are always in radians, the calculus of the trig functions only works in them.
+    pub fn parse(s: &str, options: &ParseOptions) -> Result<Expr, Error> {
+        let options = ParseOptions {
+            angle: AngleMode::Radians,
+            ..*options
+        };
+        evaluate_rpn(shunting_yard(parse_as::<Expr>(s, None, &options)?)?)
+    }
+
+    /// Whether the variable appears in the expression
+    pub fn depends_on(&self, name: &str) -> bool {
+        match self {
+            Expr::Number(_) | Expr::Constant(_) => false,
+            Expr::Variable(v) => v == name,
+            Expr::Binary(_, a, b) => a.depends_on(name) || b.depends_on(name),
+            Expr::Call(_, args) => args.iter().any(|arg| arg.depends_on(name)),
+        }
+    }
+
+    /// Evaluates the expression with values for its variables, the angles are in radians
+    pub fn eval(&self, variables: &HashMap<String, Value>) -> Result<Value, Error> {
+        match self {
+            Expr::Number(x) => Ok(x.clone()),
+            Expr::Constant(name) => Value::constant(name).ok_or(Error::UnknownToken),
+            Expr::Variable(name) => variables.get(name).cloned().ok_or(Error::UnknownToken),
+            Expr::Binary(op, a, b) => op.apply(&a.eval(variables)?, &b.eval(variables)?),
+            Expr::Call(name, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| arg.eval(variables))
+                    .collect::<Result<Vec<_>, Error>>()?;
+                Value::function(name).ok_or(Error::UnknownToken)?.call(args)
+            }
+        }
+    }
+
+    // How tightly the expression binds, like the precedences of the operators
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Binary(op, a, _) => match op {
+                BinOp::Convert => 1,
+                BinOp::Add | BinOp::Sub => 2,
+                BinOp::Mul if is_number(a, -1) => 3,
+                BinOp::Mul | BinOp::Div | BinOp::MatMul => 3,
+                BinOp::PlusMinus => 4,
+                BinOp::Pow => 6,
+                BinOp::Index => 8,
+            },
+            Expr::Number(Value::Complex(_)) => 2,
+            Expr::Number(Value::Real(x)) if x.is_sign_negative() => 3,
+            Expr::Number(Value::Rational(r)) if !r.is_integer() || r.is_negative() => 3,
+            _ => 9,
+        }
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Expr::Number(x) => write!(f, "{}", format_value(x, &FormatOptions::default())),
+            Expr::Constant(name) | Expr::Variable(name) => write!(f, "{}", name),
+            Expr::Call(name, args) => {
+                let args: Vec<String> = args.iter().map(Expr::to_string).collect();
+                write!(f, "{}({})", name, args.join(", "))
+            }
+            Expr::Binary(BinOp::Index, a, b) => write!(f, "{}[{}]", group(a, 8), b),
+            Expr::Binary(BinOp::Mul, a, b) if is_number(a, -1) => write!(f, "-{}", group(b, 3)),
+            Expr::Binary(op, a, b) => {
+                let precedence = self.precedence();
+                let symbol = match op {
+                    BinOp::Add => " + ",
+                    BinOp::Sub => " - ",
+                    BinOp::Mul => " * ",
+                    BinOp::Div => " / ",
+                    BinOp::Pow => "^",
+                    BinOp::PlusMinus => " ± ",
+                    BinOp::Convert => " to ",
+                    BinOp::MatMul => " @ ",
+                    BinOp::Index => unreachable!(),
+                };
+                // Powers of powers always get parentheses, the others group to the left
+                let (left, right) = match op {
+                    BinOp::Pow => (precedence + 1, precedence + 1),
+                    BinOp::Add | BinOp::Mul => (precedence, precedence),
+                    _ => (precedence, precedence + 1),
+                };
+                write!(f, "{}{}{}", group(a, left), symbol, group(b, right))
+            }
+        }
+    }
+}
+
+// The expression in parentheses if it binds looser than `precedence`
+fn group(expr: &Expr, precedence: u8) -> String {
+    if expr.precedence() < precedence {
+        format!("({})", expr)
+    } else {
+        expr.to_string()
+    }
+}
+
+/// An exact integer
+pub fn number(n: i64) -> Expr {
+    Expr::Number(integer::from_integer(BigInt::from(n)))
+}
+
+/// Whether the expression is the number `n`
+pub fn is_number(expr: &Expr, n: i64) -> bool {
+    matches!(expr, Expr::Number(x) if *x == Decimal::from(n))
+}
+
+/// A call of a function
+pub fn call(name: &str, args: Vec<Expr>) -> Expr {
+    Expr::Call(name.to_string(), args)
+}
+
+// The constructors below fold numbers and drop the operations that do nothing,
+// so generated expressions like derivatives don't fill up with `0 * x` and `x^1`
+
+fn binary(op: BinOp, a: Expr, b: Expr) -> Expr {
+    Expr::Binary(op, Box::new(a), Box::new(b))
+}
+
+// The operation on two numbers, if it has a result
+fn fold(op: BinOp, a: &Expr, b: &Expr) -> Option<Expr> {
+    match (a, b) {
+        (Expr::Number(a), Expr::Number(b)) => op.apply(a, b).ok().map(Expr::Number),
+        _ => None,
+    }
+}
+
+/// `a + b`
+pub fn add(a: Expr, b: Expr) -> Expr {
+    if is_number(&a, 0) {
+        b
+    } else if is_number(&b, 0) {
+        a
+    } else if let Some(sum) = fold(BinOp::Add, &a, &b) {
+        sum
+    } else if let Some(b) = negated(&b) {
+        binary(BinOp::Sub, a, b)
+    } else {
+        binary(BinOp::Add, a, b)
+    }
+}
+
+/// `a - b`
+pub fn sub(a: Expr, b: Expr) -> Expr {
+    if is_number(&b, 0) {
+        a
+    } else if is_number(&a, 0) {
+        neg(b)
+    } else if let Some(difference) = fold(BinOp::Sub, &a, &b) {
+        difference
+    } else if let Some(b) = negated(&b) {
+        binary(BinOp::Add, a, b)
+    } else {
+        binary(BinOp::Sub, a, b)
+    }
+}
+
+/// `a * b`, with the number factors multiplied in front
+pub fn mul(a: Expr, b: Expr) -> Expr {
+    // `0 * x` is 0 only where `x` has a value
+    if is_number(&a, 0) && !undefined(&b) || is_number(&b, 0) && !undefined(&a) {
+        return number(0);
+    } else if let Some(product) = fold(BinOp::Mul, &a, &b) {
+        return product;
+    } else if let Expr::Binary(BinOp::Div, n, d) = a {
+        // Fractions take the other factor into their numerator, `1 / x * y` is `y / x`
+        return div(mul(*n, b), *d);
+    } else if let Expr::Binary(BinOp::Div, n, d) = b {
+        return div(mul(a, *n), *d);
+    }
+    let ((m, a), (n, b)) = (coefficient(a), coefficient(b));
+    let Some(coefficient) = fold(BinOp::Mul, &m, &n) else {
+        return binary(BinOp::Mul, mul(m, a), mul(n, b));
+    };
+    let rest = if is_number(&a, 1) {
+        b
+    } else if is_number(&b, 1) {
+        a
+    } else {
+        binary(BinOp::Mul, a, b)
+    };
+    if is_number(&coefficient, 1) {
+        rest
+    } else if is_number(&rest, 1) {
+        coefficient
+    } else {
+        binary(BinOp::Mul, coefficient, rest)
+    }
+}
+
+// Splits a product into its number factor and the rest, `2 * x * 3` into `6` and `x`
+fn coefficient(expr: Expr) -> (Expr, Expr) {
+    match expr {
+        Expr::Number(_) => (expr, number(1)),
+        Expr::Binary(BinOp::Mul, a, b) => {
+            let ((m, a), (n, b)) = (coefficient(*a), coefficient(*b));
+            match fold(BinOp::Mul, &m, &n) {
+                Some(coefficient) if is_number(&a, 1) => (coefficient, b),
+                Some(coefficient) if is_number(&b, 1) => (coefficient, a),
+                Some(coefficient) => (coefficient, binary(BinOp::Mul, a, b)),
+                None => (number(1), binary(BinOp::Mul, mul(m, a), mul(n, b))),
+            }
+        }
+        expr => (number(1), expr),
+    }
+}
+
+/// `a / b`, numbers are only divided when the result is exact
+pub fn div(a: Expr, b: Expr) -> Expr {
+    if is_number(&b, 1) {
+        return a;
+    } else if is_number(&a, 0) && !is_number(&b, 0) && !undefined(&b) {
+        return number(0);
+    } else if a == b && !is_number(&b, 0) {
+        return number(1);
+    } else if let Expr::Binary(BinOp::Div, n, d) = a {
+        return div(*n, mul(*d, b));
+    }
+    match (fold(BinOp::Div, &a, &b), &b) {
+        (Some(quotient), b) if fold(BinOp::Mul, &quotient, b).as_ref() == Some(&a) => quotient,
+        _ => binary(BinOp::Div, a, b),
+    }
+}
+
+// Whether a part of the expression without variables has no value, like `1 / 0`
+pub(crate) fn undefined(expr: &Expr) -> bool {
+    let parts = match expr {
+        Expr::Binary(_, a, b) => undefined(a) || undefined(b),
+        Expr::Call(_, args) => args.iter().any(undefined),
+        Expr::Number(_) | Expr::Constant(_) | Expr::Variable(_) => return false,
+    };
+    parts || !has_variables(expr) && expr.eval(&HashMap::new()).is_err()
+}
+
+// Whether any variable appears in the expression
+fn has_variables(expr: &Expr) -> bool {
+    match expr {
+        Expr::Number(_) | Expr::Constant(_) => false,
+        Expr::Variable(_) => true,
+        Expr::Binary(_, a, b) => has_variables(a) || has_variables(b),
+        Expr::Call(_, args) => args.iter().any(has_variables),
+    }
+}
+
+/// `a ^ b`, numbers are only raised to integer powers
+pub fn pow(a: Expr, b: Expr) -> Expr {
+    if is_number(&b, 0) {
+        return number(1);
+    } else if is_number(&b, 1) || is_number(&a, 1) {
+        return a;
+    }
+    match (&b, fold(BinOp::Pow, &a, &b)) {
+        (Expr::Number(n), Some(power)) if integer::to_integer(n).is_ok() => power,
+        _ => binary(BinOp::Pow, a, b),
+    }
+}
+
+/// `-a`
+pub fn neg(a: Expr) -> Expr {
+    match negated(&a) {
+        Some(a) => a,
+        None => mul(number(-1), a),
+    }
+}
+
+// The expression without its minus sign, if it has one
+fn negated(expr: &Expr) -> Option<Expr> {
+    match expr {
+        Expr::Number(Value::Real(x)) if x.is_sign_negative() => Some(Expr::Number(Value::Real(-x))),
+        Expr::Number(Value::Rational(r)) if r.is_negative() => {
+            Some(Expr::Number(Value::Rational(-r)))
+        }
+        Expr::Binary(BinOp::Mul, a, b) => match &**a {
+            a if is_number(a, -1) => Some((**b).clone()),
+            Expr::Number(_) => negated(a).map(|a| mul(a, (**b).clone())),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// Builds calls of the inbuilt functions, with the same parameters as the functions of `Value`
+macro_rules! calls {
+    ($($kind:ident: [$($name:literal),* $(,)?]),* $(,)?) => {{
+        let mut map: HashMap<&'static str, Function<Expr>> = HashMap::new();
+        $($(map.insert($name, calls!(@$kind $name));)*)*
+        map
+    }};
+    (@OneParam $name:literal) => {
+        Function::OneParam(|x| Ok(call($name, vec![x])))
+    };
+    (@TwoParam $name:literal) => {
+        Function::TwoParam(|b, a| Ok(call($name, vec![a, b])))
+    };
+    (@Variadic $name:literal) => {
+        Function::Variadic(|args| Ok(call($name, args)), 0)
+    };
+}
+
+static EXPR_FUNCTIONS: Lazy<HashMap<&str, Function<Expr>>> = Lazy::new(|| {
+    calls! {
+        OneParam: [
+            "sin", "cos", "tan", "ctan", "sec", "csc", "asin", "acos", "atan", "deg", "rad",
+            "sinh", "cosh", "tanh", "asinh", "acosh", "atanh", "exp", "ln", "log10", "sqrt",
+            "cbrt", "abs", "sign", "floor", "ceil", "trunc", "re", "im", "arg", "conj",
+            "isprime", "nextprime", "totient", "factor",
+        ],
+        TwoParam: [
+            "atan2", "log", "round", "max", "min", "polar", "dot", "cross", "solve", "ncr",
+            "choose", "npr", "modinv", "polyval", "polydiv",
+        ],
+        Variadic: [
+            "count", "mean", "median", "mode", "variance", "pvariance", "stdev", "pstdev",
+            "geomean", "harmean", "range", "percentile", "fv", "pv", "pmt", "nper", "rate",
+            "npv", "irr", "xnpv", "len", "sum", "prod", "norm", "transpose", "det", "inv",
+            "rank", "gcd", "lcm", "modpow", "poly", "polyfit", "polyder", "roots", "coeffs",
+            "degree",
+        ],
+    }
+});
+
+impl Number for Expr {
+    fn from_literal(s: &str, mode: NumberMode) -> Result<Self, Error> {
+        Value::from_literal(s, mode).map(Expr::Number)
+    }
+
+    fn from_integer(n: BigInt, mode: NumberMode) -> Result<Self, Error> {
+        Value::from_integer(n, mode).map(Expr::Number)
+    }
+
+    /// Every name that is not a constant or a function is a variable
+    fn constant(name: &str) -> Option<Self> {
+        if CONSTANTS.contains_key(name) {
+            Some(Expr::Constant(name.to_string()))
+        } else if EXPR_FUNCTIONS.contains_key(name) {
+            None
+        } else {
+            Some(Expr::Variable(name.to_string()))
+        }
+    }
+
+    fn function(name: &str) -> Option<Function<Self>> {
+        EXPR_FUNCTIONS.get(name).cloned()
+    }
+
+    fn checked_add(&self, rhs: &Self) -> Result<Self, Error> {
+        Ok(binary(BinOp::Add, self.clone(), rhs.clone()))
+    }
+
+    fn checked_sub(&self, rhs: &Self) -> Result<Self, Error> {
+        Ok(binary(BinOp::Sub, self.clone(), rhs.clone()))
+    }
+
+    fn checked_mul(&self, rhs: &Self) -> Result<Self, Error> {
+        Ok(binary(BinOp::Mul, self.clone(), rhs.clone()))
+    }
+
+    fn checked_div(&self, rhs: &Self) -> Result<Self, Error> {
+        Ok(binary(BinOp::Div, self.clone(), rhs.clone()))
+    }
+
+    fn checked_pow(&self, rhs: &Self) -> Result<Self, Error> {
+        Ok(binary(BinOp::Pow, self.clone(), rhs.clone()))
+    }
+
+    fn plus_minus(&self, rhs: &Self) -> Result<Self, Error> {
+        Ok(binary(BinOp::PlusMinus, self.clone(), rhs.clone()))
+    }
+
+    fn convert(&self, target: &Self) -> Result<Self, Error> {
+        Ok(binary(BinOp::Convert, self.clone(), target.clone()))
+    }
+
+    fn index(&self, i: &Self) -> Result<Self, Error> {
+        Ok(binary(BinOp::Index, self.clone(), i.clone()))
+    }
+
+    fn matmul(&self, rhs: &Self) -> Result<Self, Error> {
+        Ok(binary(BinOp::MatMul, self.clone(), rhs.clone()))
+    }
+
+    fn sqrt(&self) -> Result<Self, Error> {
+        Ok(call("sqrt", vec![self.clone()]))
+    }
+
+    fn cbrt(&self) -> Result<Self, Error> {
+        Ok(call("cbrt", vec![self.clone()]))
+    }
+
+    fn abs(&self) -> Result<Self, Error> {
+        Ok(call("abs", vec![self.clone()]))
+    }
+
+    fn sin(&self) -> Result<Self, Error> {
+        Ok(call("sin", vec![self.clone()]))
+    }
+
+    fn cos(&self) -> Result<Self, Error> {
+        Ok(call("cos", vec![self.clone()]))
+    }
+
+    fn tan(&self) -> Result<Self, Error> {
+        Ok(call("tan", vec![self.clone()]))
+    }
+
+    /// Expressions have no order until they are evaluated
+    fn cmp_real(&self, _other: &Self) -> Result<Ordering, Error> {
+        Err(Error::NotSupported)
+    }
+
+    fn exp(&self) -> Result<Self, Error> {
+        Ok(call("exp", vec![self.clone()]))
+    }
+
+    fn ln(&self) -> Result<Self, Error> {
+        Ok(call("ln", vec![self.clone()]))
+    }
+
+    fn atan(&self) -> Result<Self, Error> {
+        Ok(call("atan", vec![self.clone()]))
+    }
+
+    fn floor(&self) -> Result<Self, Error> {
+        Ok(call("floor", vec![self.clone()]))
+    }
+}
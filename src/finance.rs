@@ -0,0 +1,351 @@
+//! Time value of money, with the conventions of spreadsheets:
+//! money paid out is negative and money received positive,
+//! and `when` is 0 for payments at the end of each period and 1 for payments at the start.
+
+use rust_decimal::{prelude::*, MathematicalOps};
+
+use crate::{error::Error, roots};
+
+// Most iterations of the solvers before they give up
+const MAX_ITERATIONS: usize = 100;
+// The number of times `irr` halves and doubles the growth factor looking for a sign change
+const MAX_EXPANSIONS: usize = 64;
+
+/// The required arguments of a function followed by its optional ones, which default to 0
+pub(crate) fn arguments<const COUNT: usize>(
+    values: &[Decimal],
+    required: usize,
+) -> Result<[Decimal; COUNT], Error> {
+    if values.len() < required {
+        return Err(Error::NotEnoughArgs);
+    } else if values.len() > COUNT {
+        return Err(Error::TooManyArgs);
+    }
+    let mut arguments = [Decimal::ZERO; COUNT];
+    arguments[..values.len()].copy_from_slice(values);
+    Ok(arguments)
+}
+
+fn overflow(x: Option<Decimal>) -> Result<Decimal, Error> {
+    x.ok_or(Error::Overflow)
+}
+
+// How much one unit grows in `periods` periods, `(1 + rate)^periods`
+fn growth(rate: Decimal, periods: Decimal) -> Result<Decimal, Error> {
+    let base = overflow(Decimal::ONE.checked_add(rate))?;
+    if base <= Decimal::ZERO {
+        return Err(Error::DomainError);
+    }
+    overflow(base.checked_powd(periods))
+}
+
+// The factor of the payments when they are made at the start of the periods
+fn timing(rate: Decimal, when: Decimal) -> Result<Decimal, Error> {
+    if when.is_zero() {
+        Ok(Decimal::ONE)
+    } else {
+        overflow(Decimal::ONE.checked_add(rate))
+    }
+}
+
+// The value the payments add up to at the end, `((1 + rate)^nper - 1) / rate`
+fn annuity(rate: Decimal, nper: Decimal, when: Decimal) -> Result<Decimal, Error> {
+    if rate.is_zero() {
+        return Ok(nper);
+    }
+    let grown = overflow(growth(rate, nper)?.checked_sub(Decimal::ONE))?;
+    let sum = overflow(grown.checked_div(rate))?;
+    overflow(sum.checked_mul(timing(rate, when)?))
+}
+
+/// The future value of an investment, with a present value `pv` and `nper` payments of `pmt`
+/// ## Example
+/// ```rust
+/// use calc::Context;
+///
+/// let context = Context::new();
+/// // Saving 100 a month for 10 years at 5% a year
+/// let fv = context.eval("round(fv(0.05/12, 120, -100), 2)").unwrap();
+/// assert_eq!(fv.to_string(), "15528.23");
+/// // The monthly payment of a 200000 mortgage over 30 years at 6% a year
+/// let pmt = context.eval("round(pmt(0.06/12, 360, 200000), 2)").unwrap();
+/// assert_eq!(pmt.to_string(), "-1199.10");
+/// ```
+pub fn fv(
+    rate: Decimal,
+    nper: Decimal,
+    pmt: Decimal,
+    pv: Decimal,
+    when: Decimal,
+) -> Result<Decimal, Error> {
+    let grown = overflow(pv.checked_mul(growth(rate, nper)?))?;
+    let paid = overflow(pmt.checked_mul(annuity(rate, nper, when)?))?;
+    overflow(grown.checked_add(paid)).map(|fv| -fv)
+}
+
+/// The present value of `nper` payments of `pmt` and a future value `fv`
+pub fn pv(
+    rate: Decimal,
+    nper: Decimal,
+    pmt: Decimal,
+    fv: Decimal,
+    when: Decimal,
+) -> Result<Decimal, Error> {
+    let paid = overflow(pmt.checked_mul(annuity(rate, nper, when)?))?;
+    let total = overflow((-fv).checked_sub(paid))?;
+    overflow(total.checked_div(growth(rate, nper)?))
+}
+
+/// The payment for each period that pays off a present value `pv`, leaving a future value `fv`
+pub fn pmt(
+    rate: Decimal,
+    nper: Decimal,
+    pv: Decimal,
+    fv: Decimal,
+    when: Decimal,
+) -> Result<Decimal, Error> {
+    let grown = overflow(pv.checked_mul(growth(rate, nper)?))?;
+    let annuity = annuity(rate, nper, when)?;
+    if annuity.is_zero() {
+        return Err(Error::DomainError);
+    }
+    overflow(overflow((-fv).checked_sub(grown))?.checked_div(annuity))
+}
+
+/// The number of periods it takes payments of `pmt` to go from `pv` to `fv`
+pub fn nper(
+    rate: Decimal,
+    pmt: Decimal,
+    pv: Decimal,
+    fv: Decimal,
+    when: Decimal,
+) -> Result<Decimal, Error> {
+    if rate.is_zero() {
+        if pmt.is_zero() {
+            return Err(Error::DomainError);
+        }
+        return overflow(overflow((-fv).checked_sub(pv))?.checked_div(pmt));
+    }
+    let payment = overflow(pmt.checked_mul(timing(rate, when)?))?;
+    let numer = overflow(payment.checked_sub(overflow(fv.checked_mul(rate))?))?;
+    let denom = overflow(payment.checked_add(overflow(pv.checked_mul(rate))?))?;
+    let ratio = overflow(numer.checked_div(denom)).map_err(|_| Error::DomainError)?;
+    if ratio <= Decimal::ZERO || rate <= -Decimal::ONE {
+        return Err(Error::DomainError);
+    }
+    let base = overflow(Decimal::ONE.checked_add(rate))?;
+    overflow(ratio.ln().checked_div(base.ln()))
+}
+
+// A root of `f` near `guess`, with the secant method
+fn solve(f: impl Fn(Decimal) -> Result<Decimal, Error>, guess: Decimal) -> Result<Decimal, Error> {
+    let tolerance = Decimal::new(1, 20);
+    let (mut x0, mut x1) = (guess, overflow(guess.checked_add(Decimal::new(1, 4)))?);
+    let mut y0 = f(x0)?;
+    for _ in 0..MAX_ITERATIONS {
+        let y1 = f(x1).map_err(|_| Error::NoConvergence)?;
+        if y1.is_zero() {
+            return Ok(x1.normalize());
+        }
+        let step = y1
+            .checked_sub(y0)
+            .and_then(|dy| dy.checked_div(x1.checked_sub(x0)?))
+            .filter(|slope| !slope.is_zero())
+            .and_then(|slope| y1.checked_div(slope))
+            .ok_or(Error::NoConvergence)?;
+        (x0, y0) = (x1, y1);
+        x1 = x1.checked_sub(step).ok_or(Error::NoConvergence)?;
+        if step.abs() < tolerance {
+            return Ok(x1.round_dp(20).normalize());
+        }
+    }
+    Err(Error::NoConvergence)
+}
+
+/// The interest rate per period that takes payments of `pmt` from `pv` to `fv` in `nper` periods.
+/// It is found iteratively from `guess`, 10% when it is 0, and it is an error if it doesn't converge.
+pub fn rate(
+    nper: Decimal,
+    pmt: Decimal,
+    pv: Decimal,
+    fv: Decimal,
+    when: Decimal,
+    guess: Decimal,
+) -> Result<Decimal, Error> {
+    let guess = if guess.is_zero() {
+        Decimal::new(1, 1)
+    } else {
+        guess
+    };
+    solve(
+        |rate| overflow(self::fv(rate, nper, pmt, pv, when)?.checked_sub(fv)),
+        guess,
+    )
+}
+
+/// The net present value of cash flows at the end of each period, like the spreadsheet `NPV`
+/// ## Example
+/// ```rust
+/// use calc::Context;
+///
+/// let context = Context::new();
+/// let npv = context.eval("round(npv(0.1, -10000, 3000, 4200, 6800), 2)").unwrap();
+/// assert_eq!(npv.to_string(), "1188.44");
+/// let irr = context.eval("round(irr(-70000, 12000, 15000, 18000, 21000, 26000), 6)").unwrap();
+/// assert_eq!(irr.to_string(), "0.086631");
+/// assert_eq!(
+///     context.eval("irr(100, 200)").unwrap_err().to_string(),
+///     "Argument out of domain"
+/// );
+/// ```
+pub fn npv(rate: Decimal, cashflows: &[Decimal]) -> Result<Decimal, Error> {
+    let mut total = Decimal::ZERO;
+    let mut discount = Decimal::ONE;
+    let factor = overflow(Decimal::ONE.checked_add(rate))?;
+    if factor.is_zero() {
+        return Err(Error::DivisionByZero);
+    }
+    for cashflow in cashflows {
+        discount = overflow(discount.checked_mul(factor))?;
+        total = overflow(total.checked_add(overflow(cashflow.checked_div(discount))?))?;
+    }
+    Ok(total)
+}
+
+// Whether there is money going both ways, without which there is no rate of return
+fn mixed(cashflows: &[Decimal]) -> Result<(), Error> {
+    let positive = cashflows
+        .iter()
+        .any(|x| x.is_sign_positive() && !x.is_zero());
+    let negative = cashflows
+        .iter()
+        .any(|x| x.is_sign_negative() && !x.is_zero());
+    if positive && negative {
+        Ok(())
+    } else {
+        Err(Error::DomainError)
+    }
+}
+
+/// The internal rate of return of cash flows at the start of each period, the rate with an NPV of 0
+pub fn irr(cashflows: &[Decimal]) -> Result<Decimal, Error> {
+    mixed(cashflows)?;
+    let (first, rest) = cashflows.split_first().ok_or(Error::NotEnoughArgs)?;
+    let value = |rate: Decimal| first.checked_add(npv(rate, rest).ok()?);
+    // Rates are only above -100%, the NPV is a polynomial in `1 / (1 + rate)`, so the
+    // growth factor `1 + rate` is halved and doubled away from 1.1 until the NPV changes sign
+    let changes_sign = |a: Decimal, b: Decimal| (a < Decimal::ZERO) != (b < Decimal::ZERO);
+    let start = Decimal::new(11, 1);
+    let (mut below, mut above) = (start, start);
+    let initial = value(Decimal::new(1, 1)).ok_or(Error::NoConvergence)?;
+    let (mut value_below, mut value_above) = (Some(initial), Some(initial));
+    for _ in 0..MAX_EXPANSIONS {
+        if let Some(previous) = value_above {
+            let factor = above.checked_mul(Decimal::TWO);
+            let next = factor.and_then(|f| value(f - Decimal::ONE));
+            if let (Some(factor), Some(next)) = (factor, next) {
+                if changes_sign(previous, next) {
+                    return bracketed(value, above - Decimal::ONE, factor - Decimal::ONE);
+                }
+                above = factor;
+            }
+            value_above = next;
+        }
+        if let Some(previous) = value_below {
+            let factor = below / Decimal::TWO;
+            let next = value(factor - Decimal::ONE);
+            if let Some(next) = next {
+                if changes_sign(previous, next) {
+                    return bracketed(value, factor - Decimal::ONE, below - Decimal::ONE);
+                }
+                below = factor;
+            }
+            value_below = next;
+        }
+    }
+    Err(Error::NoConvergence)
+}
+
+// The root between two rates where the NPV changes sign
+fn bracketed(
+    value: impl Fn(Decimal) -> Option<Decimal>,
+    a: Decimal,
+    b: Decimal,
+) -> Result<Decimal, Error> {
+    roots::brent(value, a, b).map(|rate| rate.round_dp(20).normalize())
+}
+
+/// The net present value of cash flows on days, counted from the first one with 365 days a year.
+/// The arguments alternate between a cash flow and its day, like the days of a spreadsheet date.
+pub fn xnpv(rate: Decimal, cashflows: &[Decimal]) -> Result<Decimal, Error> {
+    if !cashflows.len().is_multiple_of(2) {
+        return Err(Error::NotEnoughArgs);
+    }
+    let Some(start) = cashflows.get(1) else {
+        return Ok(Decimal::ZERO);
+    };
+    let year = Decimal::from(365);
+    cashflows.chunks(2).try_fold(Decimal::ZERO, |total, pair| {
+        let years = overflow(overflow(pair[1].checked_sub(*start))?.checked_div(year))?;
+        let value = overflow(pair[0].checked_div(growth(rate, years)?))?;
+        overflow(total.checked_add(value))
+    })
+}
+
+/// One row of an amortization schedule
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Installment {
+    pub period: u32,
+    pub payment: Decimal,
+    pub interest: Decimal,
+    /// The part of the payment that repays the principal
+    pub principal: Decimal,
+    /// What is left to repay after the payment
+    pub balance: Decimal,
+}
+
+/// The schedule of a loan of `principal` paid back in `n` equal payments,
+/// with interest `rate` per period on what is left
+/// ## Example
+/// ```rust
+/// use calc::finance::amortization;
+/// use rust_decimal::Decimal;
+///
+/// let schedule = amortization(Decimal::from(1000), Decimal::new(1, 2), 12).unwrap();
+/// assert_eq!(schedule.len(), 12);
+/// assert_eq!(schedule[0].interest, Decimal::from(10));
+/// assert_eq!(schedule[0].payment.round_dp(2), Decimal::new(8885, 2));
+/// assert!(schedule[11].balance.is_zero());
+/// ```
+pub fn amortization(principal: Decimal, rate: Decimal, n: u32) -> Result<Vec<Installment>, Error> {
+    if n == 0 {
+        return Err(Error::DomainError);
+    }
+    let payment = -pmt(
+        rate,
+        Decimal::from(n),
+        principal,
+        Decimal::ZERO,
+        Decimal::ZERO,
+    )?;
+    let mut balance = principal;
+    let mut schedule = Vec::new();
+    for period in 1..=n {
+        let interest = overflow(balance.checked_mul(rate))?;
+        // The last payment repays exactly what is left
+        let repaid = if period == n {
+            balance
+        } else {
+            overflow(payment.checked_sub(interest))?
+        };
+        balance = overflow(balance.checked_sub(repaid))?;
+        schedule.push(Installment {
+            period,
+            payment: overflow(repaid.checked_add(interest))?,
+            interest,
+            principal: repaid,
+            balance,
+        });
+    }
+    Ok(schedule)
+}
@@ -0,0 +1,183 @@
+use num_bigint::BigInt;
+use rust_decimal::prelude::*;
+
+use crate::{
+    complex::{format_rectangular, Complex},
+    rational::{format_fraction, to_decimal},
+    value::Value,
+};
+
+// Maximum number of digits written after the radix point
+const FRACTION_DIGITS: usize = 16;
+
+/// The base used for writing numbers
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum Radix {
+    Binary,
+    Octal,
+    #[default]
+    Decimal,
+    Hexadecimal,
+}
+
+impl Radix {
+    /// The numeric base of the radix
+    pub fn base(self) -> u32 {
+        match self {
+            Radix::Binary => 2,
+            Radix::Octal => 8,
+            Radix::Decimal => 10,
+            Radix::Hexadecimal => 16,
+        }
+    }
+
+    /// The literal prefix of the radix, the same one the parser accepts
+    pub fn prefix(self) -> &'static str {
+        match self {
+            Radix::Binary => "0b",
+            Radix::Octal => "0o",
+            Radix::Decimal => "",
+            Radix::Hexadecimal => "0x",
+        }
+    }
+
+    /// Looks up a radix by name, e.g. `hex`, `oct`, `bin`, `dec` or the base itself
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "bin" | "binary" | "2" => Some(Radix::Binary),
+            "oct" | "octal" | "8" => Some(Radix::Octal),
+            "dec" | "decimal" | "10" => Some(Radix::Decimal),
+            "hex" | "hexadecimal" | "16" => Some(Radix::Hexadecimal),
+            _ => None,
+        }
+    }
+}
+
+/// Formats a number in the given radix, using the same prefixes as the parser.
+/// Fractional parts are written with at most 16 digits.
+/// ## Example
+/// ```rust
+/// use calc::format::{format_radix, Radix};
+/// use rust_decimal::prelude::*;
+///
+/// assert_eq!(format_radix(Decimal::from(255), Radix::Hexadecimal), "0xff");
+/// assert_eq!(format_radix(Decimal::from(-10), Radix::Binary), "-0b1010");
+/// assert_eq!(format_radix(Decimal::from_f64(2.5).unwrap(), Radix::Binary), "0b10.1");
+/// ```
+pub fn format_radix(n: Decimal, radix: Radix) -> String {
+    if radix == Radix::Decimal {
+        return n.normalize().to_string();
+    }
+    let mut s = String::new();
+    if n.is_sign_negative() && !n.is_zero() {
+        s.push('-');
+    }
+    s.push_str(radix.prefix());
+    let n = n.abs();
+    // Decimal can hold at most 96 bits, so the integer part always fits
+    let int = n.trunc().to_u128().unwrap();
+    match radix {
+        Radix::Binary => s.push_str(&format!("{:b}", int)),
+        Radix::Octal => s.push_str(&format!("{:o}", int)),
+        _ => s.push_str(&format!("{:x}", int)),
+    }
+    let mut fract = n.fract();
+    if !fract.is_zero() {
+        s.push('.');
+        let base = Decimal::from(radix.base());
+        for _ in 0..FRACTION_DIGITS {
+            fract *= base;
+            let digit = fract.trunc().to_u32().unwrap();
+            s.push(std::char::from_digit(digit, radix.base()).unwrap());
+            fract = fract.fract();
+            if fract.is_zero() {
+                break;
+            }
+        }
+    }
+    s
+}
+
+/// How complex numbers are written
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum ComplexFormat {
+    /// `3 + 4i`
+    #[default]
+    Rectangular,
+    /// `5 ∠ 0.927…`, modulus and argument in radians
+    Polar,
+}
+
+/// How exact fractions are written
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum FractionFormat {
+    /// `3/2`
+    #[default]
+    Improper,
+    /// `1 1/2`
+    Mixed,
+    /// `1.5`, rounded like any other `Decimal`
+    Decimal,
+}
+
+/// Options for writing values
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct FormatOptions {
+    pub radix: Radix,
+    pub complex: ComplexFormat,
+    pub fraction: FractionFormat,
+}
+
+/// Formats a value with the given options
+/// ## Example
+/// ```rust
+/// use calc::{complex::Complex, format::{format_value, ComplexFormat, FormatOptions}, Value};
+/// use rust_decimal::prelude::*;
+///
+/// let z = Value::from(Complex::new(Decimal::from(3), Decimal::from(-4)));
+/// assert_eq!(format_value(&z, &FormatOptions::default()), "3 - 4i");
+/// let polar = FormatOptions { complex: ComplexFormat::Polar, ..Default::default() };
+/// assert!(format_value(&z, &polar).starts_with("5 ∠ -0.927"));
+/// ```
+pub fn format_value(value: &Value, options: &FormatOptions) -> String {
+    let part = |x: Decimal| format_radix(x, options.radix);
+    match value {
+        Value::Real(x) => part(*x),
+        Value::Complex(z) => match options.complex {
+            ComplexFormat::Rectangular => format_rectangular(z, part),
+            ComplexFormat::Polar => format_polar(z, part),
+        },
+        Value::Rational(r) => match options.fraction {
+            FractionFormat::Improper => format_fraction(r, false, |n| format_int(n, options.radix)),
+            FractionFormat::Mixed => format_fraction(r, true, |n| format_int(n, options.radix)),
+            FractionFormat::Decimal => match to_decimal(r) {
+                Ok(x) => part(x),
+                Err(_) => format_fraction(r, false, |n| format_int(n, options.radix)),
+            },
+        },
+        Value::Quantity(q) => format!("{} {}", part(q.value), q.unit),
+        Value::List(items) => {
+            let items: Vec<String> = items.iter().map(|x| format_value(x, options)).collect();
+            format!("[{}]", items.join(", "))
+        }
+        Value::Polynomial(p) => p.format(|x| format_value(x, options)),
+    }
+}
+
+/// Formats an integer of any size in the given radix
+pub fn format_int(n: &BigInt, radix: Radix) -> String {
+    let digits = n.magnitude().to_str_radix(radix.base());
+    if n.sign() == num_bigint::Sign::Minus {
+        format!("-{}{}", radix.prefix(), digits)
+    } else {
+        format!("{}{}", radix.prefix(), digits)
+    }
+}
+
+fn format_polar(z: &Complex, part: impl Fn(Decimal) -> String) -> String {
+    match z.abs() {
+        Ok(r) => format!("{} ∠ {}", part(r), part(z.arg())),
+        // The modulus overflows, so fall back to the parts
+        Err(_) => format_rectangular(z, part),
+    }
+}
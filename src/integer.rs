@@ -0,0 +1,327 @@
+use std::fmt::{Display, Formatter};
+
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::{error::Error, list, rational, value::Value};
+
+// Largest `n` for `ncr` and `npr`, the results get far too big to be useful past it
+const MAX_FACTORIAL: u64 = 100_000;
+// Most steps of Pollard's rho before factoring gives up on a number
+const MAX_STEPS: usize = 100_000;
+
+/// Reads a value as an exact integer, anything with a fractional part is an error
+pub fn to_integer(value: &Value) -> Result<BigInt, Error> {
+    let r = match value {
+        Value::Rational(r) => r.clone(),
+        _ => rational::from_decimal(value.as_real()?),
+    };
+    if r.is_integer() {
+        Ok(r.to_integer())
+    } else {
+        Err(Error::ExpectedInteger)
+    }
+}
+
+/// An exact integer as a value
+pub fn from_integer(n: BigInt) -> Value {
+    Value::Rational(BigRational::from_integer(n))
+}
+
+// The integer arguments of a variadic function, which needs at least one
+pub(crate) fn integers(args: Vec<Value>) -> Result<Vec<BigInt>, Error> {
+    let args = list::elements(args);
+    if args.is_empty() {
+        return Err(Error::NotEnoughArgs);
+    }
+    args.iter().map(to_integer).collect()
+}
+
+// Exactly `count` integer arguments, for the functions with more than two parameters
+pub(crate) fn arguments<const COUNT: usize>(args: Vec<Value>) -> Result<[BigInt; COUNT], Error> {
+    if args.len() < COUNT {
+        return Err(Error::NotEnoughArgs);
+    } else if args.len() > COUNT {
+        return Err(Error::TooManyArgs);
+    }
+    let integers = args
+        .iter()
+        .map(to_integer)
+        .collect::<Result<Vec<_>, Error>>()?;
+    Ok(integers.try_into().unwrap())
+}
+
+fn non_negative(n: &BigInt) -> Result<(), Error> {
+    if n.is_negative() {
+        Err(Error::DomainError)
+    } else {
+        Ok(())
+    }
+}
+
+/// The greatest common divisor, always positive
+/// ## Example
+/// ```rust
+/// use calc::Context;
+///
+/// let context = Context::new();
+/// assert_eq!(context.eval("gcd(12, 18, 27)").unwrap().to_string(), "3");
+/// assert_eq!(context.eval("lcm(4, 6)").unwrap().to_string(), "12");
+/// assert_eq!(context.eval("choose(52, 5)").unwrap().to_string(), "2598960");
+/// assert_eq!(context.eval("modpow(4, 13, 497)").unwrap().to_string(), "445");
+/// assert_eq!(context.eval("gcd(1.5, 3)").unwrap_err().to_string(), "Expected an integer");
+/// ```
+pub fn gcd(values: &[BigInt]) -> BigInt {
+    values.iter().fold(BigInt::zero(), |a, b| a.gcd(b))
+}
+
+/// The least common multiple, always positive
+pub fn lcm(values: &[BigInt]) -> BigInt {
+    values.iter().fold(BigInt::one(), |a, b| a.lcm(b))
+}
+
+/// The number of ways to choose `k` of `n` things, regardless of their order
+pub fn ncr(n: &BigInt, k: &BigInt) -> Result<BigInt, Error> {
+    non_negative(n)?;
+    non_negative(k)?;
+    if k > n {
+        return Ok(BigInt::zero());
+    }
+    // The smaller of k and n - k multiplies fewer numbers
+    let k = k.min(&(n - k)).clone();
+    let count = k
+        .to_u64()
+        .filter(|k| *k <= MAX_FACTORIAL)
+        .ok_or(Error::Overflow)?;
+    let mut result = BigInt::one();
+    for i in 0..count {
+        result = result * (n - i) / (i + 1);
+    }
+    Ok(result)
+}
+
+/// The number of ways to arrange `k` of `n` things
+pub fn npr(n: &BigInt, k: &BigInt) -> Result<BigInt, Error> {
+    non_negative(n)?;
+    non_negative(k)?;
+    if k > n {
+        return Ok(BigInt::zero());
+    }
+    let count = k
+        .to_u64()
+        .filter(|k| *k <= MAX_FACTORIAL)
+        .ok_or(Error::Overflow)?;
+    Ok((0..count).fold(BigInt::one(), |result, i| result * (n - i)))
+}
+
+/// Raises `base` to `exponent` modulo `modulus`, a negative exponent uses the modular inverse
+pub fn modpow(base: &BigInt, exponent: &BigInt, modulus: &BigInt) -> Result<BigInt, Error> {
+    if !modulus.is_positive() {
+        return Err(Error::DomainError);
+    }
+    let base = if exponent.is_negative() {
+        modinv(base, modulus)?
+    } else {
+        base.mod_floor(modulus)
+    };
+    Ok(base.modpow(&exponent.abs(), modulus))
+}
+
+/// The `x` for which `a * x` is 1 modulo `modulus`, an error if `a` and `modulus` share a factor
+pub fn modinv(a: &BigInt, modulus: &BigInt) -> Result<BigInt, Error> {
+    if !modulus.is_positive() {
+        return Err(Error::DomainError);
+    }
+    let gcd = a.extended_gcd(modulus);
+    if !gcd.gcd.is_one() {
+        return Err(Error::DomainError);
+    }
+    Ok(gcd.x.mod_floor(modulus))
+}
+
+// The primes used for trial division and as Miller-Rabin bases
+const SMALL_PRIMES: &[u32] = &[
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+];
+
+/// Whether `n` is prime.
+/// Miller-Rabin with the first 25 primes as bases, certain for any `n` below 3.3 × 10^24
+/// and wrong with a vanishingly small probability above.
+pub fn is_prime(n: &BigInt) -> bool {
+    if *n < BigInt::from(2) {
+        return false;
+    }
+    for p in SMALL_PRIMES {
+        if n == &BigInt::from(*p) {
+            return true;
+        } else if n.is_multiple_of(&BigInt::from(*p)) {
+            return false;
+        }
+    }
+    let one = BigInt::one();
+    let n_1 = n - &one;
+    let twos = n_1.trailing_zeros().unwrap_or(0);
+    let d = &n_1 >> twos;
+    'bases: for p in SMALL_PRIMES {
+        let mut x = BigInt::from(*p).modpow(&d, n);
+        if x == one || x == n_1 {
+            continue;
+        }
+        for _ in 1..twos {
+            x = &x * &x % n;
+            if x == n_1 {
+                continue 'bases;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+/// The smallest prime larger than `n`
+pub fn next_prime(n: &BigInt) -> BigInt {
+    let mut candidate = n + 1;
+    while !is_prime(&candidate) {
+        candidate += 1;
+    }
+    candidate
+}
+
+// A nontrivial factor of a composite odd `n`, with Pollard's rho
+fn pollard_rho(n: &BigInt) -> Result<BigInt, Error> {
+    let one = BigInt::one();
+    let mut c = one.clone();
+    let mut steps = 0;
+    loop {
+        let f = |x: &BigInt| (x * x + &c) % n;
+        let (mut x, mut y) = (BigInt::from(2), BigInt::from(2));
+        let mut d = one.clone();
+        while d.is_one() {
+            steps += 1;
+            if steps > MAX_STEPS {
+                return Err(Error::NoConvergence);
+            }
+            x = f(&x);
+            y = f(&f(&y));
+            d = (&x - &y).abs().gcd(n);
+        }
+        if &d != n {
+            return Ok(d);
+        }
+        // The sequence cycled without finding a factor, try another one
+        c += 1;
+    }
+}
+
+// Adds the prime factors of `n` to the list
+fn split(n: BigInt, factors: &mut Vec<BigInt>) -> Result<(), Error> {
+    if n.is_one() {
+        return Ok(());
+    }
+    if is_prime(&n) {
+        factors.push(n);
+        return Ok(());
+    }
+    let d = pollard_rho(&n)?;
+    let rest = &n / &d;
+    split(d, factors)?;
+    split(rest, factors)
+}
+
+/// The prime factorization of an integer, written like `2^3 × 5`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Factors {
+    /// Whether the number is negative, which is written as a factor of `-1`
+    pub negative: bool,
+    /// The prime factors in increasing order, with their multiplicities
+    pub factors: Vec<(BigInt, u32)>,
+}
+
+impl Display for Factors {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let mut parts: Vec<String> = self
+            .factors
+            .iter()
+            .map(|(p, e)| match e {
+                1 => p.to_string(),
+                _ => format!("{}^{}", p, e),
+            })
+            .collect();
+        if self.negative {
+            parts.insert(0, "-1".to_string());
+        }
+        if parts.is_empty() {
+            // One is the empty product
+            parts.push("1".to_string());
+        }
+        write!(f, "{}", parts.join(" × "))
+    }
+}
+
+/// Factors an integer into primes, with trial division by small primes then Pollard's rho.
+/// Zero has no factorization, and numbers with only huge prime factors take too long.
+/// ## Example
+/// ```rust
+/// use calc::integer::factor;
+/// use num_bigint::BigInt;
+///
+/// assert_eq!(factor(&BigInt::from(40)).unwrap().to_string(), "2^3 × 5");
+/// assert_eq!(factor(&BigInt::from(-91)).unwrap().to_string(), "-1 × 7 × 13");
+/// // The product of two primes of a toy RSA key
+/// let n = BigInt::from(1_000_003u64) * BigInt::from(998_244_353u64);
+/// assert_eq!(factor(&n).unwrap().to_string(), "1000003 × 998244353");
+/// ```
+pub fn factor(n: &BigInt) -> Result<Factors, Error> {
+    if n.is_zero() {
+        return Err(Error::DomainError);
+    }
+    let mut rest = n.abs();
+    let mut primes = Vec::new();
+    for p in SMALL_PRIMES {
+        let p = BigInt::from(*p);
+        while rest.is_multiple_of(&p) {
+            rest /= &p;
+            primes.push(p.clone());
+        }
+    }
+    split(rest, &mut primes)?;
+    primes.sort();
+    let mut factors: Vec<(BigInt, u32)> = Vec::new();
+    for p in primes {
+        match factors.last_mut() {
+            Some((q, e)) if *q == p => *e += 1,
+            _ => factors.push((p, 1)),
+        }
+    }
+    Ok(Factors {
+        negative: n.is_negative(),
+        factors,
+    })
+}
+
+impl Factors {
+    /// The prime factors repeated as many times as they divide the number, after a `-1`
+    /// for a negative one, so their product is the number
+    pub fn to_list(&self) -> Value {
+        let sign = self.negative.then(|| BigInt::from(-1));
+        let primes = self
+            .factors
+            .iter()
+            .flat_map(|(p, e)| std::iter::repeat_n(p.clone(), *e as usize));
+        Value::List(sign.into_iter().chain(primes).map(from_integer).collect())
+    }
+}
+
+/// Euler's totient, the count of the integers up to `n` that share no factor with it
+pub fn totient(n: &BigInt) -> Result<BigInt, Error> {
+    if !n.is_positive() {
+        return Err(Error::DomainError);
+    }
+    let factors = factor(n)?;
+    Ok(factors
+        .factors
+        .iter()
+        .fold(n.clone(), |phi, (p, _)| phi / p * (p - 1)))
+}
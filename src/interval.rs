@@ -0,0 +1,611 @@
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    f64::consts::{FRAC_PI_2, PI, TAU},
+    fmt::{Display, Formatter},
+};
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::ToPrimitive;
+use once_cell::sync::Lazy;
+
+use crate::{
+    error::Error,
+    number::Number,
+    parse::NumberMode,
+    rational,
+    token::{functions, Function},
+};
+
+/// A closed interval of real numbers, `[lo, hi]`.
+/// Evaluating an expression with intervals gives an interval that is
+/// guaranteed to contain every possible result: every bound is rounded outwards.
+/// Each appearance of a variable is treated independently, so `x - x`
+/// is only zero for degenerate intervals.
+/// ## Example
+/// ```rust
+/// use calc::{interval::Interval, Context};
+///
+/// let mut context = Context::<Interval>::default();
+/// context.set("x", Interval::new(1.9, 2.1));
+/// let area = context.eval("x^2").unwrap();
+/// assert!(area.lo <= 3.61 && area.hi >= 4.41);
+/// assert!(area.hi - area.lo < 0.81);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Interval {
+    pub lo: f64,
+    pub hi: f64,
+}
+
+impl Interval {
+    /// The interval between two bounds, in any order
+    pub fn new(a: f64, b: f64) -> Self {
+        Self {
+            lo: a.min(b),
+            hi: a.max(b),
+        }
+    }
+
+    /// The interval containing a single number
+    pub fn point(x: f64) -> Self {
+        Self { lo: x, hi: x }
+    }
+
+    /// The whole real line
+    pub fn entire() -> Self {
+        Self {
+            lo: f64::NEG_INFINITY,
+            hi: f64::INFINITY,
+        }
+    }
+
+    pub fn contains(&self, x: f64) -> bool {
+        self.lo <= x && x <= self.hi
+    }
+
+    /// The smallest interval containing both intervals
+    pub fn hull(&self, other: &Interval) -> Interval {
+        Interval {
+            lo: self.lo.min(other.lo),
+            hi: self.hi.max(other.hi),
+        }
+    }
+
+    pub fn midpoint(&self) -> f64 {
+        self.lo / 2.0 + self.hi / 2.0
+    }
+
+    pub fn width(&self) -> f64 {
+        self.hi - self.lo
+    }
+
+    // Whether a point `p + 2kπ` lies in the interval, for some integer k.
+    // Points just outside count as well, as the multiples of 2π are rounded.
+    fn contains_periodic(&self, p: f64) -> bool {
+        let eps = 4.0 * f64::EPSILON * (1.0 + self.lo.abs() + self.hi.abs());
+        let k = ((self.lo - p) / TAU).ceil();
+        [k - 1.0, k].iter().any(|k| {
+            let point = p + k * TAU;
+            point >= self.lo - eps && point <= self.hi + eps
+        })
+    }
+}
+
+// An interval from bounds that are already rounded, `∞ - ∞` has no result
+fn checked(lo: f64, hi: f64) -> Result<Interval, Error> {
+    if lo.is_nan() || hi.is_nan() {
+        return Err(Error::DomainError);
+    }
+    Ok(Interval { lo, hi })
+}
+
+// Widen the bounds by one unit in the last place, to make up for
+// the rounding of the operation that computed them
+fn outward(lo: f64, hi: f64) -> Result<Interval, Error> {
+    if lo.is_nan() || hi.is_nan() {
+        return Err(Error::DomainError);
+    }
+    Ok(Interval {
+        lo: lo.next_down(),
+        hi: hi.next_up(),
+    })
+}
+
+// Rounding of a computed value `x` towards `down` or `up`, given the sign
+// of the rounding error (the exact result minus `x`)
+fn directed(x: f64, error: f64, up: bool) -> f64 {
+    if x.is_infinite() {
+        // An overflow of finite operands, the exact result is finite
+        return match (up, x > 0.0) {
+            (false, true) => f64::MAX,
+            (true, false) => f64::MIN,
+            _ => x,
+        };
+    }
+    if up && error > 0.0 {
+        x.next_up()
+    } else if !up && error < 0.0 {
+        x.next_down()
+    } else {
+        x
+    }
+}
+
+// `a + b` rounded down or up, with the exact error found by Knuth's two-sum
+fn add(a: f64, b: f64, up: bool) -> f64 {
+    let s = a + b;
+    if a.is_infinite() || b.is_infinite() {
+        return s;
+    }
+    let bb = s - a;
+    directed(s, (a - (s - bb)) + (b - bb), up)
+}
+
+// `a × b` rounded down or up, the exact error comes from a fused multiply-add
+fn mul(a: f64, b: f64, up: bool) -> f64 {
+    // Zero times infinity is zero for bounds
+    if a == 0.0 || b == 0.0 {
+        return 0.0;
+    }
+    let p = a * b;
+    if a.is_infinite() || b.is_infinite() {
+        return p;
+    }
+    directed(p, a.mul_add(b, -p), up)
+}
+
+// `a / b` rounded down or up, the remainder `a - qb` gives the error's sign
+fn div(a: f64, b: f64, up: bool) -> f64 {
+    let q = a / b;
+    if a.is_infinite() || b.is_infinite() {
+        return q;
+    }
+    let remainder = -q.mul_add(b, -a);
+    directed(q, remainder * b.signum(), up)
+}
+
+// The smallest and largest of a few values
+fn bounds(values: &[f64]) -> (f64, f64) {
+    values
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), x| {
+            (lo.min(*x), hi.max(*x))
+        })
+}
+
+// Same as `outward`, with some more room for library functions like `sin`,
+// which are not always correctly rounded
+fn loose(lo: f64, hi: f64) -> Result<Interval, Error> {
+    let result = outward(lo, hi)?;
+    Ok(Interval {
+        lo: result.lo.next_down(),
+        hi: result.hi.next_up(),
+    })
+}
+
+// The tightest interval around an exact number, given its closest float.
+// Literals like `0.1` can not be represented exactly.
+fn enclose(x: f64, exact: &BigRational) -> Interval {
+    match BigRational::from_float(x).map(|r| r.cmp(exact)) {
+        Some(Ordering::Equal) => Interval::point(x),
+        Some(Ordering::Less) => Interval {
+            lo: x,
+            hi: x.next_up(),
+        },
+        Some(Ordering::Greater) => Interval {
+            lo: x.next_down(),
+            hi: x,
+        },
+        None => around(x),
+    }
+}
+
+// An interval around a number that may not be exact
+fn around(x: f64) -> Interval {
+    Interval {
+        lo: x.next_down(),
+        hi: x.next_up(),
+    }
+}
+
+// Sine or cosine, taking the endpoints and the extremes in between into account.
+// `max_at` and `min_at` are where the function is 1 and -1, modulo 2π.
+fn periodic(x: &Interval, f: fn(f64) -> f64, max_at: f64, min_at: f64) -> Result<Interval, Error> {
+    if x.width() >= TAU || x.lo.is_infinite() || x.hi.is_infinite() {
+        return Ok(Interval::new(-1.0, 1.0));
+    }
+    let (lo, hi) = bounds(&[f(x.lo), f(x.hi)]);
+    let mut result = loose(lo, hi)?;
+    if x.contains_periodic(max_at) {
+        result.hi = 1.0;
+    }
+    if x.contains_periodic(min_at) {
+        result.lo = -1.0;
+    }
+    Ok(Interval {
+        lo: result.lo.max(-1.0),
+        hi: result.hi.min(1.0),
+    })
+}
+
+// A function that is increasing on the domain `[min, max]`, applied to the part of `x` inside it
+fn increasing(x: &Interval, f: fn(f64) -> f64, min: f64, max: f64) -> Result<Interval, Error> {
+    if x.hi < min || x.lo > max {
+        return Err(Error::DomainError);
+    }
+    loose(f(x.lo.max(min)), f(x.hi.min(max)))
+}
+
+// -1, 0 or 1, with zero staying zero unlike `f64::signum`
+fn sign(x: f64) -> f64 {
+    if x == 0.0 {
+        0.0
+    } else {
+        x.signum()
+    }
+}
+
+// `a^n` for a non-negative `a`, by squaring with every product rounded the same way
+fn pow_abs(a: f64, mut n: u32, up: bool) -> f64 {
+    let (mut result, mut base) = (1.0, a);
+    while n > 0 {
+        if n & 1 == 1 {
+            result = mul(result, base, up);
+        }
+        n >>= 1;
+        if n > 0 {
+            base = mul(base, base, up);
+        }
+    }
+    result
+}
+
+// Integer powers, which are monotonic on each side of zero
+fn powi(x: &Interval, n: i32) -> Result<Interval, Error> {
+    if n < 0 {
+        return Interval::point(1.0).checked_div(&powi(x, -n)?);
+    }
+    let n = n as u32;
+    if n % 2 == 1 {
+        // Odd powers keep the sign and are increasing
+        let bound = |t: f64, up: bool| {
+            if t < 0.0 {
+                -pow_abs(-t, n, !up)
+            } else {
+                pow_abs(t, n, up)
+            }
+        };
+        return checked(bound(x.lo, false), bound(x.hi, true));
+    }
+    let (small, large) = bounds(&[x.lo.abs(), x.hi.abs()]);
+    let small = if x.contains(0.0) { 0.0 } else { small };
+    checked(pow_abs(small, n, false), pow_abs(large, n, true))
+}
+
+static FUNCTIONS: Lazy<HashMap<&str, Function<Interval>>> = Lazy::new(|| {
+    let mut map = functions::<Interval>();
+    // The generic versions pick one of the arguments, which is wrong for overlapping intervals
+    map.insert(
+        "max",
+        Function::TwoParam(|a: Interval, b: Interval| {
+            Ok(Interval::new(a.lo.max(b.lo), a.hi.max(b.hi)))
+        }),
+    );
+    map.insert(
+        "min",
+        Function::TwoParam(|a: Interval, b: Interval| {
+            Ok(Interval::new(a.lo.min(b.lo), a.hi.min(b.hi)))
+        }),
+    );
+    map.insert(
+        "interval",
+        Function::TwoParam(|b: Interval, a: Interval| Ok(a.hull(&b))),
+    );
+    map
+});
+
+impl Number for Interval {
+    fn from_literal(s: &str, _: NumberMode) -> Result<Self, Error> {
+        Ok(enclose(s.parse::<f64>()?, &rational::parse_exact(s)?))
+    }
+
+    fn from_integer(n: BigInt, _: NumberMode) -> Result<Self, Error> {
+        let x = n.to_f64().ok_or(Error::NumberParseError)?;
+        Ok(enclose(x, &BigRational::from_integer(n)))
+    }
+
+    fn constant(name: &str) -> Option<Self> {
+        match name {
+            "e" => Some(around(std::f64::consts::E)),
+            "pi" | "π" => Some(around(PI)),
+            "∞" => Some(Interval::point(f64::INFINITY)),
+            _ => None,
+        }
+    }
+
+    fn function(name: &str) -> Option<Function<Self>> {
+        FUNCTIONS.get(name).cloned()
+    }
+
+    fn checked_add(&self, rhs: &Self) -> Result<Self, Error> {
+        checked(add(self.lo, rhs.lo, false), add(self.hi, rhs.hi, true))
+    }
+
+    fn checked_sub(&self, rhs: &Self) -> Result<Self, Error> {
+        checked(add(self.lo, -rhs.hi, false), add(self.hi, -rhs.lo, true))
+    }
+
+    fn checked_mul(&self, rhs: &Self) -> Result<Self, Error> {
+        let corners = |up| {
+            [
+                mul(self.lo, rhs.lo, up),
+                mul(self.lo, rhs.hi, up),
+                mul(self.hi, rhs.lo, up),
+                mul(self.hi, rhs.hi, up),
+            ]
+        };
+        checked(bounds(&corners(false)).0, bounds(&corners(true)).1)
+    }
+
+    /// Dividing by an interval that contains zero gives the smallest interval
+    /// containing all the quotients, which is unbounded on at least one side
+    fn checked_div(&self, rhs: &Self) -> Result<Self, Error> {
+        if rhs.lo == 0.0 && rhs.hi == 0.0 {
+            return Err(Error::DivisionByZero);
+        }
+        if !rhs.contains(0.0) {
+            let corners = |up| {
+                [
+                    div(self.lo, rhs.lo, up),
+                    div(self.lo, rhs.hi, up),
+                    div(self.hi, rhs.lo, up),
+                    div(self.hi, rhs.hi, up),
+                ]
+            };
+            return checked(bounds(&corners(false)).0, bounds(&corners(true)).1);
+        }
+        if self.contains(0.0) || (rhs.lo < 0.0 && rhs.hi > 0.0) {
+            return Ok(Interval::entire());
+        }
+        // Zero is one of the bounds of the divisor, so the result is a ray
+        let (n, d) = if self.hi < 0.0 {
+            (self.hi, if rhs.lo == 0.0 { rhs.hi } else { rhs.lo })
+        } else {
+            (self.lo, if rhs.lo == 0.0 { rhs.hi } else { rhs.lo })
+        };
+        Ok(if n / d > 0.0 {
+            Interval {
+                lo: div(n, d, false),
+                hi: f64::INFINITY,
+            }
+        } else {
+            Interval {
+                lo: f64::NEG_INFINITY,
+                hi: div(n, d, true),
+            }
+        })
+    }
+
+    fn checked_pow(&self, rhs: &Self) -> Result<Self, Error> {
+        // A single integer exponent keeps the sign information
+        if rhs.lo == rhs.hi && rhs.lo.fract() == 0.0 && rhs.lo.abs() <= i32::MAX as f64 {
+            if rhs.lo < 0.0 && self.lo == 0.0 && self.hi == 0.0 {
+                return Err(Error::DivisionByZero);
+            }
+            return powi(self, rhs.lo as i32);
+        }
+        if self.lo < 0.0 {
+            // Negative numbers only have the integer powers, and between the first two and
+            // the last two integers of the exponent the powers of each sign grow or shrink
+            let (first, last) = (rhs.lo.ceil(), rhs.hi.floor());
+            if first > last || first < i32::MIN as f64 || last > i32::MAX as f64 {
+                return Err(Error::DomainError);
+            }
+            let mut result = None::<Interval>;
+            for n in [first, first + 1.0, last - 1.0, last] {
+                if (first..=last).contains(&n) {
+                    let power = powi(self, n as i32)?;
+                    result = Some(result.map_or(power, |r| r.hull(&power)));
+                }
+            }
+            let result = result.ok_or(Error::DomainError)?;
+            if self.hi < 0.0 {
+                return Ok(result);
+            }
+            let positive = Interval {
+                lo: 0.0,
+                hi: self.hi,
+            }
+            .checked_pow(rhs)?;
+            return Ok(result.hull(&positive));
+        }
+        let base = *self;
+        if base.lo == 0.0 && rhs.lo < 0.0 {
+            return Ok(Interval {
+                lo: 0.0,
+                hi: f64::INFINITY,
+            });
+        }
+        // x^y is monotonic in both x and y, so the extremes are at the corners
+        let (lo, hi) = bounds(&[
+            base.lo.powf(rhs.lo),
+            base.lo.powf(rhs.hi),
+            base.hi.powf(rhs.lo),
+            base.hi.powf(rhs.hi),
+        ]);
+        let result = loose(lo, hi)?;
+        Ok(Interval {
+            lo: result.lo.max(0.0),
+            hi: result.hi,
+        })
+    }
+
+    /// A tolerance, `10 ± 0.1` is `[9.9, 10.1]`
+    fn plus_minus(&self, rhs: &Self) -> Result<Self, Error> {
+        if rhs.lo < 0.0 {
+            return Err(Error::DomainError);
+        }
+        let lo = self.checked_sub(rhs)?;
+        let hi = self.checked_add(rhs)?;
+        Ok(lo.hull(&hi))
+    }
+
+    fn sqrt(&self) -> Result<Self, Error> {
+        if self.hi < 0.0 {
+            return Err(Error::DomainError);
+        }
+        let result = outward(self.lo.max(0.0).sqrt(), self.hi.sqrt())?;
+        Ok(Interval {
+            lo: result.lo.max(0.0),
+            hi: result.hi,
+        })
+    }
+
+    fn cbrt(&self) -> Result<Self, Error> {
+        loose(self.lo.cbrt(), self.hi.cbrt())
+    }
+
+    fn abs(&self) -> Result<Self, Error> {
+        if self.contains(0.0) {
+            Ok(Interval::new(0.0, self.lo.abs().max(self.hi.abs())))
+        } else {
+            Ok(Interval::new(self.lo.abs(), self.hi.abs()))
+        }
+    }
+
+    fn sin(&self) -> Result<Self, Error> {
+        periodic(self, f64::sin, FRAC_PI_2, -FRAC_PI_2)
+    }
+
+    fn cos(&self) -> Result<Self, Error> {
+        periodic(self, f64::cos, 0.0, PI)
+    }
+
+    fn tan(&self) -> Result<Self, Error> {
+        // Increasing between the poles at π/2 + kπ
+        let pole = self.contains_periodic(FRAC_PI_2) || self.contains_periodic(-FRAC_PI_2);
+        if self.width() >= PI || pole {
+            return Ok(Interval::entire());
+        }
+        loose(self.lo.tan(), self.hi.tan())
+    }
+
+    /// Intervals are only ordered when they do not overlap
+    fn cmp_real(&self, other: &Self) -> Result<Ordering, Error> {
+        if self == other && self.lo == self.hi {
+            Ok(Ordering::Equal)
+        } else if self.hi < other.lo {
+            Ok(Ordering::Less)
+        } else if self.lo > other.hi {
+            Ok(Ordering::Greater)
+        } else {
+            Err(Error::DomainError)
+        }
+    }
+
+    fn exp(&self) -> Result<Self, Error> {
+        let result = increasing(self, f64::exp, f64::NEG_INFINITY, f64::INFINITY)?;
+        Ok(Interval {
+            lo: result.lo.max(0.0),
+            hi: result.hi,
+        })
+    }
+
+    fn ln(&self) -> Result<Self, Error> {
+        if self.hi <= 0.0 {
+            return Err(Error::DomainError);
+        }
+        increasing(self, f64::ln, 0.0, f64::INFINITY)
+    }
+
+    fn log10(&self) -> Result<Self, Error> {
+        if self.hi <= 0.0 {
+            return Err(Error::DomainError);
+        }
+        increasing(self, f64::log10, 0.0, f64::INFINITY)
+    }
+
+    // Without the snap to exact powers, which would drop the width
+    fn log(&self, base: &Self) -> Result<Self, Error> {
+        if base.lo == 1.0 && base.hi == 1.0 {
+            return Err(Error::DomainError);
+        }
+        self.ln()?.checked_div(&base.ln()?)
+    }
+
+    fn atan(&self) -> Result<Self, Error> {
+        increasing(self, f64::atan, f64::NEG_INFINITY, f64::INFINITY)
+    }
+
+    fn floor(&self) -> Result<Self, Error> {
+        Ok(Interval::new(self.lo.floor(), self.hi.floor()))
+    }
+
+    fn asin(&self) -> Result<Self, Error> {
+        increasing(self, f64::asin, -1.0, 1.0)
+    }
+
+    fn acos(&self) -> Result<Self, Error> {
+        // Decreasing, so the bounds swap
+        let flipped = Interval::new(-self.hi, -self.lo);
+        increasing(&flipped, |x| f64::acos(-x), -1.0, 1.0)
+    }
+
+    fn sinh(&self) -> Result<Self, Error> {
+        increasing(self, f64::sinh, f64::NEG_INFINITY, f64::INFINITY)
+    }
+
+    fn cosh(&self) -> Result<Self, Error> {
+        // Smallest at zero
+        let (small, large) = bounds(&[self.lo.abs(), self.hi.abs()]);
+        let small = if self.contains(0.0) { 0.0 } else { small };
+        let result = loose(small.cosh(), large.cosh())?;
+        Ok(Interval {
+            lo: result.lo.max(1.0),
+            hi: result.hi,
+        })
+    }
+
+    fn tanh(&self) -> Result<Self, Error> {
+        let result = increasing(self, f64::tanh, f64::NEG_INFINITY, f64::INFINITY)?;
+        Ok(Interval {
+            lo: result.lo.max(-1.0),
+            hi: result.hi.min(1.0),
+        })
+    }
+
+    fn asinh(&self) -> Result<Self, Error> {
+        increasing(self, f64::asinh, f64::NEG_INFINITY, f64::INFINITY)
+    }
+
+    fn acosh(&self) -> Result<Self, Error> {
+        increasing(self, f64::acosh, 1.0, f64::INFINITY)
+    }
+
+    fn atanh(&self) -> Result<Self, Error> {
+        if self.hi <= -1.0 || self.lo >= 1.0 {
+            return Err(Error::DomainError);
+        }
+        increasing(self, f64::atanh, -1.0, 1.0)
+    }
+
+    fn trunc(&self) -> Result<Self, Error> {
+        Ok(Interval::new(self.lo.trunc(), self.hi.trunc()))
+    }
+
+    fn sign(&self) -> Result<Self, Error> {
+        Ok(Interval::new(sign(self.lo), sign(self.hi)))
+    }
+}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        if self.lo == self.hi {
+            write!(f, "{}", self.lo)
+        } else {
+            write!(f, "[{}, {}]", self.lo, self.hi)
+        }
+    }
+}
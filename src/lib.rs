@@ -1,15 +1,44 @@
+pub mod angle;
+#[cfg(feature = "bigdecimal")]
+pub mod big;
+pub mod calculus;
+pub mod complex;
+pub mod constants;
+pub mod context;
+pub mod diff;
 pub mod error;
 pub mod execute;
+pub mod expr;
+pub mod finance;
+pub mod format;
+pub mod integer;
+pub mod interval;
+pub mod list;
+pub mod matrix;
+pub mod number;
 pub mod op;
 pub mod parse;
+pub mod plot;
+pub mod polynomial;
+pub mod quantity;
+pub mod rational;
+pub mod roots;
 pub mod rpn;
+pub mod simplify;
+pub mod stats;
 pub mod token;
+pub mod uncertain;
+pub mod units;
+pub mod value;
 
+pub use context::Context;
 pub use error::Error;
 pub use execute::evaluate_rpn;
-pub use parse::parse_str;
+pub use number::Number;
+pub use parse::{parse_as, parse_str, parse_str_with, ParseOptions};
 pub use rpn::shunting_yard;
 use token::Token;
+pub use value::Value;
 
 #[cfg(test)]
 mod test {
@@ -26,16 +55,16 @@ mod test {
         assert_eq!(
             tokens,
             vec![
-                Token::Number(Decimal::from_f64(1.0).unwrap()),
+                Token::Number(Decimal::from_f64(1.0).unwrap().into()),
                 Token::Operator(*OPERATORS.get(&'+').unwrap()),
-                Token::Number(Decimal::from_f64(2.0).unwrap()),
+                Token::Number(Decimal::from_f64(2.0).unwrap().into()),
                 Token::Operator(*OPERATORS.get(&'*').unwrap()),
-                Token::Number(Decimal::from_f64(3.0).unwrap()),
+                Token::Number(Decimal::from_f64(3.0).unwrap().into()),
                 Token::Operator(*OPERATORS.get(&'+').unwrap()),
                 Token::ParLeft,
-                Token::Number(Decimal::from_f64(1.0).unwrap()),
+                Token::Number(Decimal::from_f64(1.0).unwrap().into()),
                 Token::Operator(*OPERATORS.get(&'+').unwrap()),
-                Token::Number(Decimal::from_f64(4.0).unwrap()),
+                Token::Number(Decimal::from_f64(4.0).unwrap().into()),
                 Token::ParRight,
             ]
         );
@@ -53,14 +82,14 @@ mod test {
                 Token::ParLeft,
                 Token::Function(FUNCTIONS.get("max").unwrap().clone()),
                 Token::ParLeft,
-                Token::Number(Decimal::from_f64(2.0).unwrap()),
+                Token::Number(Decimal::from_f64(2.0).unwrap().into()),
                 Token::Comma,
-                Token::Number(Decimal::from_f64(3.0).unwrap()),
+                Token::Number(Decimal::from_f64(3.0).unwrap().into()),
                 Token::ParRight,
                 Token::Operator(*OPERATORS.get(&'/').unwrap()),
-                Token::Number(Decimal::from_f64(3.0).unwrap()),
+                Token::Number(Decimal::from_f64(3.0).unwrap().into()),
                 Token::Operator(*OPERATORS.get(&'*').unwrap()),
-                Token::Number(Decimal::PI),
+                Token::Number(Decimal::PI.into()),
                 Token::ParRight,
             ]
         );
@@ -75,16 +104,16 @@ mod test {
             s,
             Some(&HashMap::from([(
                 "x".to_string(),
-                Decimal::from_f64(1.0).unwrap(),
+                Decimal::from_f64(1.0).unwrap().into(),
             )])),
         )
         .unwrap();
         assert_eq!(
             tokens,
             vec![
-                Token::Number(Decimal::from_f64(1.0).unwrap()),
+                Token::Number(Decimal::from_f64(1.0).unwrap().into()),
                 Token::Operator(*OPERATORS.get(&'+').unwrap()),
-                Token::Number(Decimal::from_f64(3.0).unwrap()),
+                Token::Number(Decimal::from_f64(3.0).unwrap().into()),
             ]
         );
     }
@@ -98,15 +127,15 @@ mod test {
         assert_eq!(
             rpn,
             vec![
-                Token::Number(Decimal::from_f64(1.0).unwrap()),
-                Token::Number(Decimal::from_f64(2.0).unwrap()),
-                Token::Number(Decimal::from_f64(3.0).unwrap()),
+                Token::Number(Decimal::from_f64(1.0).unwrap().into()),
+                Token::Number(Decimal::from_f64(2.0).unwrap().into()),
+                Token::Number(Decimal::from_f64(3.0).unwrap().into()),
                 Token::Operator(*OPERATORS.get(&'*').unwrap()),
                 Token::Operator(*OPERATORS.get(&'+').unwrap()),
-                Token::Number(Decimal::from_f64(1.0).unwrap()),
+                Token::Number(Decimal::from_f64(1.0).unwrap().into()),
                 Token::Operator(*OPERATORS.get(&'+').unwrap()),
-                Token::Number(Decimal::from_f64(4.0).unwrap()),
-                Token::Number(Decimal::from_f64(2.0).unwrap()),
+                Token::Number(Decimal::from_f64(4.0).unwrap().into()),
+                Token::Number(Decimal::from_f64(2.0).unwrap().into()),
                 Token::Operator(*OPERATORS.get(&'^').unwrap()),
                 Token::Operator(*OPERATORS.get(&'+').unwrap()),
             ]
@@ -122,15 +151,15 @@ mod test {
         assert_eq!(
             rpn,
             vec![
-                Token::Number(Decimal::from_f64(1.2).unwrap()),
-                Token::Number(Decimal::from_f64(2.0).unwrap()),
-                Token::Number(Decimal::from_f64(3.0).unwrap()),
+                Token::Number(Decimal::from_f64(1.2).unwrap().into()),
+                Token::Number(Decimal::from_f64(2.0).unwrap().into()),
+                Token::Number(Decimal::from_f64(3.0).unwrap().into()),
                 Token::Operator(*OPERATORS.get(&'*').unwrap()),
-                Token::Number(Decimal::from_f64(1.2e5).unwrap()),
-                Token::Number(Decimal::from_f64(4.0).unwrap()),
+                Token::Number(Decimal::from_f64(1.2e5).unwrap().into()),
+                Token::Number(Decimal::from_f64(4.0).unwrap().into()),
                 Token::Operator(*OPERATORS.get(&'^').unwrap()),
                 Token::Operator(*OPERATORS.get(&'*').unwrap()),
-                Token::Number(Decimal::from_f64(2.0).unwrap()),
+                Token::Number(Decimal::from_f64(2.0).unwrap().into()),
                 Token::Operator(*OPERATORS.get(&'*').unwrap()),
                 Token::Operator(*OPERATORS.get(&'-').unwrap()),
             ]
@@ -186,4 +215,1606 @@ mod test {
         )
         .unwrap();
     }
+
+    #[test]
+    fn test_radix_literals() {
+        let s = "0x1F + 0o17 + 0b1010 - 0XfF";
+        let rpn = super::shunting_yard(super::parse_str(s, None).unwrap()).unwrap();
+        assert_eq!(
+            crate::execute::evaluate_rpn(rpn).unwrap(),
+            Decimal::from(31 + 15 + 10 - 255)
+        );
+    }
+    #[test]
+    fn test_digit_separators() {
+        let s = "1_000_000 + 0b1111_0000 + 1_0.2_5";
+        let rpn = super::shunting_yard(super::parse_str(s, None).unwrap()).unwrap();
+        assert_eq!(
+            crate::execute::evaluate_rpn(rpn).unwrap(),
+            Decimal::from_f64(1_000_250.25).unwrap()
+        );
+    }
+    #[test]
+    fn test_signed_fractions() {
+        let s = "-.5 + 2 * -.25 - (+.5)";
+        let rpn = super::shunting_yard(super::parse_str(s, None).unwrap()).unwrap();
+        assert_eq!(
+            crate::execute::evaluate_rpn(rpn).unwrap(),
+            Decimal::from_f64(-1.5).unwrap()
+        );
+        assert!(super::parse_str("-.", None).is_err());
+    }
+    #[test]
+    fn test_invalid_literals() {
+        use super::Error;
+        assert!(matches!(
+            super::parse_str("0b102", None),
+            Err(Error::InvalidDigit('2', 2))
+        ));
+        assert!(matches!(
+            super::parse_str("0o78", None),
+            Err(Error::InvalidDigit('8', 8))
+        ));
+        assert!(matches!(
+            super::parse_str("0x1G", None),
+            Err(Error::InvalidDigit('G', 16))
+        ));
+        assert!(matches!(
+            super::parse_str("1__000", None),
+            Err(Error::MisplacedSeparator)
+        ));
+        assert!(matches!(
+            super::parse_str("1000_ + 1", None),
+            Err(Error::MisplacedSeparator)
+        ));
+        assert!(matches!(
+            super::parse_str("0x", None),
+            Err(Error::NumberParseError)
+        ));
+    }
+    #[test]
+    fn test_format_radix() {
+        use crate::format::{format_radix, Radix};
+        assert_eq!(format_radix(Decimal::from(31), Radix::Hexadecimal), "0x1f");
+        assert_eq!(format_radix(Decimal::from(15), Radix::Octal), "0o17");
+        assert_eq!(format_radix(Decimal::from(0), Radix::Binary), "0b0");
+        assert_eq!(
+            format_radix(Decimal::from_f64(-0.75).unwrap(), Radix::Binary),
+            "-0b0.11"
+        );
+        assert_eq!(format_radix(Decimal::from(42), Radix::Decimal), "42");
+        assert_eq!(Radix::from_name("HEX"), Some(Radix::Hexadecimal));
+        assert_eq!(Radix::from_name("7"), None);
+    }
+    #[test]
+    fn test_implicit_multiplication() {
+        use std::collections::HashMap;
+        let variables = HashMap::from([
+            ("x".to_string(), Decimal::from(2).into()),
+            ("r".to_string(), Decimal::from(3).into()),
+        ]);
+        let eval = |s: &str| {
+            let tokens = super::parse_str(s, Some(&variables)).unwrap();
+            crate::execute::evaluate_rpn(super::shunting_yard(tokens).unwrap()).unwrap()
+        };
+        assert_eq!(eval("2π"), Decimal::from(2) * Decimal::PI);
+        assert_eq!(
+            eval("2πr"),
+            Decimal::from(2) * Decimal::PI * Decimal::from(3)
+        );
+        assert_eq!(eval("3x"), Decimal::from(6));
+        assert_eq!(eval("2(3+4)"), Decimal::from(14));
+        assert_eq!(eval("(1+2)(3+4)"), Decimal::from(21));
+        assert_eq!(eval("(x+r)(x-r)"), Decimal::from(-5));
+        assert_eq!(eval("(1+2)3"), Decimal::from(9));
+        assert_eq!(eval("1/2x"), Decimal::from_f64(0.25).unwrap());
+        assert_eq!(eval("2x^2"), Decimal::from(8));
+        assert_eq!(eval("2e"), Decimal::from(2) * Decimal::E);
+        assert_eq!(eval("2e2"), Decimal::from(200));
+        assert_eq!(eval("xr max(1, 2)"), Decimal::from(12));
+    }
+    #[test]
+    fn test_strict_mode() {
+        use super::{parse_str_with, Error, ParseOptions};
+        assert!(matches!(
+            parse_str_with("2pi", None, &ParseOptions::strict()),
+            Ok(tokens) if tokens.len() == 2
+        ));
+        assert!(matches!(
+            super::evaluate_rpn(
+                super::shunting_yard(
+                    parse_str_with("2(3)", None, &ParseOptions::strict()).unwrap()
+                )
+                .unwrap()
+            ),
+            Err(Error::UnfinishedExpr)
+        ));
+        assert!(matches!(
+            parse_str_with("pie", None, &ParseOptions::strict()),
+            Err(Error::InvalidToken)
+        ));
+        // Numbers next to each other are never multiplied
+        assert!(super::evaluate_rpn(
+            super::shunting_yard(super::parse_str("2 3", None).unwrap()).unwrap()
+        )
+        .is_err());
+    }
+    #[test]
+    fn test_unicode_input() {
+        use std::collections::HashMap;
+        let variables = HashMap::from([("x".to_string(), Decimal::from(3).into())]);
+        let eval = |s: &str| {
+            let tokens = super::parse_str(s, Some(&variables)).unwrap();
+            crate::execute::evaluate_rpn(super::shunting_yard(tokens).unwrap()).unwrap()
+        };
+        assert_eq!(eval("x²"), Decimal::from(9));
+        assert_eq!(eval("2³"), Decimal::from(8));
+        assert_eq!(eval("2x²"), Decimal::from(18));
+        assert_eq!(eval("(1+1)¹⁰"), Decimal::from(1024));
+        assert_eq!(eval("10⁻¹"), Decimal::from_f64(0.1).unwrap());
+        assert_eq!(eval("√16"), Decimal::from(4));
+        assert_eq!(eval("√16 + 1"), Decimal::from(5));
+        assert_eq!(eval("2√(x+6)"), Decimal::from(6));
+        assert_eq!(eval("∛27"), Decimal::from(3));
+        assert_eq!(eval("∛-8"), Decimal::from(-2));
+        assert_eq!(eval("|x − 5|"), Decimal::from(2));
+        assert_eq!(eval("|1 − 3||2 − 5|"), Decimal::from(6));
+        assert_eq!(eval("||−2| − 5|"), Decimal::from(3));
+        assert_eq!(eval("2 · 3 − −1"), Decimal::from(7));
+    }
+    #[test]
+    fn test_unicode_errors() {
+        use super::Error;
+        assert!(matches!(
+            super::parse_str("|1 + 2", None),
+            Err(Error::UnbalancedParens)
+        ));
+        assert!(matches!(
+            super::parse_str("(|1 + 2)|", None),
+            Err(Error::UnbalancedParens)
+        ));
+        assert!(matches!(
+            super::evaluate_rpn(
+                super::shunting_yard(super::parse_str("ctan(0)", None).unwrap()).unwrap()
+            ),
+            Err(Error::DomainError)
+        ));
+        // Decimal has no infinity, only the interval, uncertainty and f64 modes know `∞`
+        assert!(matches!(
+            super::parse_str("∞ - 1", None),
+            Err(Error::NoInfinity)
+        ));
+    }
+    #[test]
+    fn test_complex() {
+        use crate::{complex::Complex, Value};
+        use rust_decimal::MathematicalOps;
+        let eval = |s: &str| {
+            let tokens = super::parse_str(s, None).unwrap();
+            crate::execute::evaluate_rpn(super::shunting_yard(tokens).unwrap()).unwrap()
+        };
+        let c = |re: i64, im: i64| Value::from(Complex::new(Decimal::from(re), Decimal::from(im)));
+        assert_eq!(eval("sqrt(-1)"), c(0, 1));
+        assert_eq!(eval("√-4"), c(0, 2));
+        assert_eq!(eval("i^2"), Decimal::from(-1));
+        assert_eq!(eval("(3 + 4i)(1 - 2j)"), c(11, -2));
+        assert_eq!(eval("(3 + 4i) / (1 - 2i)"), c(-1, 2));
+        assert_eq!(eval("abs(3 + 4i)"), Decimal::from(5));
+        assert_eq!(eval("re(3 + 4i) + im(3 + 4i)"), Decimal::from(7));
+        assert_eq!(eval("conj(3 + 4i)"), c(3, -4));
+        assert_eq!(eval("arg(2i)"), Decimal::HALF_PI);
+        // The principal cube root of -8 is 1 + √3 i
+        let root = eval("(-8)^(1/3)").to_complex().unwrap();
+        assert!((root.re - Decimal::ONE).abs() < Decimal::new(1, 20));
+        assert!((root.im - Decimal::from(3).sqrt().unwrap()).abs() < Decimal::new(1, 20));
+        // sin(i) = i·sinh(1)
+        let z = eval("sin(i)").to_complex().unwrap();
+        assert!(z.re.is_zero());
+        assert!((z.im - Decimal::new(11752011936438014, 16)).abs() < Decimal::new(1, 15));
+        let z = eval("polar(2, pi/2)").to_complex().unwrap();
+        assert!(z.re.abs() < Decimal::new(1, 20));
+        assert_eq!(z.im.round_dp(20), Decimal::from(2));
+        // Parts near the largest Decimal overflow instead of panicking
+        for re in [Decimal::MAX, Decimal::MIN] {
+            let z = Complex::new(re, Decimal::ONE);
+            assert!(matches!(z.sqrt(), Err(crate::Error::Overflow)));
+        }
+        for im in [66, 67, -67] {
+            let z = Complex::new(Decimal::ONE, Decimal::from(im));
+            assert!(matches!(z.sin(), Ok(_) | Err(crate::Error::Overflow)));
+        }
+        // Powers too small for Decimal round to 0 like `exp`, only large ones overflow
+        for s in [
+            "e^-70",
+            "2^-96",
+            "10^-29",
+            "1.5^-200",
+            "(-2)^-101",
+            "(2i)^-200",
+            "exp(-100 + i)",
+        ] {
+            assert_eq!(eval(s).abs().unwrap(), Value::Real(Decimal::ZERO), "{}", s);
+        }
+        assert_eq!(eval("2^-90"), Value::Real(Decimal::new(8, 28)));
+        for s in ["2^96", "0.5^-200", "(0.5i)^-200"] {
+            let tokens = super::parse_str(s, None).unwrap();
+            let power = crate::execute::evaluate_rpn(super::shunting_yard(tokens).unwrap());
+            assert!(matches!(power, Err(crate::Error::Overflow)), "{}", s);
+        }
+    }
+    #[test]
+    fn test_complex_format() {
+        use crate::{
+            complex::Complex,
+            format::{format_value, ComplexFormat, FormatOptions, Radix},
+            Value,
+        };
+        let rect = FormatOptions::default();
+        let c = |re: i64, im: i64| Value::from(Complex::new(Decimal::from(re), Decimal::from(im)));
+        assert_eq!(format_value(&c(0, 1), &rect), "i");
+        assert_eq!(format_value(&c(0, -2), &rect), "-2i");
+        assert_eq!(format_value(&c(1, -1), &rect), "1 - i");
+        let hex = FormatOptions {
+            radix: Radix::Hexadecimal,
+            ..rect
+        };
+        assert_eq!(format_value(&c(255, 16), &hex), "0xff + 0x10i");
+        let polar = FormatOptions {
+            complex: ComplexFormat::Polar,
+            ..rect
+        };
+        assert_eq!(
+            format_value(&c(0, 2), &polar),
+            format!("2 ∠ {}", Decimal::HALF_PI)
+        );
+        assert_eq!(format_value(&c(-3, 0), &polar), "-3");
+    }
+    #[test]
+    fn test_arithmetic_errors() {
+        use super::Error;
+        let eval = |s: &str| {
+            let tokens = super::parse_str(s, None).unwrap();
+            crate::execute::evaluate_rpn(super::shunting_yard(tokens).unwrap())
+        };
+        assert!(matches!(eval("1 / 0"), Err(Error::DivisionByZero)));
+        assert!(matches!(eval("i / 0"), Err(Error::DivisionByZero)));
+        assert!(matches!(eval("0^-1"), Err(Error::DivisionByZero)));
+        assert!(matches!(eval("2^64 * 2^64"), Err(Error::Overflow)));
+        assert!(eval("∛((2^95 - 1) * 2 + 1)").is_ok());
+        assert!(matches!(eval("max(i, 1)"), Err(Error::ExpectedReal)));
+    }
+    #[test]
+    fn test_rational_mode() {
+        use crate::{parse::NumberMode, Context, Value};
+        let mut context = Context::new();
+        assert_ne!(context.eval("1/3*3").unwrap(), Decimal::ONE);
+        context.options.mode = NumberMode::Rational;
+        assert_eq!(context.eval("1/3*3").unwrap(), Decimal::ONE);
+        assert_eq!(context.eval("0.1 + 0.2").unwrap().to_string(), "3/10");
+        assert_eq!(context.eval("1/4 + 1/3").unwrap().to_string(), "7/12");
+        assert_eq!(context.eval("(2/3)^-2").unwrap().to_string(), "9/4");
+        assert_eq!(context.eval("(8/27)^(2/3)").unwrap().to_string(), "4/9");
+        assert_eq!(context.eval("1.5e-2").unwrap().to_string(), "3/200");
+        assert_eq!(
+            context.eval("2^100").unwrap().to_string(),
+            "1267650600228229401496703205376"
+        );
+        assert_eq!(context.eval("0x10 / 0b11").unwrap().to_string(), "16/3");
+        assert!(context.eval("x²").is_err());
+        context.set("x", context.eval("1/2").unwrap());
+        assert_eq!(context.eval("x² + |x − 1|").unwrap().to_string(), "3/4");
+        assert_eq!(context.eval("max(1/3, 1/4)").unwrap().to_string(), "1/3");
+        // Functions without exact results fall back to Decimal
+        assert!(matches!(context.eval("2^(1/2)").unwrap(), Value::Real(_)));
+        assert!(matches!(
+            context.eval("sqrt(-4)").unwrap(),
+            Value::Complex(_)
+        ));
+        assert!(matches!(
+            context.eval("1/0"),
+            Err(crate::Error::DivisionByZero)
+        ));
+    }
+    #[test]
+    fn test_fraction_format() {
+        use crate::format::{format_value, FormatOptions, FractionFormat};
+        use crate::{parse::NumberMode, Context};
+        let mut context = Context::new();
+        context.options.mode = NumberMode::Rational;
+        let value = context.eval("-7/4").unwrap();
+        let mut options = FormatOptions::default();
+        assert_eq!(format_value(&value, &options), "-7/4");
+        options.fraction = FractionFormat::Mixed;
+        assert_eq!(format_value(&value, &options), "-1 3/4");
+        assert_eq!(format_value(&context.eval("3/4").unwrap(), &options), "3/4");
+        options.fraction = FractionFormat::Decimal;
+        assert_eq!(format_value(&value, &options), "-1.75");
+    }
+    #[test]
+    fn test_number_types() {
+        use crate::{evaluate_rpn, parse_as, shunting_yard, Context, Error, ParseOptions};
+        let mut floats = Context::<f64>::default();
+        let mut decimals = Context::<Decimal>::default();
+        floats.set("rate", 0.1);
+        decimals.set("rate", Decimal::from_f64(0.1).unwrap());
+        let formula = "1000 * (1 + rate)^2 + max(3, 4)";
+        assert!((floats.eval(formula).unwrap() - 1214.0).abs() < 1e-9);
+        assert_eq!(decimals.eval(formula).unwrap(), Decimal::from(1214));
+        // f64 rounds 0.1 + 0.2, Decimal does not
+        assert_ne!(floats.eval("0.1 + 0.2").unwrap(), 0.3);
+        assert_eq!(
+            decimals.eval("0.1 + 0.2").unwrap(),
+            Decimal::from_f64(0.3).unwrap()
+        );
+        assert_eq!(decimals.eval("1.5e-3").unwrap().to_string(), "0.0015");
+        // Literals keep all the digits `Decimal` has, not just those of a float
+        let values = Context::new();
+        for literal in [
+            "0.5772156649015328606065120901",
+            "79228162514264337593543950335",
+        ] {
+            assert_eq!(values.eval(literal).unwrap().to_string(), literal);
+        }
+        assert_eq!(floats.eval("∛-27").unwrap(), -3.0);
+        let tokens = parse_as::<f64>("2^10", None, &ParseOptions::default()).unwrap();
+        assert_eq!(
+            evaluate_rpn(shunting_yard(tokens).unwrap()).unwrap(),
+            1024.0
+        );
+        // Real only types have no complex results
+        assert!(matches!(floats.eval("sqrt(-1)"), Err(Error::DomainError)));
+        assert!(matches!(decimals.eval("sqrt(-1)"), Err(Error::DomainError)));
+        assert!(matches!(decimals.eval("(-8)^0.5"), Err(Error::DomainError)));
+        assert!(matches!(floats.eval("1/0"), Err(Error::DivisionByZero)));
+        assert!(matches!(floats.eval("i"), Err(Error::InvalidToken)));
+    }
+    #[test]
+    fn test_interval() {
+        use crate::{interval::Interval, rational, Context, Error};
+        use num_rational::BigRational;
+        let mut context = Context::<Interval>::default();
+        context.set("x", Interval::new(1.9, 2.1));
+        let eval = |s: &str| context.eval(s).unwrap();
+        let encloses = |i: Interval, lo: &str, hi: &str| {
+            let exact = |x: f64| BigRational::from_float(x).unwrap();
+            exact(i.lo) <= rational::parse_exact(lo).unwrap()
+                && exact(i.hi) >= rational::parse_exact(hi).unwrap()
+        };
+        let stack = eval("x^2 - x");
+        assert!(encloses(stack, "1.51", "2.51"));
+        assert!(stack.width() < 1.0 + 1e-9);
+        // Literals that floats can not represent are enclosed
+        assert!(encloses(eval("0.1"), "0.1", "0.1"));
+        assert!(encloses(eval("0.1 * 3"), "0.3", "0.3"));
+        assert!(encloses(
+            eval("1/3"),
+            "0.33333333333333333333",
+            "0.33333333333333333334"
+        ));
+        assert_eq!(eval("(-2)^3"), Interval::point(-8.0));
+        assert_eq!(eval("2 + 3"), Interval::point(5.0));
+        // Monotonicity
+        assert_eq!(eval("interval(-1, 2)^2"), Interval::new(0.0, 4.0));
+        assert_eq!(eval("|interval(-3, 2)|"), Interval::new(0.0, 3.0));
+        // Negative bases only have the integer powers in the exponent
+        assert!(eval("interval(-1, 1)^interval(1, 2)").contains(-1.0));
+        assert_eq!(
+            eval("interval(-2, -1)^interval(0.5, 3)"),
+            Interval::new(-8.0, 4.0)
+        );
+        assert!(matches!(
+            context.eval("interval(-2, 1)^interval(0.2, 0.5)"),
+            Err(Error::DomainError)
+        ));
+        assert_eq!(eval("sin(interval(0, 3))").hi, 1.0);
+        assert_eq!(eval("cos(interval(-1, 1))").hi, 1.0);
+        assert_eq!(eval("sin(interval(-100, 100))"), Interval::new(-1.0, 1.0));
+        assert!(eval("sqrt(interval(4, 9))").contains(3.0));
+        assert_eq!(eval("max(x, 2)").lo, 2.0);
+        // Division by intervals containing zero
+        assert_eq!(eval("1/interval(-1, 1)"), Interval::entire());
+        assert_eq!(eval("1/interval(0, 2)"), Interval::new(0.5, f64::INFINITY));
+        assert_eq!(
+            eval("-1/interval(0, 2)"),
+            Interval::new(f64::NEG_INFINITY, -0.5)
+        );
+        assert_eq!(eval("tan(interval(1, 2))"), Interval::entire());
+        assert!(matches!(context.eval("1/0"), Err(Error::DivisionByZero)));
+        assert!(matches!(
+            context.eval("sqrt(interval(-4, -1))"),
+            Err(Error::DomainError)
+        ));
+        assert_eq!(eval("interval(1, 2)").to_string(), "[1, 2]");
+    }
+    #[test]
+    fn test_uncertainty() {
+        use crate::{uncertain::Uncertain, Context, Error};
+        let mut context = Context::<Uncertain>::default();
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+        context.set("g", context.eval("9.81 ± 0.02").unwrap());
+        context.set("t", context.eval("1.5 ± 0.1").unwrap());
+        let fall = context.eval("g*t^2/2").unwrap();
+        assert!(close(fall.value, 9.81 * 1.5 * 1.5 / 2.0));
+        let expected = ((1.5f64 * 1.5 / 2.0 * 0.02).powi(2) + (9.81f64 * 1.5 * 0.1).powi(2)).sqrt();
+        assert!(close(fall.uncertainty(), expected));
+        assert_eq!(fall.to_string(), "11.0 ± 1.5");
+        // The same variable is fully correlated with itself
+        assert!(close(context.eval("g + g").unwrap().uncertainty(), 0.04));
+        assert!(close(
+            context.eval("g*g").unwrap().uncertainty(),
+            2.0 * 9.81 * 0.02
+        ));
+        assert_eq!(context.eval("g - g").unwrap().to_string(), "0");
+        assert!(close(context.eval("g/g").unwrap().uncertainty(), 0.0));
+        // Independent values add in quadrature
+        assert_eq!(
+            context.eval("3 ± 0.3 + 4 ± 0.4").unwrap().to_string(),
+            "7.00 ± 0.50"
+        );
+        assert!(close(
+            context.eval("sin(0.5 ± 0.01)").unwrap().uncertainty(),
+            0.5f64.cos() * 0.01
+        ));
+        assert!(close(
+            context.eval("sqrt(4 ± 0.4)").unwrap().uncertainty(),
+            0.1
+        ));
+        assert!(close(
+            context.eval("2^(3 ± 0.1)").unwrap().uncertainty(),
+            8.0 * 2f64.ln() * 0.1
+        ));
+        assert!(matches!(
+            context.eval("1 ± (2 ± 1)"),
+            Err(Error::DomainError)
+        ));
+        assert!(matches!(context.eval("1 ± -1"), Err(Error::DomainError)));
+        // Only exact powers snap to the exponent, uncertain ones keep their uncertainty
+        assert_eq!(
+            context.eval("log10(10 ± 1)").unwrap().to_string(),
+            "1.000 ± 0.043"
+        );
+        assert_eq!(
+            context.eval("log(2, 8 ± 1)").unwrap().to_string(),
+            "3.00 ± 0.18"
+        );
+        assert_eq!(context.eval("log(2, 1024)").unwrap().to_string(), "10");
+        assert!(matches!(
+            crate::Context::new().eval("1 ± 1"),
+            Err(Error::NotSupported)
+        ));
+    }
+    #[test]
+    fn test_units() {
+        use crate::{units, Context, Error};
+        let mut context = Context::new();
+        let eval = |context: &Context, s: &str| context.eval(s).map(|v| v.to_string());
+        assert_eq!(eval(&context, "3 m / 2 s").unwrap(), "1.5 m/s");
+        assert_eq!(eval(&context, "5 km + 300 m").unwrap(), "5.3 km");
+        assert_eq!(eval(&context, "60 mph to m/s").unwrap(), "26.8224 m/s");
+        assert_eq!(eval(&context, "12 inch in cm").unwrap(), "30.48 cm");
+        // `in` with no unit after it is the inch
+        assert_eq!(eval(&context, "1 in to cm").unwrap(), "2.54 cm");
+        assert_eq!(eval(&context, "2 in + 1 inch").unwrap(), "3 inch");
+        assert_eq!(eval(&context, "(1 in) in mm").unwrap(), "25.4 mm");
+        assert_eq!(eval(&context, "1 in/s in cm/s").unwrap(), "2.54 cm/s");
+        assert_eq!(eval(&context, "1 kWh → MJ").unwrap(), "3.6 MJ");
+        assert_eq!(eval(&context, "(3 m)^2").unwrap(), "9 m^2");
+        assert_eq!(eval(&context, "sqrt(16 m^2)").unwrap(), "4 m");
+        assert_eq!(eval(&context, "1 J/(kg·K) * 2 kg").unwrap(), "2 J/K");
+        assert_eq!(eval(&context, "max(1 m, 50 cm)").unwrap(), "1 m");
+        // Units that cancel out leave a plain number
+        assert_eq!(eval(&context, "6 km / 300 m").unwrap(), "20");
+        // `min` is a function when called and a unit otherwise
+        assert_eq!(eval(&context, "5 min + 30 s").unwrap(), "5.5 min");
+        assert_eq!(eval(&context, "min(3, 4)").unwrap(), "3");
+        // Variables are looked up before units
+        context.set("m", context.eval("2").unwrap());
+        assert_eq!(eval(&context, "3 m").unwrap(), "6");
+        let context = Context::new();
+        assert!(matches!(
+            context.eval("1 m + 1 s"),
+            Err(Error::IncompatibleUnits("add", _, _))
+        ));
+        assert_eq!(
+            eval(&context, "1 m - 1 s").unwrap_err().to_string(),
+            "Cannot subtract m and s"
+        );
+        assert!(matches!(
+            context.eval("3 m to s"),
+            Err(Error::CannotConvert(_, _))
+        ));
+        assert_eq!(
+            eval(&context, "1 m to 2 m").unwrap_err().to_string(),
+            "Cannot convert m to 2 m"
+        );
+        assert!(matches!(
+            context.eval("sin(1 m)"),
+            Err(Error::UnitsNotAllowed(_))
+        ));
+        assert!(matches!(
+            context.eval("2^(1 m)"),
+            Err(Error::UnitsNotAllowed(_))
+        ));
+        assert!(matches!(context.eval("sqrt(2 m)"), Err(Error::DomainError)));
+        // More units from a file
+        let source = "# Silly units\nfurlong = 201.168 m\n\nfortnight = 14 d\n";
+        assert_eq!(units::load(source).unwrap(), 2);
+        assert_eq!(
+            eval(&context, "1 furlong/fortnight to m/d").unwrap(),
+            "14.369142857142857142857142857 m/d"
+        );
+        assert!(matches!(
+            units::load("furlong 201 m"),
+            Err(Error::InvalidUnitDefinition(_))
+        ));
+    }
+
+    #[test]
+    fn test_constants() {
+        use crate::{constants, Context, Error};
+        let context = Context::new();
+        let eval = |s: &str| context.eval(s).map(|v| v.to_string());
+        assert_eq!(eval("phys.c").unwrap(), "299792458 m/s");
+        assert_eq!(eval("phys.N_A").unwrap(), "602214076000000000000000 mol^-1");
+        assert_eq!(eval("2phys.e").unwrap(), "0.0000000000000000003204353268 C");
+        assert_eq!(eval("phys.k_B * 300 K to meV").unwrap(), "25.851999786 meV");
+        assert_eq!(eval("math.tau").unwrap(), eval("2π").unwrap());
+        assert_eq!(eval("math.sqrt2").unwrap(), eval("√2").unwrap());
+        assert!(matches!(
+            context.eval("phys.nope"),
+            Err(Error::InvalidToken)
+        ));
+        // Namespaced names do not collide with variables
+        let mut context = Context::new();
+        context.set("c", context.eval("3").unwrap());
+        assert_eq!(context.eval("c + phys.c/phys.c").unwrap().to_string(), "4");
+        // Plain constants work with every number type, those with units need units
+        let float = Context::<f64>::default();
+        assert!((float.eval("math.phi").unwrap() - 1.618033988749895).abs() < 1e-15);
+        assert!(matches!(float.eval("phys.c"), Err(Error::NotSupported)));
+        assert!((float.eval("phys.alpha").unwrap() - 0.0072973525693).abs() < 1e-15);
+        for constant in constants::CONSTANTS {
+            assert!(!constant.description.is_empty());
+            assert!(
+                Context::new().eval(constant.name).is_ok(),
+                "{}",
+                constant.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_scientific_functions() {
+        use crate::{interval::Interval, uncertain::Uncertain, Context, Error};
+        let context = Context::new();
+        let eval = |s: &str| context.eval(s).map(|v| v.to_string());
+        assert_eq!(eval("ln(e)").unwrap(), "1");
+        assert_eq!(eval("log10(1000)").unwrap(), "3");
+        assert_eq!(eval("log(2, 1024)").unwrap(), "10");
+        assert_eq!(eval("asin(1)").unwrap(), eval("π/2").unwrap());
+        assert_eq!(
+            eval("atan2(1, -1)").unwrap(),
+            "2.3561944901923449288469825374"
+        );
+        assert_eq!(eval("atan2(0, 0)").unwrap(), "0");
+        assert_eq!(eval("tanh(100)").unwrap(), "1");
+        assert_eq!(eval("sign(-3) + sign(0)").unwrap(), "-1");
+        assert_eq!(eval("floor(-2.5)").unwrap(), "-3");
+        assert_eq!(eval("ceil(-2.5)").unwrap(), "-2");
+        assert_eq!(eval("trunc(-2.7)").unwrap(), "-2");
+        assert_eq!(eval("round(2.345, 2)").unwrap(), "2.35");
+        assert_eq!(eval("round(-2.5, 0)").unwrap(), "-3");
+        assert_eq!(eval("round(1234, -2)").unwrap(), "1200");
+        assert_eq!(eval("round(1e-20, 30)").unwrap(), "0.00000000000000000001");
+        assert_eq!(eval("sec(0)").unwrap(), "1");
+        // The logarithm of a negative number is complex
+        assert_eq!(eval("ln(-1)").unwrap(), "3.1415926535897932384626433833i");
+        for input in [
+            "ln(0)",
+            "log(1, 5)",
+            "asin(2)",
+            "acos(-1.5)",
+            "acosh(0.5)",
+            "atanh(1)",
+            "csc(0)",
+            "round(1, 0.5)",
+        ] {
+            assert!(
+                matches!(context.eval(input), Err(Error::DomainError)),
+                "{}",
+                input
+            );
+        }
+        assert!(matches!(context.eval("exp(1000)"), Err(Error::Overflow)));
+        // Every number type has them
+        let float = Context::<f64>::default();
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-12;
+        assert!(close(float.eval("acosh(cosh(2))").unwrap(), 2.0));
+        assert!(close(
+            float.eval("atan2(-1, -1)").unwrap(),
+            -0.75 * std::f64::consts::PI
+        ));
+        assert!(matches!(float.eval("ln(-1)"), Err(Error::DomainError)));
+        let decimal = Context::<rust_decimal::Decimal>::default();
+        assert_eq!(decimal.eval("sinh(0)").unwrap().to_string(), "0");
+        let interval = Context::<Interval>::default()
+            .eval("asin(interval(0.1, 0.2))")
+            .unwrap();
+        assert!(interval.contains(0.1f64.asin()) && interval.contains(0.2f64.asin()));
+        let uncertain = Context::<Uncertain>::default();
+        let ln = uncertain.eval("ln(2 ± 0.2)").unwrap();
+        assert!(close(ln.uncertainty(), 0.1));
+    }
+
+    #[test]
+    fn test_angle_modes() {
+        use crate::{angle::AngleMode, parse::NumberMode, Context, Error};
+        let mut context = Context::new();
+        let same = |context: &Context, a: &str, b: &str| {
+            assert_eq!(context.eval(a).unwrap(), context.eval(b).unwrap(), "{}", a)
+        };
+        // The degree postfix works in every mode, exactly when the angles are in degrees or gradians
+        same(&context, "round(sin(30°), 20)", "0.5");
+        for mode in [AngleMode::Degrees, AngleMode::Gradians] {
+            context.options.angle = mode;
+            same(&context, "sin(30°)", "0.5");
+        }
+        same(&context, "cos(100)", "0");
+        same(&context, "asin(1)", "100");
+        context.options.angle = AngleMode::Degrees;
+        same(&context, "tan(45)", "1");
+        same(&context, "tan(-135)", "1");
+        same(&context, "cos(720 + 60)", "0.5");
+        same(&context, "asin(0.5)", "30");
+        same(&context, "acos(-1)", "180");
+        same(&context, "atan2(-1, -1)", "-135");
+        same(&context, "2sin(30)", "1");
+        assert!(matches!(context.eval("tan(90)"), Err(Error::DomainError)));
+        same(&context, "ctan(90)", "0");
+        same(&context, "ctan(45)", "1");
+        assert!(matches!(context.eval("ctan(180)"), Err(Error::DomainError)));
+        // Complex angles are converted without reducing them to a turn
+        assert_eq!(
+            context.eval("sin(i)").unwrap(),
+            Context::new().eval("sin(i π / 180)").unwrap()
+        );
+        // Other angles are converted to radians
+        assert_eq!(
+            context.eval("sin(10)").unwrap(),
+            Context::new().eval("sin(π/18)").unwrap()
+        );
+        // The conversions don't depend on the mode
+        same(&context, "deg(π)", "180");
+        same(&context, "rad(180)", "π");
+        // Exact fractions stay exact
+        context.options.mode = NumberMode::Rational;
+        assert_eq!(context.eval("sin(150)").unwrap().to_string(), "1/2");
+        let mut float = Context::<f64>::default();
+        float.options.angle = AngleMode::Degrees;
+        assert_eq!(float.eval("sin(30)").unwrap(), 0.5);
+        assert!((float.eval("atan(2)").unwrap() - 2f64.atan().to_degrees()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_statistics() {
+        use crate::{
+            token::{Function, Token},
+            Context, Error,
+        };
+        let context = Context::new();
+        let eval = |s: &str| context.eval(s).map(|v| v.to_string());
+        assert_eq!(eval("mean(0.1, 0.2)").unwrap(), "0.15");
+        assert_eq!(eval("median(5, 3, 1, 4)").unwrap(), "3.5");
+        assert_eq!(eval("mode(1, 3, 3, 2, 2)").unwrap(), "3");
+        assert_eq!(eval("pvariance(1, 2, 3, 4)").unwrap(), "1.25");
+        assert_eq!(eval("stdev(1, 3)").unwrap(), eval("sqrt(2)").unwrap());
+        assert_eq!(eval("percentile(0, 4, 2, 9)").unwrap(), "2");
+        assert_eq!(eval("percentile(100, 4, 2, 9)").unwrap(), "9");
+        assert_eq!(eval("geomean(1, 3, 9)").unwrap(), "3");
+        assert_eq!(eval("range(-2, 7, 3)").unwrap(), "9");
+        assert_eq!(eval("count(1, 2, 3) + count()").unwrap(), "3");
+        // Arguments can be any expression, and calls can be nested
+        assert_eq!(eval("mean(1, max(2, 3), 4 - 2) * 2").unwrap(), "4");
+        assert_eq!(eval("2mean(1, sin(0), 2)").unwrap(), "2");
+        assert!(matches!(
+            context.eval("variance(1)"),
+            Err(Error::NotEnoughArgs)
+        ));
+        assert!(matches!(
+            context.eval("median()"),
+            Err(Error::NotEnoughArgs)
+        ));
+        assert!(matches!(
+            context.eval("geomean(1, -1)"),
+            Err(Error::DomainError)
+        ));
+        assert!(matches!(
+            context.eval("percentile(101, 1, 2)"),
+            Err(Error::DomainError)
+        ));
+        assert!(matches!(
+            context.eval("mean(1, i)"),
+            Err(Error::ExpectedReal)
+        ));
+        // Values near the limit of `Decimal` where only the result fits
+        assert_eq!(
+            eval("mean(7e28, 7e28)").unwrap(),
+            "70000000000000000000000000000"
+        );
+        assert_eq!(eval("median(7e28, -7e28)").unwrap(), "0");
+        // and where it doesn't
+        for s in [
+            "range(-7e28, 7e28)",
+            "pvariance(-7e28, 7e28)",
+            "mean(2^200, 1)",
+        ] {
+            assert!(matches!(context.eval(s), Err(Error::Overflow)), "{}", s);
+        }
+        // Decimal has no infinity to take the statistics of
+        assert!(matches!(context.eval("mean(∞, 1)"), Err(Error::NoInfinity)));
+        // The number of arguments of a call ends up in the output of the shunting-yard
+        let rpn =
+            super::shunting_yard(super::parse_str("mean(1, (2 + 3), 4)", None).unwrap()).unwrap();
+        assert!(matches!(
+            rpn.last(),
+            Some(Token::Function(Function::Variadic(_, 3)))
+        ));
+    }
+
+    #[test]
+    fn test_integer_functions() {
+        use crate::{integer, parse::NumberMode, Context, Error};
+        use num_bigint::BigInt;
+        let mut context = Context::new();
+        let eval = |context: &Context, s: &str| context.eval(s).map(|v| v.to_string());
+        assert_eq!(eval(&context, "gcd(-12, 18)").unwrap(), "6");
+        assert_eq!(eval(&context, "lcm(2, 3, 4)").unwrap(), "12");
+        assert_eq!(eval(&context, "ncr(5, 2) + npr(5, 2)").unwrap(), "30");
+        assert_eq!(eval(&context, "choose(3, 5)").unwrap(), "0");
+        assert_eq!(eval(&context, "isprime(7919) + isprime(1)").unwrap(), "1");
+        assert_eq!(eval(&context, "nextprime(7919)").unwrap(), "7927");
+        assert_eq!(eval(&context, "totient(97)").unwrap(), "96");
+        // A toy RSA key: n = 61 × 53, e = 17, d = 2753
+        assert_eq!(
+            eval(&context, "modinv(17, totient(61 * 53))").unwrap(),
+            "2753"
+        );
+        assert_eq!(
+            eval(&context, "modpow(modpow(65, 17, 3233), 2753, 3233)").unwrap(),
+            "65"
+        );
+        assert!(matches!(
+            context.eval("gcd(2.5, 5)"),
+            Err(Error::ExpectedInteger)
+        ));
+        assert!(matches!(
+            context.eval("npr(-1, 2)"),
+            Err(Error::DomainError)
+        ));
+        assert!(matches!(
+            context.eval("modinv(6, 9)"),
+            Err(Error::DomainError)
+        ));
+        assert!(matches!(
+            context.eval("modpow(2, 3, 4, 5)"),
+            Err(Error::TooManyArgs)
+        ));
+        // Exact beyond the range of `Decimal`
+        context.options.mode = NumberMode::Rational;
+        assert_eq!(
+            eval(&context, "ncr(100, 50)").unwrap(),
+            "100891344545564193334812497256"
+        );
+        assert_eq!(eval(&context, "isprime(2^127 - 1)").unwrap(), "1");
+        let n = context.eval("2^64 + 1").unwrap();
+        let factors = integer::factor(&integer::to_integer(&n).unwrap()).unwrap();
+        assert_eq!(factors.to_string(), "274177 × 67280421310721");
+        let factors = integer::factor(&BigInt::from(360)).unwrap();
+        assert_eq!(factors.to_string(), "2^3 × 3^2 × 5");
+        assert_eq!(eval(&context, "factor(360)").unwrap(), "[2, 2, 2, 3, 3, 5]");
+        assert_eq!(eval(&context, "prod(factor(-91))").unwrap(), "-91");
+        // Two large primes take Pollard's rho too long
+        assert!(matches!(
+            context.eval("factor(10000000000037 * 30000000000011)"),
+            Err(Error::NoConvergence)
+        ));
+    }
+
+    #[test]
+    fn test_finance() {
+        use crate::{finance, Context, Error, Value};
+        use rust_decimal::Decimal;
+        let context = Context::new();
+        let eval = |s: &str| context.eval(s).map(|v| v.to_string());
+        // The values spreadsheets give
+        assert_eq!(
+            eval("round(pv(0.08/12, 240, 500), 2)").unwrap(),
+            "-59777.15"
+        );
+        assert_eq!(
+            eval("round(fv(0.06, 10, -200, -500, 1), 2)").unwrap(),
+            "3689.75"
+        );
+        assert_eq!(eval("round(nper(0.01, -100, 1000), 4)").unwrap(), "10.5886");
+        assert_eq!(eval("pmt(0, 10, 1000)").unwrap(), "-100");
+        assert_eq!(eval("round(rate(48, -200, 8000), 6)").unwrap(), "0.007701");
+        assert_eq!(
+            eval("round(xnpv(0.09, -10000, 0, 2750, 60, 4250, 303, 3250, 411, 2750, 456), 2)")
+                .unwrap(),
+            "2086.65"
+        );
+        // The rate, payment and present value agree with each other
+        assert_eq!(
+            eval("round(pv(rate(60, -500, 25000), 60, -500), 10)").unwrap(),
+            "25000"
+        );
+        assert_eq!(eval("irr(-1000, 1100)").unwrap(), "0.1");
+        // Far from the first guess, on either side of it
+        assert_eq!(
+            eval("irr(-100, 0, 0, 0, 1)").unwrap(),
+            "-0.6837722339831620668"
+        );
+        assert_eq!(eval("irr(-100, 1000)").unwrap(), "9");
+        assert!(matches!(
+            context.eval("irr(-100, 100, -100)"),
+            Err(Error::NoConvergence)
+        ));
+        assert!(matches!(
+            context.eval("pmt(0.1, 12)"),
+            Err(Error::NotEnoughArgs)
+        ));
+        assert!(matches!(
+            context.eval("pv(0.1, 1, 2, 3, 4, 5)"),
+            Err(Error::TooManyArgs)
+        ));
+        // Results past the largest Decimal are errors rather than panics
+        let mut large = context.clone();
+        large.set("big", Value::from(Decimal::MAX));
+        for s in [
+            "nper(10, 1, big, 0)",
+            "nper(0.5, big, 1, 0, 1)",
+            "pv(0, 1, big, big)",
+            "rate(10, -100, 1000, 0, 0, big)",
+        ] {
+            let result = large.eval(s);
+            assert!(
+                matches!(result, Err(Error::Overflow | Error::NoConvergence)),
+                "{}",
+                s
+            );
+        }
+        let schedule =
+            finance::amortization(Decimal::from(100000), Decimal::new(5, 3), 360).unwrap();
+        let interest: Decimal = schedule.iter().map(|row| row.interest).sum();
+        assert_eq!(interest.round_dp(2), Decimal::new(11_583_819, 2));
+        assert_eq!(schedule.last().unwrap().balance, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_lists() {
+        use crate::{Context, Error};
+        let context = Context::new();
+        let eval = |s: &str| context.eval(s).map(|v| v.to_string());
+        // Every operator works element by element, with a single value on either side
+        assert_eq!(eval("[1, 2, 3] - 1").unwrap(), "[0, 1, 2]");
+        assert_eq!(eval("12 / [2, 3, 4]").unwrap(), "[6, 4, 3]");
+        assert_eq!(eval("[1, 2] ^ [3, 2]").unwrap(), "[1, 4]");
+        assert_eq!(eval("[[1, 2], [3, 4]] * 2").unwrap(), "[[2, 4], [6, 8]]");
+        assert_eq!(eval("[]").unwrap(), "[]");
+        // Indexing binds tighter than any operator
+        assert_eq!(eval("[10, 20, 30][1]").unwrap(), "20");
+        assert_eq!(eval("2 * [10, 20, 30][-3]^2").unwrap(), "200");
+        assert_eq!(eval("[[1, 2], [3, 4]][1][0]").unwrap(), "3");
+        assert_eq!(eval("abs([-1, 2, -3])").unwrap(), "[1, 2, 3]");
+        assert_eq!(eval("map(floor, [1.5, -1.5])").unwrap(), "[1, -2]");
+        assert_eq!(eval("len([1, 2, 3], 4)").unwrap(), "4");
+        // A matrix has as many elements as rows, but its numbers are all added up
+        assert_eq!(eval("len([[1, 2], [3, 4]])").unwrap(), "2");
+        assert_eq!(eval("count([[1, 2], [3, 4]])").unwrap(), "2");
+        assert_eq!(eval("sum([[1, 2], [3, 4]])").unwrap(), "10");
+        assert!(matches!(
+            context.eval("map(max, [1, 2])"),
+            Err(Error::InvalidToken)
+        ));
+        assert_eq!(eval("prod([1, 2, 3, 4])").unwrap(), "24");
+        assert_eq!(eval("dot([1, 2, 3], [4, 5, 6])").unwrap(), "32");
+        assert_eq!(eval("cross([1, 0, 0], [0, 1, 0])").unwrap(), "[0, 0, 1]");
+        assert_eq!(context.eval("norm([3, 4])").unwrap(), Decimal::from(5));
+        assert_eq!(eval("mean([1, 2, 3, 4])").unwrap(), "2.5");
+        assert_eq!(eval("gcd([12, 18])").unwrap(), "6");
+        assert!(matches!(
+            context.eval("[1, 2, 3][3]"),
+            Err(Error::IndexOutOfRange(..))
+        ));
+        assert!(matches!(
+            context.eval("[1, 2][0.5]"),
+            Err(Error::ExpectedInteger)
+        ));
+        assert!(matches!(context.eval("3[0]"), Err(Error::ExpectedList)));
+        assert!(matches!(
+            context.eval("cross([1, 2], [3, 4])"),
+            Err(Error::DimensionMismatch(..))
+        ));
+        assert!(matches!(
+            context.eval("[1, 2) + 1"),
+            Err(Error::UnbalancedParens)
+        ));
+        assert!(matches!(
+            context.eval("(1, 2]"),
+            Err(Error::UnbalancedParens)
+        ));
+        assert!(matches!(
+            context.eval("max([1, 2], 3)"),
+            Err(Error::ExpectedNumber)
+        ));
+    }
+
+    #[test]
+    fn test_matrices() {
+        use crate::{parse::NumberMode, Context, Error};
+        let mut context = Context::new();
+        let eval = |context: &Context, s: &str| context.eval(s).map(|v| v.to_string());
+        assert_eq!(
+            eval(&context, "[1, 2] @ [[1, 2], [3, 4]]").unwrap(),
+            "[7, 10]"
+        );
+        assert_eq!(eval(&context, "[1, 2, 3] @ [4, 5, 6]").unwrap(), "32");
+        assert_eq!(
+            eval(&context, "inv([[1, 2], [3, 4]])").unwrap(),
+            "[[-2, 1], [1.5, -0.5]]"
+        );
+        // Rows are swapped to put the largest pivot first
+        assert_eq!(eval(&context, "det([[0, 1], [1, 0]])").unwrap(), "-1");
+        assert_eq!(eval(&context, "det([[1, 2], [2, 4]])").unwrap(), "0");
+        assert_eq!(
+            eval(&context, "rank([[1, 2, 3], [2, 4, 6], [1, 0, 1]])").unwrap(),
+            "2"
+        );
+        assert_eq!(
+            eval(
+                &context,
+                "solve([[1, 1, 1], [0, 2, 5], [2, 5, -1]], [6, -4, 27])"
+            )
+            .unwrap(),
+            "[5, 3, -2]"
+        );
+        assert_eq!(
+            eval(&context, "solve([[2, 0], [0, 4]], [[2, 4], [4, 8]])").unwrap(),
+            "[[1, 2], [1, 2]]"
+        );
+        // A matrix times its inverse is the identity
+        assert_eq!(
+            eval(&context, "[[4, 7], [2, 6]] @ inv([[4, 7], [2, 6]])").unwrap(),
+            "[[1, 0], [0, 1]]"
+        );
+        // Fractions stay exact
+        context.options.mode = NumberMode::Rational;
+        assert_eq!(
+            eval(&context, "inv([[3, 1], [1, 3]])").unwrap(),
+            "[[3/8, -1/8], [-1/8, 3/8]]"
+        );
+        assert_eq!(
+            eval(&context, "det([[1, 2, 3], [4, 5, 6], [7, 8, 10]])").unwrap(),
+            "-3"
+        );
+        assert!(matches!(
+            context.eval("[[1, 2], [3, 4]] @ [[1, 2, 3]]"),
+            Err(Error::DimensionMismatch(..))
+        ));
+        assert!(matches!(
+            context.eval("det([[1, 2], [3]])"),
+            Err(Error::DimensionMismatch(..))
+        ));
+        assert!(matches!(
+            context.eval("det([[1, 2, 3], [4, 5, 6]])"),
+            Err(Error::NotSquare(_))
+        ));
+        assert!(matches!(
+            context.eval("solve([[1, 2], [2, 4]], [1, 2])"),
+            Err(Error::SingularMatrix)
+        ));
+        // Empty matrices have no rows, and products with them no entries
+        for s in ["[] @ []", "[[]] @ [[]]", "[1, 2] @ []", "[] @ [[1, 2]]"] {
+            assert!(
+                matches!(context.eval(s), Err(Error::DimensionMismatch(..))),
+                "{}",
+                s
+            );
+        }
+        assert_eq!(eval(&context, "det([])").unwrap(), "1");
+        assert_eq!(eval(&context, "transpose([])").unwrap(), "[]");
+        assert!(matches!(context.eval("inv(2)"), Err(Error::ExpectedList)));
+    }
+
+    #[test]
+    fn test_derivatives() {
+        use crate::{
+            diff::derivative,
+            expr::Expr,
+            token::{Function, FUNCTIONS, INTEGER_FUNCTIONS, POLYNOMIAL_FUNCTIONS},
+            Context, Error, Number, ParseOptions, Value,
+        };
+        use std::collections::HashMap;
+        let options = ParseOptions::default();
+        let d = |s: &str, var: &str| {
+            derivative(&Expr::parse(s, &options).unwrap(), var).map(|d| d.to_string())
+        };
+        assert_eq!(d("x^3 - 2x + 1", "x").unwrap(), "3 * x^2 - 2");
+        assert_eq!(d("sin(x) / x", "x").unwrap(), "(cos(x) * x - sin(x)) / x^2");
+        assert_eq!(d("2^x", "x").unwrap(), "2^x * ln(2)");
+        assert_eq!(d("cos(x^2)", "x").unwrap(), "-2 * sin(x^2) * x");
+        assert_eq!(d("|x| + floor(x)", "x").unwrap(), "sign(x)");
+        assert_eq!(d("x * y^2", "y").unwrap(), "2 * x * y");
+        assert_eq!(d("pi * e", "x").unwrap(), "0");
+        assert!(matches!(d("x ± 1", "x"), Err(Error::NotDifferentiable(_))));
+        assert!(matches!(
+            d("gcd(x, 4)", "x"),
+            Err(Error::NotDifferentiable(_))
+        ));
+        // The derivatives agree with the slope between two close points
+        let slope = |s: &str, x: &str| {
+            let expr = Expr::parse(s, &options).unwrap();
+            let at = |x: &str| {
+                let x = Context::new().eval(x).unwrap();
+                HashMap::from([("x".to_string(), x)])
+            };
+            let h = Value::Real(Decimal::new(1, 8));
+            let x1 = at(&format!("{} - 0.00000001", x));
+            let x2 = at(&format!("{} + 0.00000001", x));
+            let difference = expr
+                .eval(&x2)
+                .unwrap()
+                .checked_sub(&expr.eval(&x1).unwrap());
+            let slope = difference.unwrap().checked_div(&h).unwrap();
+            let exact = derivative(&expr, "x").unwrap().eval(&at(x)).unwrap();
+            let error = slope.checked_div(&Value::Real(Decimal::TWO)).unwrap();
+            let error = error.checked_sub(&exact).unwrap().as_real().unwrap().abs();
+            assert!(error < Decimal::new(1, 6), "{} at {}: {}", s, x, error);
+        };
+        slope("x^2 * sin(x)", "1.3");
+        slope("atan2(x, 2) + tan(x) + sec(x)", "0.4");
+        slope("asinh(x) * acosh(x + 2) - atanh(x / 2)", "0.7");
+        slope(
+            "log(3, x) + log(x, 5) + log10(x) + cbrt(x) * sqrt(x)",
+            "2.5",
+        );
+        slope("x^x + deg(x) + cosh(x) / exp(-x)", "1.1");
+        slope("max(x^2, x) + min(sin(x), 0.5) + min(x, 2)", "1.3");
+        // Every function can be part of an expression, with the same parameters
+        let functions = FUNCTIONS.iter().chain(INTEGER_FUNCTIONS.iter());
+        for (name, f) in functions.chain(POLYNOMIAL_FUNCTIONS.iter()) {
+            let call = Expr::function(name).unwrap_or_else(|| panic!("{}", name));
+            let same = matches!(
+                (&call, f),
+                (Function::OneParam(_), Function::OneParam(_))
+                    | (Function::TwoParam(_), Function::TwoParam(_))
+                    | (Function::Variadic(..), Function::Variadic(..))
+            );
+            assert!(same, "{}", name);
+        }
+        // A minus negates what follows, binding looser than `^`
+        let context = Context::new();
+        assert_eq!(context.eval("-(4 - 1)").unwrap(), Decimal::from(-3));
+        assert_eq!(context.eval("2 * -sqrt(4)^2").unwrap(), Decimal::from(-8));
+        assert_eq!(d("-x^2", "x").unwrap(), "-2 * x");
+    }
+
+    #[test]
+    fn test_simplify() {
+        use crate::{diff::derivative, expr::Expr, simplify::simplify, ParseOptions, Value};
+        use std::collections::HashMap;
+        let parse = |s: &str| Expr::parse(s, &ParseOptions::default()).unwrap();
+        let s = |s: &str| simplify(&parse(s)).to_string();
+        assert_eq!(s("x + 0 + 1 * y"), "x + y");
+        assert_eq!(s("2 * 3 + 4"), "10");
+        assert_eq!(s("x * x^2 * x"), "x^4");
+        assert_eq!(s("x^2 / x^3"), "1 / x");
+        assert_eq!(s("x / 2 + x / 3"), "5 * x / 6");
+        assert_eq!(s("2 * (x + 1) - 2"), "2 * x");
+        assert_eq!(s("(2x)^3 - x"), "8 * x^3 - x");
+        assert_eq!(s("x * (x + 1)"), "x * (x + 1)");
+        assert_eq!(s("(x + 1) / (x + 1) + x - x"), "1");
+        assert_eq!(s("x^(1/2) * x^(1/2)"), "x");
+        assert_eq!(s("ln(e) + exp(0) + sqrt(2)"), "sqrt(2) + 2");
+        assert_eq!(s("2 * pi * x / pi"), "2 * x");
+        assert_eq!(s("0.5x + 0.25x"), "0.75 * x");
+        assert_eq!(s("x / 0"), "x / 0");
+        // `0 * x` is 0 only where `x` has a value
+        assert_eq!(s("0 * x + 0 * sqrt(4)"), "0");
+        assert_eq!(s("0 * (1 / 0)"), "0 / 0");
+        assert_eq!(s("(1 / 0) * 0 + x"), "0 / 0 + x");
+        assert!(matches!(
+            simplify(&parse("0 * (1 / 0)")).eval(&HashMap::new()),
+            Err(crate::Error::DivisionByZero)
+        ));
+        let d = |s: &str| simplify(&derivative(&parse(s), "x").unwrap()).to_string();
+        assert_eq!(d("sqrt(1 - x^2)"), "-x / sqrt(-x^2 + 1)");
+        assert_eq!(d("ln(x) / x"), "(-ln(x) + 1) / x^2");
+        assert_eq!(d("x^3 * y + y"), "3 * x^2 * y");
+        // The simplified expression has the same value
+        let expr = parse("(x + 1)^2 / (x + 1) + 3x * x / x^3 - sin(x)^2 / sin(x)");
+        let at = HashMap::from([("x".to_string(), Value::Real(Decimal::new(7, 1)))]);
+        let value = |e: &Expr| e.eval(&at).unwrap().as_real().unwrap().round_dp(20);
+        assert_eq!(value(&simplify(&expr)), value(&expr));
+    }
+
+    #[test]
+    fn test_roots() {
+        use crate::{
+            roots::{equation, root, roots},
+            Error, ParseOptions, Value,
+        };
+        use std::collections::HashMap;
+        let options = ParseOptions::default();
+        let none = HashMap::new();
+        let solve = |s: &str, guess: i64| {
+            root(
+                &equation(s, &options).unwrap(),
+                "x",
+                Decimal::from(guess),
+                &none,
+            )
+        };
+        let all = |s: &str, from: i64, to: i64| {
+            let expr = equation(s, &options).unwrap();
+            roots(&expr, "x", Decimal::from(from), Decimal::from(to), &none).unwrap()
+        };
+        // Newton's method fails where the slope is 0, the guess is widened into an interval
+        assert_eq!(
+            solve("x^2 - 2 = 0", 0).unwrap().round_dp(10),
+            Decimal::new(14142135624, 10)
+        );
+        assert_eq!(solve("exp(x) = 1", 5).unwrap(), Decimal::ZERO);
+        assert_eq!(solve("floor(x) = 3", 10).unwrap(), Decimal::from(3));
+        assert!(matches!(solve("x^2 + 1", 0), Err(Error::NoConvergence)));
+        assert!(matches!(solve("1 / x", 1), Err(Error::NoConvergence)));
+        assert_eq!(
+            all("(x - 1) * (x - 2) * (x - 3)", 0, 10),
+            [1, 2, 3].map(Decimal::from)
+        );
+        assert_eq!(all("(x - 1)^2", -5, 5), [Decimal::ONE]);
+        assert_eq!(all("sin(x)", -4, 4).len(), 3);
+        assert!(all("1 / x", -1, 1).is_empty());
+        assert!(all("sqrt(x) + 1", -1, 1).is_empty());
+        // Zeros from values too small for Decimal and jumps across 0 are not roots
+        assert!(matches!(solve("exp(x) = 0", 0), Err(Error::NoConvergence)));
+        assert!(all("exp(x)", -100, 0).is_empty());
+        assert!(all("floor(x) - 0.5", 0, 2).is_empty());
+        assert_eq!(all("floor(x) - 1", 0, 2), [Decimal::ONE]);
+        assert_eq!(solve("x^3", 0).unwrap(), Decimal::ZERO);
+        assert_eq!(solve("x^4", 0).unwrap(), Decimal::ZERO);
+        // An equation that always holds has no roots to list
+        let identity = equation("x = x", &options).unwrap();
+        assert!(matches!(
+            roots(&identity, "x", Decimal::ZERO, Decimal::ONE, &none),
+            Err(Error::Identity)
+        ));
+        // Intervals too wide for a Decimal and variables the equation doesn't have are errors
+        let line = equation("x - 1", &options).unwrap();
+        assert!(matches!(
+            roots(&line, "x", Decimal::MIN, Decimal::MAX, &none),
+            Err(Error::Overflow)
+        ));
+        let wide = Decimal::MAX / Decimal::from(4);
+        assert_eq!(
+            roots(&line, "x", -wide, wide, &none).unwrap(),
+            [Decimal::ONE]
+        );
+        assert!(matches!(
+            root(&line, "y", Decimal::ZERO, &none),
+            Err(Error::MissingVariable(_))
+        ));
+        assert!(matches!(
+            roots(&line, "2", Decimal::ZERO, Decimal::ONE, &none),
+            Err(Error::MissingVariable(_))
+        ));
+        // The other variables keep their values
+        let a = HashMap::from([("a".to_string(), Value::from(Decimal::from(9)))]);
+        let expr = equation("x^2 = a", &options).unwrap();
+        assert_eq!(
+            root(&expr, "x", Decimal::ONE, &a).unwrap(),
+            Decimal::from(3)
+        );
+        assert!(matches!(
+            equation("x = 1 = 2", &options),
+            Err(Error::UnexpectedToken)
+        ));
+    }
+
+    #[test]
+    fn test_calculus() {
+        use crate::{calculus::integral, interval::Interval, Context, Error, Value};
+        let mut context = Context::new();
+        let eval = |context: &Context, s: &str| context.eval(s).map(|x| x.to_string());
+        assert_eq!(eval(&context, "sum(k, k, 1, 100)").unwrap(), "5050");
+        assert_eq!(eval(&context, "prod(2, k, 1, 10)").unwrap(), "1024");
+        assert_eq!(
+            eval(&context, "sum(k, k, 5, 1) + prod(k, k, 5, 1)").unwrap(),
+            "1"
+        );
+        assert_eq!(
+            eval(&context, "sum(sum(m * n, m, 1, n), n, 1, 4)").unwrap(),
+            "65"
+        );
+        let geometric = context.eval("sum(1/2^k, k, 0, 20) + 1/2^20").unwrap();
+        assert_eq!(geometric, Decimal::TWO);
+        // Up to 100000 terms
+        assert_eq!(
+            eval(&context, "sum(k, k, 1, 100000)").unwrap(),
+            "5000050000"
+        );
+        assert!(matches!(
+            context.eval("sum(k, k, 0, 100000)"),
+            Err(Error::TooManyTerms(100000))
+        ));
+        // Constants can't be bound, the sum of a constant's arguments still works
+        for s in [
+            "sum(i^2, i, 1, 3)",
+            "prod(i, i, 1, 3)",
+            "integrate(e, e, 0, 1)",
+        ] {
+            assert!(
+                matches!(context.eval(s), Err(Error::ExpectedVariable(_))),
+                "{}",
+                s
+            );
+        }
+        assert_eq!(
+            eval(&context, "sum(1, e, 3, 4)").unwrap(),
+            eval(&context, "8 + e").unwrap()
+        );
+        // A bound variable hides a variable with the same name, the others keep their values
+        context.set("k", Value::from(Decimal::from(10)));
+        context.set("a", Value::from(Decimal::from(2)));
+        assert_eq!(eval(&context, "sum(a * k, k, 1, 3)").unwrap(), "12");
+        assert_eq!(eval(&context, "sum(a, 1, k, 3)").unwrap(), "16");
+        // Integrals with their error estimates
+        let round = |x: Value| x.as_real().unwrap().round_dp(15);
+        let (area, error) = integral(&context, "exp(x)", "x", "0", "1").unwrap();
+        let exact = context.eval("e - 1").unwrap();
+        assert_eq!(round(area), round(exact));
+        assert!(error.as_real().unwrap() < Decimal::new(1, 15));
+        let (area, _) = integral(&context, "a * x", "x", "3", "1").unwrap();
+        assert_eq!(round(area), Decimal::from(-8));
+        let pi = eval(&context, "round(integrate(sqrt(1 - x^2), x, -1, 1) * 2, 4)");
+        assert_eq!(pi.unwrap(), "3.1416");
+        assert!(matches!(
+            context.eval("integrate(1 / x, x, 0, 1)"),
+            Err(Error::DivisionByZero)
+        ));
+        // Other number types
+        let intervals = Context::<Interval>::default();
+        let sum = intervals.eval("sum(interval(k, k + 1), k, 1, 3)").unwrap();
+        assert_eq!(sum.to_string(), "[6, 9]");
+    }
+
+    #[test]
+    fn test_polynomials() {
+        use crate::{polynomial::to_polynomial, Context, Error};
+        let context = Context::new();
+        let eval = |s: &str| context.eval(s).map(|x| x.to_string());
+        assert_eq!(eval("poly([1, 0, -2])").unwrap(), "x^2 - 2");
+        assert_eq!(
+            eval("poly([1, -3, 3, -1]) + poly([2, 1])").unwrap(),
+            "x^3 - 3x^2 + 5x"
+        );
+        assert_eq!(eval("poly([1, 1])^2").unwrap(), "x^2 + 2x + 1");
+        assert_eq!(eval("poly([1, 0, -1]) / poly([1, 1])").unwrap(), "x - 1");
+        assert_eq!(
+            eval("polydiv(poly([1, 0, 0]), poly([1, 1]))").unwrap(),
+            "[x - 1, 1]"
+        );
+        assert_eq!(
+            eval("polyder(poly([1, 2, 3, 4]))").unwrap(),
+            "3x^2 + 4x + 3"
+        );
+        assert_eq!(
+            eval("polyval(poly([1, 0, -2]), [0, 1, 2])").unwrap(),
+            "[-2, -1, 2]"
+        );
+        assert_eq!(eval("coeffs(poly([2, 0, 1]))").unwrap(), "[2, 0, 1]");
+        assert_eq!(eval("degree(poly([2, 0, 1]))").unwrap(), "2");
+        // Roots, real and complex, with repeated ones kept
+        assert_eq!(eval("roots([1, -3, 3, -1])").unwrap(), "[1, 1, 1]");
+        assert_eq!(eval("roots([1, 2, 5])").unwrap(), "[-1 - 2i, -1 + 2i]");
+        assert_eq!(eval("roots(poly(1, -2, 1))").unwrap(), "[1, 1]");
+        assert_eq!(eval("roots(poly(2, -4, 2))").unwrap(), "[1, 1]");
+        assert_eq!(
+            eval("roots(poly(1, -10)^6)").unwrap(),
+            "[10, 10, 10, 10, 10, 10]"
+        );
+        assert_eq!(
+            eval("roots(poly(1, 0, 0, 0, -1))").unwrap(),
+            "[-1, -i, i, 1]"
+        );
+        let high = eval("roots(poly(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13))").unwrap();
+        assert_eq!(high.matches(',').count(), 11);
+        // Coefficients of very different sizes, and high degrees
+        assert_eq!(
+            eval("roots(poly([1, 1e10, 1]))").unwrap(),
+            "[-9999999999.9999999999, -0.0000000001]"
+        );
+        assert_eq!(
+            eval("roots(poly([1e-10, 1, 1]))").unwrap(),
+            "[-9999999998.9999999999, -1.00000000010000000002]"
+        );
+        assert_eq!(
+            eval("roots(poly([1, 1e10, 1e10, 1]))").unwrap(),
+            "[-9999999998.9999999999, -1, -0.00000000010000000001]"
+        );
+        assert_eq!(
+            eval("roots(poly([1e21, 0, 0, -8]))").unwrap(),
+            "[-0.0000001 - 0.00000017320508075689i, -0.0000001 + 0.00000017320508075689i, 0.0000002]"
+        );
+        let unity = eval("roots(poly(1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, -1))").unwrap();
+        assert_eq!(unity.matches(',').count(), 14);
+        assert!(unity.starts_with("[-0.97814760073380563793 - 0.2079116908177593371i, "));
+        assert!(unity.ends_with(", 0.9135454576426008955 + 0.40673664307580020775i, 1]"));
+        assert_eq!(
+            unity.matches(" 1,").count() + unity.matches(" 1]").count(),
+            1
+        );
+        let twenty = eval("roots(poly([1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21]))").unwrap();
+        assert_eq!(twenty.matches(',').count(), 19);
+        assert!(twenty.starts_with("[-1.17859048291205590825 - 0.17390610107238064098i, "));
+        assert_eq!(
+            eval("roots(poly([1, 0, -2]))").unwrap(),
+            "[-1.4142135623730950488, 1.4142135623730950488]"
+        );
+        assert_eq!(eval("roots(5)").unwrap(), "[]");
+        assert!(matches!(context.eval("roots(0)"), Err(Error::DomainError)));
+        assert!(matches!(
+            context.eval("poly([1, 0, 0]) / poly([1, 1])"),
+            Err(Error::Remainder(_))
+        ));
+        // Least squares fits
+        assert_eq!(
+            eval("polyfit([0, 1, 2, 3], [1, 3, 5, 7], 1)").unwrap(),
+            "2x + 1"
+        );
+        assert_eq!(eval("polyfit([-1, 0, 1], [1, 0, 1], 2)").unwrap(), "x^2");
+        assert!(matches!(
+            context.eval("polyfit([], [], 0)"),
+            Err(Error::DomainError)
+        ));
+        let p = to_polynomial(&context.eval("[1, -1]").unwrap()).unwrap();
+        assert_eq!(p.degree(), 1);
+    }
+
+    #[test]
+    fn test_plots() {
+        use crate::{
+            expr::Expr,
+            plot::{plot_terminal, Series, Style, TerminalOptions},
+            Error, ParseOptions,
+        };
+        use std::collections::HashMap;
+        let parse = |s: &str| Expr::parse(s, &ParseOptions::default()).unwrap();
+        let variables = HashMap::new();
+        let sample = |s: &str, from: f64, to: f64| {
+            Series::sample(&parse(s), "x", &(from..=to), 101, &variables)
+        };
+        // Jumps and poles are found, steep slopes are not jumps
+        let jumps = |s: &str, from: f64, to: f64| -> Vec<f64> {
+            sample(s, from, to)
+                .jumps
+                .iter()
+                .map(|x| (x * 1e6).round() / 1e6)
+                .collect()
+        };
+        assert_eq!(jumps("floor(x)", -1.5, 1.5), [-1.0, 0.0, 1.0]);
+        let pole = (std::f64::consts::FRAC_PI_2 * 1e6).round() / 1e6;
+        assert_eq!(jumps("tan(x)", 0.0, 3.0), [pole]);
+        assert_eq!(jumps("1 / x", -1.0, 1.0), [0.0]);
+        assert!(jumps("x^(1/3) + 10 * x", 0.0, 1.0).is_empty());
+        assert!(jumps("atan(1000 * x)", -1.0, 1.0).is_empty());
+        // No value where the expression is not real
+        let series = sample("sqrt(x)", -1.0, 1.0);
+        assert!(series.points[..50].iter().all(|(_, y)| y.is_none()));
+        assert!(series.points[50..].iter().all(|(_, y)| y.is_some()));
+        // Several curves, with a legend
+        let options = TerminalOptions {
+            width: 20,
+            height: 6,
+            style: Style::Ascii,
+            color: false,
+        };
+        let plot = plot_terminal(
+            &[parse("x"), parse("-x")],
+            "x",
+            -1.0..=1.0,
+            &variables,
+            &options,
+        )
+        .unwrap();
+        let lines = [
+            " 1 +oo        |       **",
+            "   |  oooo    |   ****",
+            "   |      oooo****",
+            " 0 +------****oooo------",
+            "   |  ****    |   oooo",
+            "-1 +**        |       oo",
+            "   +--------------------",
+            "    -1        0        1",
+            "    * x   o -x",
+        ];
+        assert_eq!(plot, lines.map(|line| format!("{}\n", line)).concat());
+        let a = plot_terminal(
+            &[parse("sin(x)")],
+            "x",
+            0.0..=6.0,
+            &variables,
+            &Default::default(),
+        );
+        assert!(a
+            .unwrap()
+            .lines()
+            .all(|line| line.chars().count() <= 64 + 4));
+        assert!(matches!(
+            plot_terminal(&[parse("x")], "x", 1.0..=1.0, &variables, &options),
+            Err(Error::DomainError)
+        ));
+        // A curve without a value anywhere is the error of its value
+        assert!(matches!(
+            plot_terminal(
+                &[parse("x"), parse("y")],
+                "x",
+                0.0..=1.0,
+                &variables,
+                &options
+            ),
+            Err(Error::UnknownToken)
+        ));
+        assert!(matches!(
+            plot_terminal(&[parse("sqrt(x)")], "x", -2.0..=-1.0, &variables, &options),
+            Err(Error::ExpectedReal)
+        ));
+        // Colours only when asked for
+        let braille = TerminalOptions {
+            style: Style::Braille,
+            ..options
+        };
+        let plot = plot_terminal(
+            &[parse("x"), parse("-x")],
+            "x",
+            -1.0..=1.0,
+            &variables,
+            &braille,
+        );
+        assert!(!plot.unwrap().contains('\x1b'));
+        let colored = TerminalOptions {
+            color: true,
+            ..braille
+        };
+        let plot = plot_terminal(
+            &[parse("x"), parse("-x")],
+            "x",
+            -1.0..=1.0,
+            &variables,
+            &colored,
+        );
+        assert!(plot.unwrap().contains('\x1b'));
+    }
+
+    #[test]
+    fn test_svg_plots() {
+        use crate::{
+            expr::Expr,
+            plot::{plot_svg, SvgOptions},
+            Error, ParseOptions, Value,
+        };
+        use std::collections::HashMap;
+        let parse = |s: &str| Expr::parse(s, &ParseOptions::default()).unwrap();
+        let variables = HashMap::new();
+        let options = SvgOptions {
+            width: 320,
+            height: 240,
+        };
+        let exprs = [parse("sin(x)"), parse("tan(x)"), parse("a * x")];
+        let svg = |variables: &HashMap<String, Value>| {
+            plot_svg(&exprs, "x", -3.0..=3.0, variables, &options).unwrap()
+        };
+        let a = HashMap::from([("a".to_string(), Value::from(Decimal::TWO))]);
+        // The same plot is the same text
+        let plot = svg(&a);
+        assert_eq!(plot, svg(&a));
+        assert!(plot.starts_with(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"320\" height=\"240\" viewBox=\"0 0 320 240\""
+        ));
+        assert!(plot.ends_with("</svg>\n"));
+        // A curve and an entry in the legend for each expression, and the poles of `tan` marked
+        assert_eq!(plot.matches("<path ").count(), 3);
+        for label in ["sin(x)", "tan(x)", "a * x"] {
+            assert!(plot.contains(&format!(">{}</text>", label)));
+        }
+        assert_eq!(plot.matches("stroke-dasharray").count(), 2);
+        assert!(plot.contains(">-3</text>") && plot.contains(">3</text>"));
+        assert!(!plot.contains("<path d=\"\""));
+        // Without a value for `a` there is no curve to draw
+        assert!(matches!(
+            plot_svg(&exprs, "x", -3.0..=3.0, &variables, &options),
+            Err(Error::UnknownToken)
+        ));
+        let tiny = SvgOptions {
+            width: 40,
+            height: 40,
+        };
+        assert!(matches!(
+            plot_svg(&exprs, "x", -3.0..=3.0, &variables, &tiny),
+            Err(Error::DomainError)
+        ));
+    }
+
+    #[cfg(feature = "bigdecimal")]
+    #[test]
+    fn test_big_decimal() {
+        use crate::{big, Context};
+        use bigdecimal::BigDecimal;
+        let context = Context::<BigDecimal>::default();
+        let eval = |s: &str| context.eval(s).unwrap().to_string();
+        assert_eq!(eval("2^100"), "1267650600228229401496703205376");
+        assert_eq!(
+            eval("0xFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF + 1"),
+            eval("2^128")
+        );
+        assert_eq!(eval("10^40 / 10^38"), "100");
+        assert_eq!(
+            eval("e"),
+            "2.7182818284590452353602874713526624977572470937"
+        );
+        assert_eq!(
+            eval("π"),
+            "3.1415926535897932384626433832795028841971693993751"
+        );
+        assert_eq!(
+            eval("sqrt(2)"),
+            "1.4142135623730950488016887242096980785696718753769"
+        );
+        assert_eq!(eval("sin(pi/6)"), "0.5");
+        assert_eq!(eval("cos(0)"), "1");
+        assert_eq!(eval("2^0.5"), eval("sqrt(2)"));
+        assert_eq!(eval("1/4"), "0.25");
+        assert!(matches!(
+            context.eval("1/0"),
+            Err(crate::Error::DivisionByZero)
+        ));
+        assert!(matches!(
+            context.eval("sqrt(-1)"),
+            Err(crate::Error::DomainError)
+        ));
+        // Rounded results are bounded by the precision instead of growing without limit,
+        // and integers by their bits, so they stay exact whatever the precision
+        assert_eq!(eval("2^1000").len(), 302);
+        assert_eq!(eval("2^100000").len(), 30103);
+        let key = eval("2^4096 - 1");
+        assert_eq!(key.len(), 1234);
+        assert!(key.ends_with("5"));
+        assert_eq!(eval("(2^4096 - 1) - 2^4096"), "-1");
+        assert_eq!(eval("modpow(2, 4096, 2^4096 - 1)"), "1");
+        assert_eq!(eval("modpow(3, 2^127 - 2, 2^127 - 1)"), "1");
+        assert_eq!(eval("gcd(2^200, 6^100)"), eval("2^100"));
+        assert_eq!(
+            eval("isprime(2^127 - 1) + nextprime(13) + choose(52, 5)"),
+            "2598978"
+        );
+        assert!(matches!(
+            context.eval("gcd(1.5, 3)"),
+            Err(crate::Error::ExpectedInteger)
+        ));
+        big::set_precision(5);
+        assert_eq!(
+            eval("3^100"),
+            "515377520732011331036461129765621272702107522001"
+        );
+        assert_eq!(eval("2^4096 - 1"), key);
+        big::set_precision(big::DEFAULT_PRECISION);
+        for s in ["(2^1000)^2000", "0.5^-100000", "sin(1e600)", "cos(-1e600)"] {
+            assert!(
+                matches!(context.eval(s), Err(crate::Error::Overflow)),
+                "{}",
+                s
+            );
+        }
+        assert_eq!(big::precision(), big::DEFAULT_PRECISION);
+    }
 }
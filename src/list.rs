@@ -0,0 +1,164 @@
+use num_traits::{Signed, ToPrimitive};
+use rust_decimal::Decimal;
+
+use crate::{error::Error, integer, value::Value};
+
+/// Applies a binary operation element by element when either side is a list.
+/// Two lists need the same length, a single value is combined with every element.
+/// Returns `None` when neither side is a list.
+pub(crate) fn broadcast(
+    a: &Value,
+    b: &Value,
+    f: impl Fn(&Value, &Value) -> Result<Value, Error>,
+) -> Option<Result<Value, Error>> {
+    let result: Result<Vec<Value>, Error> = match (a, b) {
+        (Value::List(xs), Value::List(ys)) => {
+            if xs.len() != ys.len() {
+                return Some(Err(Error::DimensionMismatch(
+                    xs.len().to_string(),
+                    ys.len().to_string(),
+                )));
+            }
+            xs.iter().zip(ys).map(|(x, y)| f(x, y)).collect()
+        }
+        (Value::List(xs), y) => xs.iter().map(|x| f(x, y)).collect(),
+        (x, Value::List(ys)) => ys.iter().map(|y| f(x, y)).collect(),
+        _ => return None,
+    };
+    Some(result.map(Value::List))
+}
+
+/// Applies a function to every element of a list, or to a single value
+pub(crate) fn map(value: Value, f: fn(Value) -> Result<Value, Error>) -> Result<Value, Error> {
+    match value {
+        Value::List(items) => items
+            .into_iter()
+            .map(|x| map(x, f))
+            .collect::<Result<Vec<_>, Error>>()
+            .map(Value::List),
+        x => f(x),
+    }
+}
+
+/// The element at an index, counting from 0, negative indices count from the end
+/// ## Example
+/// ```rust
+/// use calc::Context;
+///
+/// let context = Context::new();
+/// assert_eq!(context.eval("[1, 2, 3] * 2 + 1").unwrap().to_string(), "[3, 5, 7]");
+/// assert_eq!(context.eval("[1, 2, 3] + [10, 20, 30]").unwrap().to_string(), "[11, 22, 33]");
+/// assert_eq!(context.eval("[4, 5, 6][0] + [4, 5, 6][-1]").unwrap().to_string(), "10");
+/// assert_eq!(context.eval("sum([1, 2, 3]^2)").unwrap().to_string(), "14");
+/// assert_eq!(context.eval("map(abs, [-4, 9])").unwrap().to_string(), "[4, 9]");
+/// assert_eq!(
+///     context.eval("[1, 2] + [1, 2, 3]").unwrap_err().to_string(),
+///     "Dimensions 2 and 3 don't match"
+/// );
+/// ```
+pub fn index(list: &Value, i: &Value) -> Result<Value, Error> {
+    let Value::List(items) = list else {
+        return Err(Error::ExpectedList);
+    };
+    let i = integer::to_integer(i)?;
+    let position = if i.is_negative() {
+        (&i + items.len()).to_usize()
+    } else {
+        i.to_usize()
+    };
+    position
+        .and_then(|p| items.get(p))
+        .cloned()
+        .ok_or_else(|| Error::IndexOutOfRange(i.to_string(), items.len()))
+}
+
+/// The elements of the arguments of a call, the elements of lists taken one by one.
+/// This lets `sum([1, 2, 3])` and `sum(1, 2, 3)` be the same.
+pub(crate) fn elements(args: Vec<Value>) -> Vec<Value> {
+    args.into_iter()
+        .flat_map(|arg| match arg {
+            Value::List(items) => elements(items),
+            x => vec![x],
+        })
+        .collect()
+}
+
+// The numbers of a vector argument, which has to be a list of numbers
+fn vector(value: &Value) -> Result<&[Value], Error> {
+    match value {
+        Value::List(items) if items.iter().all(|x| !matches!(x, Value::List(_))) => Ok(items),
+        Value::List(_) => Err(Error::ExpectedNumber),
+        _ => Err(Error::ExpectedList),
+    }
+}
+
+/// The number of elements of the arguments of a call, with a list counted by its elements
+/// but not the elements of the lists in it, so a matrix has as many as it has rows
+pub(crate) fn count(args: &[Value]) -> usize {
+    args.iter()
+        .map(|arg| match arg {
+            Value::List(items) => items.len(),
+            _ => 1,
+        })
+        .sum()
+}
+
+/// The sum of the elements, 0 for none
+pub fn sum(values: &[Value]) -> Result<Value, Error> {
+    values
+        .iter()
+        .try_fold(Value::Real(Decimal::ZERO), |sum, x| sum.checked_add(x))
+}
+
+/// The product of the elements, 1 for none
+pub fn prod(values: &[Value]) -> Result<Value, Error> {
+    values
+        .iter()
+        .try_fold(Value::Real(Decimal::ONE), |prod, x| prod.checked_mul(x))
+}
+
+/// The dot product of two vectors of the same length
+pub fn dot(a: &Value, b: &Value) -> Result<Value, Error> {
+    let (a, b) = (vector(a)?, vector(b)?);
+    if a.len() != b.len() {
+        return Err(Error::DimensionMismatch(
+            a.len().to_string(),
+            b.len().to_string(),
+        ));
+    }
+    a.iter()
+        .zip(b)
+        .try_fold(Value::Real(Decimal::ZERO), |sum, (x, y)| {
+            sum.checked_add(&x.checked_mul(y)?)
+        })
+}
+
+/// The cross product of two vectors of length 3
+pub fn cross(a: &Value, b: &Value) -> Result<Value, Error> {
+    let (a, b) = (vector(a)?, vector(b)?);
+    if a.len() != 3 || b.len() != 3 {
+        let len = if a.len() != 3 { a.len() } else { b.len() };
+        return Err(Error::DimensionMismatch(len.to_string(), "3".to_string()));
+    }
+    let component = |i: usize, j: usize| {
+        a[i].checked_mul(&b[j])?
+            .checked_sub(&a[j].checked_mul(&b[i])?)
+    };
+    Ok(Value::List(vec![
+        component(1, 2)?,
+        component(2, 0)?,
+        component(0, 1)?,
+    ]))
+}
+
+/// The euclidean length of a vector, the square root of the sum of the squared magnitudes
+pub fn norm(values: &[Value]) -> Result<Value, Error> {
+    let squares = values
+        .iter()
+        .map(|x| {
+            let magnitude = x.abs()?;
+            magnitude.checked_mul(&magnitude)
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    sum(&squares)?.sqrt()
+}
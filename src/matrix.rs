@@ -0,0 +1,308 @@
+//! Linear algebra on matrices written as lists of rows, `[[1, 2], [3, 4]]`.
+//! Decimals are turned into exact fractions for the arithmetic and back at the end,
+//! so the results are rounded once and the same every time.
+
+use num_bigint::BigInt;
+use num_traits::Zero;
+use rust_decimal::Decimal;
+
+use crate::{error::Error, integer::from_integer, rational, value::Value};
+
+// Entries that are not exact, like complex numbers, count as zero below this when looking for pivots,
+// so the rounding of their divisions doesn't make singular matrices regular
+const EPSILON: Decimal = Decimal::from_parts(1, 0, 0, false, 20);
+
+// Whether a value is a list of numbers, rather than a list of lists
+fn is_vector(value: &Value) -> bool {
+    matches!(value, Value::List(items) if !items.iter().any(|x| matches!(x, Value::List(_))))
+}
+
+/// The rows of a matrix, a list of lists of numbers of the same length.
+/// A list of numbers is a matrix with a single row, and `[]` is a matrix without any.
+pub fn rows(value: &Value) -> Result<Vec<Vec<Value>>, Error> {
+    let Value::List(items) = value else {
+        return Err(Error::ExpectedList);
+    };
+    if items.is_empty() {
+        return Ok(Vec::new());
+    } else if is_vector(value) {
+        return Ok(vec![items.clone()]);
+    }
+    let rows = items
+        .iter()
+        .map(|row| match row {
+            Value::List(row) if row.iter().all(|x| !matches!(x, Value::List(_))) => Ok(row.clone()),
+            Value::List(_) => Err(Error::ExpectedNumber),
+            _ => Err(Error::ExpectedList),
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    if let Some(row) = rows.iter().find(|row| row.len() != rows[0].len()) {
+        return Err(Error::DimensionMismatch(
+            rows[0].len().to_string(),
+            row.len().to_string(),
+        ));
+    }
+    Ok(rows)
+}
+
+fn from_rows(rows: Vec<Vec<Value>>) -> Value {
+    Value::List(rows.into_iter().map(Value::List).collect())
+}
+
+// The size of a matrix, like `2×3`
+fn size(rows: &[Vec<Value>]) -> String {
+    format!("{}×{}", rows.len(), rows.first().map_or(0, Vec::len))
+}
+
+fn square(rows: &[Vec<Value>]) -> Result<usize, Error> {
+    if rows.iter().any(|row| row.len() != rows.len()) {
+        return Err(Error::NotSquare(size(rows)));
+    }
+    Ok(rows.len())
+}
+
+// Exact integers keep the results of fractions exact
+fn integer(n: i64) -> Value {
+    from_integer(BigInt::from(n))
+}
+
+// The rows with their decimals as exact fractions, so the elimination doesn't round,
+// and whether there were any decimals to turn the results back into
+fn exact(rows: Vec<Vec<Value>>) -> (Vec<Vec<Value>>, bool) {
+    let mut decimal = false;
+    let rows = rows
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|x| match x {
+                    Value::Real(x) => {
+                        decimal = true;
+                        Value::Rational(rational::from_decimal(x))
+                    }
+                    x => x,
+                })
+                .collect()
+        })
+        .collect();
+    (rows, decimal)
+}
+
+// A result of an exact elimination as a decimal, if the input had decimals
+fn inexact(x: Value, decimal: bool) -> Result<Value, Error> {
+    match x {
+        Value::Rational(r) if decimal => Ok(Value::Real(rational::to_decimal(&r)?.normalize())),
+        x => Ok(x),
+    }
+}
+
+fn is_zero(x: &Value) -> Result<bool, Error> {
+    Ok(match x {
+        Value::Rational(r) => r.is_zero(),
+        _ => x.abs()?.as_real()? < EPSILON,
+    })
+}
+
+// Brings the first `columns` columns to row echelon form, with the largest pivot of each column.
+// Returns the columns of the pivots and whether an odd number of rows were swapped.
+fn echelon(rows: &mut [Vec<Value>], columns: usize) -> Result<(Vec<usize>, bool), Error> {
+    let mut pivots = Vec::new();
+    let mut swapped = false;
+    for column in 0..columns {
+        let top = pivots.len();
+        if top == rows.len() {
+            break;
+        }
+        let mut best: Option<(usize, Value)> = None;
+        for (i, row) in rows.iter().enumerate().skip(top) {
+            if is_zero(&row[column])? {
+                continue;
+            }
+            let magnitude = row[column].abs()?;
+            match &best {
+                Some((_, largest)) if magnitude.cmp_real(largest)?.is_le() => {}
+                _ => best = Some((i, magnitude)),
+            }
+        }
+        let Some((i, _)) = best else {
+            continue;
+        };
+        if i != top {
+            rows.swap(i, top);
+            swapped = !swapped;
+        }
+        let pivot = rows[top].clone();
+        for row in rows.iter_mut().skip(top + 1) {
+            let factor = row[column].checked_div(&pivot[column])?;
+            for (x, p) in row.iter_mut().zip(&pivot).skip(column) {
+                *x = x.checked_sub(&factor.checked_mul(p)?)?;
+            }
+        }
+        pivots.push(column);
+    }
+    Ok((pivots, swapped))
+}
+
+// Solves a square system in echelon form with its right hand sides after the first `n` columns,
+// leaving the solutions there
+fn back_substitute(rows: &mut [Vec<Value>], n: usize) -> Result<(), Error> {
+    for top in (0..n).rev() {
+        let pivot = rows[top][top].clone();
+        for x in rows[top].iter_mut() {
+            *x = x.checked_div(&pivot)?;
+        }
+        let pivot_row = rows[top].clone();
+        for row in rows.iter_mut().take(top) {
+            let factor = row[top].clone();
+            for (x, p) in row.iter_mut().zip(&pivot_row) {
+                *x = x.checked_sub(&factor.checked_mul(p)?)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// Solves `a x = b` for the columns of `b`
+fn solve_rows(a: &Value, b: Vec<Vec<Value>>) -> Result<Vec<Vec<Value>>, Error> {
+    let a = rows(a)?;
+    let n = square(&a)?;
+    if b.len() != n {
+        return Err(Error::DimensionMismatch(size(&a), size(&b)));
+    }
+    let augmented = a.into_iter().zip(b).map(|(a, b)| [a, b].concat()).collect();
+    let (mut augmented, decimal) = exact(augmented);
+    let (pivots, _) = echelon(&mut augmented, n)?;
+    if pivots.len() < n {
+        return Err(Error::SingularMatrix);
+    }
+    back_substitute(&mut augmented, n)?;
+    augmented
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .skip(n)
+                .map(|x| inexact(x, decimal))
+                .collect()
+        })
+        .collect()
+}
+
+/// The matrix product `a @ b`.
+/// A list of numbers on the left is a row and on the right a column, and gives a list back.
+/// ## Example
+/// ```rust
+/// use calc::Context;
+///
+/// let context = Context::new();
+/// let eval = |s: &str| context.eval(s).unwrap().to_string();
+/// assert_eq!(eval("[[1, 2], [3, 4]] @ [[5, 6], [7, 8]]"), "[[19, 22], [43, 50]]");
+/// assert_eq!(eval("[[1, 2], [3, 4]] @ [1, 1]"), "[3, 7]");
+/// assert_eq!(eval("transpose([[1, 2, 3], [4, 5, 6]])"), "[[1, 4], [2, 5], [3, 6]]");
+/// assert_eq!(eval("det([[2, 1], [1, 3]])"), "5");
+/// assert_eq!(eval("solve([[2, 1], [1, 3]], [3, 5])"), "[0.8, 1.4]");
+/// assert_eq!(
+///     context.eval("inv([[1, 2], [2, 4]])").unwrap_err().to_string(),
+///     "The matrix is singular"
+/// );
+/// ```
+pub fn multiply(a: &Value, b: &Value) -> Result<Value, Error> {
+    let (row_vector, column_vector) = (is_vector(a), is_vector(b));
+    let (a, a_decimal) = exact(rows(a)?);
+    let (b, b_decimal) = exact(if column_vector {
+        transpose_rows(rows(b)?)
+    } else {
+        rows(b)?
+    });
+    let decimal = a_decimal || b_decimal;
+    let inner = a.first().map_or(0, Vec::len);
+    let columns = b.first().map_or(0, Vec::len);
+    // A product of empty matrices has no entries, not even the single one of a row times a column
+    if inner != b.len() || a.is_empty() || inner == 0 || columns == 0 {
+        return Err(Error::DimensionMismatch(size(&a), size(&b)));
+    }
+    let product = a
+        .iter()
+        .map(|row| {
+            (0..columns)
+                .map(|j| {
+                    let sum = row.iter().zip(&b).try_fold(integer(0), |sum, (x, b_row)| {
+                        sum.checked_add(&x.checked_mul(&b_row[j])?)
+                    })?;
+                    inexact(sum, decimal)
+                })
+                .collect::<Result<Vec<_>, Error>>()
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    Ok(match (row_vector, column_vector) {
+        (true, true) => product[0][0].clone(),
+        (true, false) => Value::List(product[0].clone()),
+        (false, true) => Value::List(product.into_iter().map(|row| row[0].clone()).collect()),
+        (false, false) => from_rows(product),
+    })
+}
+
+fn transpose_rows(rows: Vec<Vec<Value>>) -> Vec<Vec<Value>> {
+    let columns = rows.first().map_or(0, Vec::len);
+    (0..columns)
+        .map(|j| rows.iter().map(|row| row[j].clone()).collect())
+        .collect()
+}
+
+/// The transpose, rows become columns
+pub fn transpose(a: &Value) -> Result<Value, Error> {
+    Ok(from_rows(transpose_rows(rows(a)?)))
+}
+
+/// The determinant of a square matrix
+pub fn det(a: &Value) -> Result<Value, Error> {
+    let (mut rows, decimal) = exact(rows(a)?);
+    let n = square(&rows)?;
+    let (pivots, swapped) = echelon(&mut rows, n)?;
+    if pivots.len() < n {
+        return inexact(integer(0), decimal);
+    }
+    let sign = integer(if swapped { -1 } else { 1 });
+    let det = rows
+        .iter()
+        .enumerate()
+        .try_fold(sign, |det, (i, row)| det.checked_mul(&row[i]))?;
+    inexact(det, decimal)
+}
+
+/// The inverse of a square matrix, an error if it is singular
+pub fn inv(a: &Value) -> Result<Value, Error> {
+    let n = rows(a)?.len();
+    let identity = (0..n)
+        .map(|i| (0..n).map(|j| integer(i64::from(i == j))).collect())
+        .collect();
+    solve_rows(a, identity).map(from_rows)
+}
+
+/// The number of linearly independent rows
+pub fn rank(a: &Value) -> Result<Value, Error> {
+    let (mut rows, _) = exact(rows(a)?);
+    let columns = rows.first().map_or(0, Vec::len);
+    let (pivots, _) = echelon(&mut rows, columns)?;
+    Ok(integer(pivots.len() as i64))
+}
+
+/// The solution `x` of the linear system `a x = b`, for a list `b` or for each column of a matrix `b`
+pub fn solve(a: &Value, b: &Value) -> Result<Value, Error> {
+    if is_vector(b) {
+        let solution = solve_rows(a, transpose_rows(rows(b)?))?;
+        Ok(Value::List(
+            solution.into_iter().map(|row| row[0].clone()).collect(),
+        ))
+    } else {
+        solve_rows(a, rows(b)?).map(from_rows)
+    }
+}
+
+// The single matrix argument of a function
+pub(crate) fn argument(args: Vec<Value>) -> Result<Value, Error> {
+    let mut args = args.into_iter();
+    match (args.next(), args.next()) {
+        (Some(a), None) => Ok(a),
+        (None, _) => Err(Error::NotEnoughArgs),
+        (Some(_), Some(_)) => Err(Error::TooManyArgs),
+    }
+}
@@ -0,0 +1,722 @@
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fmt::{Debug, Display},
+    str::FromStr,
+};
+
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+use once_cell::sync::Lazy;
+use rust_decimal::{prelude::*, MathematicalOps};
+
+use crate::{
+    complex,
+    error::Error,
+    list, matrix,
+    parse::NumberMode,
+    quantity::{Quantity, Unit},
+    token::{functions, Function, CONSTANTS, FUNCTIONS, INTEGER_FUNCTIONS, POLYNOMIAL_FUNCTIONS},
+    value::Value,
+};
+
+static DECIMAL_FUNCTIONS: Lazy<HashMap<&str, Function<Decimal>>> = Lazy::new(functions);
+static FLOAT_FUNCTIONS: Lazy<HashMap<&str, Function<f64>>> = Lazy::new(functions);
+
+/// A number type expressions can be evaluated with.
+/// Parsing, `shunting_yard` and `evaluate_rpn` are generic over it,
+/// so the same expression can be computed with different backends.
+/// Besides `Value`, it is implemented for plain `Decimal` and `f64`,
+/// which only know about real numbers.
+/// ## Example
+/// ```rust
+/// use calc::Context;
+/// use rust_decimal::Decimal;
+///
+/// let formula = "2 * (3 + 4)^2";
+/// let fast = Context::<f64>::default().eval(formula).unwrap();
+/// let exact = Context::<Decimal>::default().eval(formula).unwrap();
+/// assert_eq!(fast, 98.0);
+/// assert_eq!(exact, Decimal::from(98));
+/// ```
+pub trait Number: Clone + Debug + Display + PartialEq + Sized + 'static {
+    /// Reads a decimal literal like `12.5e-3`
+    fn from_literal(s: &str, mode: NumberMode) -> Result<Self, Error>;
+    /// Converts an integer, e.g. from a `0xFF` literal or the exponent of `x²`
+    fn from_integer(n: BigInt, mode: NumberMode) -> Result<Self, Error>;
+    /// Looks up an inbuilt constant
+    fn constant(name: &str) -> Option<Self>;
+    /// Looks up an inbuilt function
+    fn function(name: &str) -> Option<Function<Self>>;
+    /// One of a unit, like `km`, for the types that have units
+    fn unit(_name: &str) -> Option<Self> {
+        None
+    }
+
+    fn checked_add(&self, rhs: &Self) -> Result<Self, Error>;
+    fn checked_sub(&self, rhs: &Self) -> Result<Self, Error>;
+    fn checked_mul(&self, rhs: &Self) -> Result<Self, Error>;
+    fn checked_div(&self, rhs: &Self) -> Result<Self, Error>;
+    fn checked_pow(&self, rhs: &Self) -> Result<Self, Error>;
+    /// A value with an uncertainty, `self ± rhs`, for the types that have them
+    fn plus_minus(&self, _rhs: &Self) -> Result<Self, Error> {
+        Err(Error::NotSupported)
+    }
+    /// The value written in the unit of `target`, `self to target`
+    fn convert(&self, _target: &Self) -> Result<Self, Error> {
+        Err(Error::NotSupported)
+    }
+    /// A list of values, `[1, 2, 3]`, for the types that have lists
+    fn list(_items: Vec<Self>) -> Result<Self, Error> {
+        Err(Error::NotSupported)
+    }
+    /// The element of a list at an index, `self[i]`
+    fn index(&self, _i: &Self) -> Result<Self, Error> {
+        Err(Error::NotSupported)
+    }
+    /// The matrix product, `self @ rhs`
+    fn matmul(&self, _rhs: &Self) -> Result<Self, Error> {
+        Err(Error::NotSupported)
+    }
+    /// Applies a function of one number, to every element of a list for the types that have them
+    fn map(self, f: fn(Self) -> Result<Self, Error>) -> Result<Self, Error> {
+        f(self)
+    }
+
+    fn sqrt(&self) -> Result<Self, Error>;
+    fn cbrt(&self) -> Result<Self, Error>;
+    fn abs(&self) -> Result<Self, Error>;
+    fn sin(&self) -> Result<Self, Error>;
+    fn cos(&self) -> Result<Self, Error>;
+    fn tan(&self) -> Result<Self, Error>;
+    /// Compares two real numbers
+    fn cmp_real(&self, other: &Self) -> Result<Ordering, Error>;
+
+    fn exp(&self) -> Result<Self, Error>;
+    /// The natural logarithm, an error for zero and for negative reals
+    fn ln(&self) -> Result<Self, Error>;
+    fn atan(&self) -> Result<Self, Error>;
+    /// The largest integer less than or equal to a real number
+    fn floor(&self) -> Result<Self, Error>;
+
+    // The functions below are built from the ones above,
+    // types with faster or more precise versions override them
+
+    fn log10(&self) -> Result<Self, Error> {
+        self.log(&integer::<Self>(10)?)
+    }
+
+    /// The logarithm in any base, which has to be positive and not 1
+    fn log(&self, base: &Self) -> Result<Self, Error> {
+        let ln_base = base.ln()?;
+        if ln_base.cmp_real(&integer::<Self>(0)?)?.is_eq() {
+            return Err(Error::DomainError);
+        }
+        let result = self.ln()?.checked_div(&ln_base)?;
+        // Exact powers of the base give exact results, `log(2, 1024)` is 10
+        let power = result.round(&integer::<Self>(0)?)?;
+        match base.checked_pow(&power).map(|p| p.cmp_real(self)) {
+            Ok(Ok(Ordering::Equal)) => Ok(power),
+            _ => Ok(result),
+        }
+    }
+
+    fn asin(&self) -> Result<Self, Error> {
+        let one = integer::<Self>(1)?;
+        match self.abs()?.cmp_real(&one)? {
+            Ordering::Greater => Err(Error::DomainError),
+            Ordering::Equal => self.sign()?.checked_mul(&half_pi::<Self>()?),
+            Ordering::Less => {
+                let cos = one.checked_sub(&self.checked_mul(self)?)?.sqrt()?;
+                self.checked_div(&cos)?.atan()
+            }
+        }
+    }
+
+    fn acos(&self) -> Result<Self, Error> {
+        half_pi::<Self>()?.checked_sub(&self.asin()?)
+    }
+
+    /// The angle of the point `(x, y)`, `self` being `y`, between -π and π
+    fn atan2(&self, x: &Self) -> Result<Self, Error> {
+        let zero = integer::<Self>(0)?;
+        let pi = Self::constant("pi").ok_or(Error::NotSupported)?;
+        let y_sign = self.cmp_real(&zero)?;
+        match x.cmp_real(&zero)? {
+            Ordering::Greater => self.checked_div(x)?.atan(),
+            Ordering::Less if y_sign.is_lt() => self.checked_div(x)?.atan()?.checked_sub(&pi),
+            Ordering::Less => self.checked_div(x)?.atan()?.checked_add(&pi),
+            Ordering::Equal => match y_sign {
+                Ordering::Equal => Ok(zero),
+                _ => self.sign()?.checked_mul(&half_pi::<Self>()?),
+            },
+        }
+    }
+
+    fn sec(&self) -> Result<Self, Error> {
+        integer::<Self>(1)?
+            .checked_div(&self.cos()?)
+            .map_err(|_| Error::DomainError)
+    }
+
+    fn csc(&self) -> Result<Self, Error> {
+        integer::<Self>(1)?
+            .checked_div(&self.sin()?)
+            .map_err(|_| Error::DomainError)
+    }
+
+    fn sinh(&self) -> Result<Self, Error> {
+        let e = self.exp()?;
+        e.checked_sub(&integer::<Self>(1)?.checked_div(&e)?)?
+            .checked_div(&integer::<Self>(2)?)
+    }
+
+    fn cosh(&self) -> Result<Self, Error> {
+        let e = self.exp()?;
+        e.checked_add(&integer::<Self>(1)?.checked_div(&e)?)?
+            .checked_div(&integer::<Self>(2)?)
+    }
+
+    // With e^(-2|x|), which never overflows
+    fn tanh(&self) -> Result<Self, Error> {
+        let one = integer::<Self>(1)?;
+        let e = self.abs()?.checked_mul(&integer::<Self>(-2)?)?.exp()?;
+        let t = one.checked_sub(&e)?.checked_div(&one.checked_add(&e)?)?;
+        self.sign()?.checked_mul(&t)
+    }
+
+    // Odd, so computed for |x| to avoid cancellation
+    fn asinh(&self) -> Result<Self, Error> {
+        let x = self.abs()?;
+        let root = x
+            .checked_mul(&x)?
+            .checked_add(&integer::<Self>(1)?)?
+            .sqrt()?;
+        self.sign()?.checked_mul(&x.checked_add(&root)?.ln()?)
+    }
+
+    fn acosh(&self) -> Result<Self, Error> {
+        let one = integer::<Self>(1)?;
+        if self.cmp_real(&one)?.is_lt() {
+            return Err(Error::DomainError);
+        }
+        let root = self.checked_mul(self)?.checked_sub(&one)?.sqrt()?;
+        self.checked_add(&root)?.ln()
+    }
+
+    fn atanh(&self) -> Result<Self, Error> {
+        let one = integer::<Self>(1)?;
+        if self.abs()?.cmp_real(&one)?.is_ge() {
+            return Err(Error::DomainError);
+        }
+        let ratio = one
+            .checked_add(self)?
+            .checked_div(&one.checked_sub(self)?)?;
+        ratio.ln()?.checked_div(&integer::<Self>(2)?)
+    }
+
+    fn ceil(&self) -> Result<Self, Error> {
+        let zero = integer::<Self>(0)?;
+        zero.checked_sub(&zero.checked_sub(self)?.floor()?)
+    }
+
+    /// Rounds towards zero
+    fn trunc(&self) -> Result<Self, Error> {
+        if self.cmp_real(&integer::<Self>(0)?)?.is_lt() {
+            self.ceil()
+        } else {
+            self.floor()
+        }
+    }
+
+    /// -1, 0 or 1
+    fn sign(&self) -> Result<Self, Error> {
+        integer::<Self>(match self.cmp_real(&integer::<Self>(0)?)? {
+            Ordering::Less => -1,
+            Ordering::Equal => 0,
+            Ordering::Greater => 1,
+        })
+    }
+
+    /// Rounds to a number of decimal places, halfway cases away from zero.
+    /// Negative places round to tens, hundreds and so on.
+    fn round(&self, places: &Self) -> Result<Self, Error> {
+        round(self, places)
+    }
+}
+
+fn round<N: Number>(x: &N, places: &N) -> Result<N, Error> {
+    if !places.floor()?.cmp_real(places)?.is_eq() {
+        return Err(Error::DomainError);
+    }
+    let scale = integer::<N>(10)?.checked_pow(places)?;
+    let half = integer::<N>(1)?.checked_div(&integer::<N>(2)?)?;
+    let rounded = x
+        .abs()?
+        .checked_mul(&scale)?
+        .checked_add(&half)?
+        .floor()?
+        .checked_div(&scale)?;
+    x.sign()?.checked_mul(&rounded)
+}
+
+// Decimals have at most 28 places, rounding to more leaves them as they are
+// and only overflows the scale
+fn decimal_places<N: Number>(places: &N) -> Result<N, Error> {
+    let most = integer::<N>(28)?;
+    let whole = places.floor()?.cmp_real(places)?.is_eq();
+    Ok(if whole && places.cmp_real(&most)?.is_gt() {
+        most
+    } else {
+        places.clone()
+    })
+}
+
+// A small integer of any number type, an exact fraction for `Value`
+pub(crate) fn integer<N: Number>(n: i64) -> Result<N, Error> {
+    N::from_integer(BigInt::from(n), NumberMode::Rational)
+}
+
+fn half_pi<N: Number>() -> Result<N, Error> {
+    N::constant("pi")
+        .ok_or(Error::NotSupported)?
+        .checked_div(&integer::<N>(2)?)
+}
+
+impl Number for Value {
+    fn from_literal(s: &str, mode: NumberMode) -> Result<Self, Error> {
+        Value::from_literal(s, mode)
+    }
+
+    fn from_integer(n: BigInt, mode: NumberMode) -> Result<Self, Error> {
+        Value::from_integer(n, mode)
+    }
+
+    fn constant(name: &str) -> Option<Self> {
+        CONSTANTS.get(name).cloned()
+    }
+
+    fn function(name: &str) -> Option<Function<Self>> {
+        FUNCTIONS
+            .get(name)
+            .or_else(|| INTEGER_FUNCTIONS.get(name))
+            .or_else(|| POLYNOMIAL_FUNCTIONS.get(name))
+            .cloned()
+    }
+
+    fn unit(name: &str) -> Option<Self> {
+        let unit = Unit::named(name)?;
+        Some(Value::Quantity(Quantity {
+            value: Decimal::ONE,
+            unit,
+        }))
+    }
+
+    fn checked_add(&self, rhs: &Self) -> Result<Self, Error> {
+        Value::checked_add(self, rhs)
+    }
+
+    fn checked_sub(&self, rhs: &Self) -> Result<Self, Error> {
+        Value::checked_sub(self, rhs)
+    }
+
+    fn checked_mul(&self, rhs: &Self) -> Result<Self, Error> {
+        Value::checked_mul(self, rhs)
+    }
+
+    fn checked_div(&self, rhs: &Self) -> Result<Self, Error> {
+        Value::checked_div(self, rhs)
+    }
+
+    fn checked_pow(&self, rhs: &Self) -> Result<Self, Error> {
+        Value::checked_pow(self, rhs)
+    }
+
+    fn convert(&self, target: &Self) -> Result<Self, Error> {
+        Value::convert(self, target)
+    }
+
+    fn list(items: Vec<Self>) -> Result<Self, Error> {
+        Ok(Value::List(items))
+    }
+
+    fn index(&self, i: &Self) -> Result<Self, Error> {
+        list::index(self, i)
+    }
+
+    fn matmul(&self, rhs: &Self) -> Result<Self, Error> {
+        matrix::multiply(self, rhs)
+    }
+
+    fn map(self, f: fn(Self) -> Result<Self, Error>) -> Result<Self, Error> {
+        list::map(self, f)
+    }
+
+    fn sqrt(&self) -> Result<Self, Error> {
+        Value::sqrt(self)
+    }
+
+    fn cbrt(&self) -> Result<Self, Error> {
+        Value::cbrt(self)
+    }
+
+    fn abs(&self) -> Result<Self, Error> {
+        Value::abs(self)
+    }
+
+    fn sin(&self) -> Result<Self, Error> {
+        Value::sin(self)
+    }
+
+    fn cos(&self) -> Result<Self, Error> {
+        Value::cos(self)
+    }
+
+    fn tan(&self) -> Result<Self, Error> {
+        Value::tan(self)
+    }
+
+    fn cmp_real(&self, other: &Self) -> Result<Ordering, Error> {
+        Value::cmp_real(self, other)
+    }
+
+    fn exp(&self) -> Result<Self, Error> {
+        Value::exp(self)
+    }
+
+    fn ln(&self) -> Result<Self, Error> {
+        Value::ln(self)
+    }
+
+    fn log10(&self) -> Result<Self, Error> {
+        Value::log10(self)
+    }
+
+    fn atan(&self) -> Result<Self, Error> {
+        Value::atan(self)
+    }
+
+    fn floor(&self) -> Result<Self, Error> {
+        Value::floor(self)
+    }
+
+    fn round(&self, places: &Self) -> Result<Self, Error> {
+        match self {
+            Value::Rational(_) => round(self, places),
+            _ => round(self, &decimal_places(places)?),
+        }
+    }
+}
+
+impl Number for Decimal {
+    fn from_literal(s: &str, _: NumberMode) -> Result<Self, Error> {
+        if s.contains('e') {
+            Decimal::from_scientific(s).map_err(|_| Error::NumberParseError)
+        } else {
+            Decimal::from_str(s).map_err(|_| Error::NumberParseError)
+        }
+    }
+
+    fn from_integer(n: BigInt, _: NumberMode) -> Result<Self, Error> {
+        n.to_i128()
+            .and_then(Decimal::from_i128)
+            .ok_or(Error::NumberParseError)
+    }
+
+    fn constant(name: &str) -> Option<Self> {
+        match name {
+            "e" => Some(Decimal::E),
+            "pi" | "π" => Some(Decimal::PI),
+            _ => None,
+        }
+    }
+
+    fn function(name: &str) -> Option<Function<Self>> {
+        DECIMAL_FUNCTIONS.get(name).cloned()
+    }
+
+    fn checked_add(&self, rhs: &Self) -> Result<Self, Error> {
+        Decimal::checked_add(*self, *rhs).ok_or(Error::Overflow)
+    }
+
+    fn checked_sub(&self, rhs: &Self) -> Result<Self, Error> {
+        Decimal::checked_sub(*self, *rhs).ok_or(Error::Overflow)
+    }
+
+    fn checked_mul(&self, rhs: &Self) -> Result<Self, Error> {
+        Decimal::checked_mul(*self, *rhs).ok_or(Error::Overflow)
+    }
+
+    fn checked_div(&self, rhs: &Self) -> Result<Self, Error> {
+        if rhs.is_zero() {
+            return Err(Error::DivisionByZero);
+        }
+        Decimal::checked_div(*self, *rhs).ok_or(Error::Overflow)
+    }
+
+    fn checked_pow(&self, rhs: &Self) -> Result<Self, Error> {
+        if self.is_zero() && rhs.is_sign_negative() {
+            Err(Error::DivisionByZero)
+        } else if self.is_sign_negative() && !rhs.fract().is_zero() {
+            // The result would be complex
+            Err(Error::DomainError)
+        } else {
+            pow_decimal(*self, *rhs)
+        }
+    }
+
+    fn sqrt(&self) -> Result<Self, Error> {
+        MathematicalOps::sqrt(self).ok_or(Error::DomainError)
+    }
+
+    // Starts from the f64 estimate and refines it with Newton's method
+    fn cbrt(&self) -> Result<Self, Error> {
+        let x = *self;
+        if x.is_zero() {
+            return Ok(x);
+        }
+        let mut y = x
+            .to_f64()
+            .and_then(|f| Decimal::from_f64(f.cbrt()))
+            .ok_or(Error::Overflow)?;
+        for _ in 0..3 {
+            // (y - x / y²) / 3 is the Newton step without the cube, which overflows near the limit
+            let step = (|| {
+                let error = y.checked_sub(x.checked_div(y.checked_mul(y)?)?)?;
+                error.checked_div(Decimal::from(3))
+            })();
+            y = step
+                .and_then(|step| y.checked_sub(step))
+                .ok_or(Error::Overflow)?;
+        }
+        Ok(y.normalize())
+    }
+
+    fn abs(&self) -> Result<Self, Error> {
+        Ok(Decimal::abs(self))
+    }
+
+    fn sin(&self) -> Result<Self, Error> {
+        self.checked_sin().ok_or(Error::Overflow)
+    }
+
+    fn cos(&self) -> Result<Self, Error> {
+        self.checked_cos().ok_or(Error::Overflow)
+    }
+
+    fn tan(&self) -> Result<Self, Error> {
+        self.checked_tan().ok_or(Error::DomainError)
+    }
+
+    fn cmp_real(&self, other: &Self) -> Result<Ordering, Error> {
+        Ok(self.cmp(other))
+    }
+
+    fn exp(&self) -> Result<Self, Error> {
+        exp_decimal(*self)
+    }
+
+    fn ln(&self) -> Result<Self, Error> {
+        if !self.is_sign_positive() || self.is_zero() {
+            return Err(Error::DomainError);
+        }
+        self.checked_ln().ok_or(Error::DomainError)
+    }
+
+    fn log10(&self) -> Result<Self, Error> {
+        if !self.is_sign_positive() || self.is_zero() {
+            return Err(Error::DomainError);
+        }
+        self.checked_log10().ok_or(Error::DomainError)
+    }
+
+    fn atan(&self) -> Result<Self, Error> {
+        Ok(complex::atan2(*self, Decimal::ONE))
+    }
+
+    fn floor(&self) -> Result<Self, Error> {
+        Ok(Decimal::floor(self))
+    }
+
+    fn round(&self, places: &Self) -> Result<Self, Error> {
+        round(self, &decimal_places(places)?)
+    }
+}
+
+/// `x^y`, rounded to 0 when it is too small for `Decimal` like `exp_decimal`
+pub(crate) fn pow_decimal(x: Decimal, y: Decimal) -> Result<Decimal, Error> {
+    match x.checked_powd(y) {
+        Some(power) => Ok(power),
+        // A base below 1 to a positive power or above 1 to a negative one shrinks
+        None if (x.abs() < Decimal::ONE) == y.is_sign_positive() => Ok(Decimal::ZERO),
+        None => Err(Error::Overflow),
+    }
+}
+
+/// `e^x`, rounded to 0 when it is too small for `Decimal`
+pub(crate) fn exp_decimal(x: Decimal) -> Result<Decimal, Error> {
+    match x.checked_exp() {
+        Some(e) => Ok(e),
+        None if x.is_sign_negative() => Ok(Decimal::ZERO),
+        None => Err(Error::Overflow),
+    }
+}
+
+// Results that are not a number come from arguments outside of the domain
+fn float(x: f64) -> Result<f64, Error> {
+    if x.is_nan() {
+        Err(Error::DomainError)
+    } else {
+        Ok(x)
+    }
+}
+
+impl Number for f64 {
+    fn from_literal(s: &str, _: NumberMode) -> Result<Self, Error> {
+        Ok(s.parse::<f64>()?)
+    }
+
+    fn from_integer(n: BigInt, _: NumberMode) -> Result<Self, Error> {
+        n.to_f64().ok_or(Error::NumberParseError)
+    }
+
+    fn constant(name: &str) -> Option<Self> {
+        match name {
+            "e" => Some(std::f64::consts::E),
+            "pi" | "π" => Some(std::f64::consts::PI),
+            "∞" => Some(f64::INFINITY),
+            _ => None,
+        }
+    }
+
+    fn function(name: &str) -> Option<Function<Self>> {
+        FLOAT_FUNCTIONS.get(name).cloned()
+    }
+
+    fn checked_add(&self, rhs: &Self) -> Result<Self, Error> {
+        float(self + rhs)
+    }
+
+    fn checked_sub(&self, rhs: &Self) -> Result<Self, Error> {
+        float(self - rhs)
+    }
+
+    fn checked_mul(&self, rhs: &Self) -> Result<Self, Error> {
+        float(self * rhs)
+    }
+
+    fn checked_div(&self, rhs: &Self) -> Result<Self, Error> {
+        if *rhs == 0.0 {
+            return Err(Error::DivisionByZero);
+        }
+        float(self / rhs)
+    }
+
+    fn checked_pow(&self, rhs: &Self) -> Result<Self, Error> {
+        if *self == 0.0 && *rhs < 0.0 {
+            return Err(Error::DivisionByZero);
+        }
+        float(self.powf(*rhs))
+    }
+
+    fn sqrt(&self) -> Result<Self, Error> {
+        float(f64::sqrt(*self))
+    }
+
+    fn cbrt(&self) -> Result<Self, Error> {
+        float(f64::cbrt(*self))
+    }
+
+    fn abs(&self) -> Result<Self, Error> {
+        Ok(f64::abs(*self))
+    }
+
+    fn sin(&self) -> Result<Self, Error> {
+        float(f64::sin(*self))
+    }
+
+    fn cos(&self) -> Result<Self, Error> {
+        float(f64::cos(*self))
+    }
+
+    fn tan(&self) -> Result<Self, Error> {
+        float(f64::tan(*self))
+    }
+
+    fn cmp_real(&self, other: &Self) -> Result<Ordering, Error> {
+        self.partial_cmp(other).ok_or(Error::DomainError)
+    }
+
+    fn exp(&self) -> Result<Self, Error> {
+        float(f64::exp(*self))
+    }
+
+    fn ln(&self) -> Result<Self, Error> {
+        if *self <= 0.0 {
+            return Err(Error::DomainError);
+        }
+        float(f64::ln(*self))
+    }
+
+    fn log10(&self) -> Result<Self, Error> {
+        if *self <= 0.0 {
+            return Err(Error::DomainError);
+        }
+        float(f64::log10(*self))
+    }
+
+    fn atan(&self) -> Result<Self, Error> {
+        float(f64::atan(*self))
+    }
+
+    fn floor(&self) -> Result<Self, Error> {
+        float(f64::floor(*self))
+    }
+
+    fn asin(&self) -> Result<Self, Error> {
+        float(f64::asin(*self))
+    }
+
+    fn acos(&self) -> Result<Self, Error> {
+        float(f64::acos(*self))
+    }
+
+    fn atan2(&self, x: &Self) -> Result<Self, Error> {
+        float(f64::atan2(*self, *x))
+    }
+
+    fn sinh(&self) -> Result<Self, Error> {
+        float(f64::sinh(*self))
+    }
+
+    fn cosh(&self) -> Result<Self, Error> {
+        float(f64::cosh(*self))
+    }
+
+    fn tanh(&self) -> Result<Self, Error> {
+        float(f64::tanh(*self))
+    }
+
+    fn asinh(&self) -> Result<Self, Error> {
+        float(f64::asinh(*self))
+    }
+
+    fn acosh(&self) -> Result<Self, Error> {
+        float(f64::acosh(*self))
+    }
+
+    fn atanh(&self) -> Result<Self, Error> {
+        if f64::abs(*self) >= 1.0 {
+            return Err(Error::DomainError);
+        }
+        float(f64::atanh(*self))
+    }
+
+    fn ceil(&self) -> Result<Self, Error> {
+        float(f64::ceil(*self))
+    }
+
+    fn trunc(&self) -> Result<Self, Error> {
+        float(f64::trunc(*self))
+    }
+}
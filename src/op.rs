@@ -2,6 +2,8 @@ use std::collections::HashMap;
 
 use once_cell::sync::Lazy;
 
+use crate::{error::Error, number::Number};
+
 /// BinOp enum
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum BinOp {
@@ -10,6 +12,31 @@ pub enum BinOp {
     Mul,
     Div,
     Pow,
+    /// A value with its uncertainty or tolerance, `9.81 ± 0.02`
+    PlusMinus,
+    /// A unit conversion, `60 mph to m/s`
+    Convert,
+    /// The element of a list, `v[0]`
+    Index,
+    /// The matrix product, `A @ B`, as `*` multiplies lists element by element
+    MatMul,
+}
+
+impl BinOp {
+    /// Applies the operation to two numbers of any type, `a op b`
+    pub fn apply<N: Number>(self, a: &N, b: &N) -> Result<N, Error> {
+        match self {
+            BinOp::Add => a.checked_add(b),
+            BinOp::Sub => a.checked_sub(b),
+            BinOp::Mul => a.checked_mul(b),
+            BinOp::Div => a.checked_div(b),
+            BinOp::Pow => a.checked_pow(b),
+            BinOp::PlusMinus => a.plus_minus(b),
+            BinOp::Convert => a.convert(b),
+            BinOp::Index => a.index(b),
+            BinOp::MatMul => a.matmul(b),
+        }
+    }
 }
 
 /// A binary operator struct
@@ -44,6 +71,21 @@ impl PartialOrd for Operator {
     }
 }
 
+/// The multiplication inserted between adjacent operands, like in `2π`.
+/// It binds tighter than `*`, `/` and `±`, but looser than `^`.
+pub const IMPLICIT_MUL: Operator = Operator::new(BinOp::Mul, 5);
+
+/// The unit conversion written as `to`, `in` or `→`.
+/// It binds looser than everything else, so `5 km + 300 m to mi` converts the whole sum.
+pub const CONVERT: Operator = Operator::new(BinOp::Convert, 1);
+
+/// The multiplication by one degree that the `°` postfix stands for, `30°`.
+/// It binds tightest of all, so `sin(2 * 30°)` and `30°^2` take the degrees first.
+pub const DEGREE: Operator = Operator::new(BinOp::Mul, 7);
+
+/// The indexing of a list, `v[0]`, which binds tighter than any other operator
+pub const INDEX: Operator = Operator::new(BinOp::Index, 8);
+
 // Add the operators to the map
 // Add a few unicode characters to flex on cniles
 /// Map that contains all binary operators
@@ -53,9 +95,15 @@ pub static OPERATORS: Lazy<HashMap<char, Operator>> = Lazy::new(|| {
     map.insert('-', Operator::new(BinOp::Sub, 2));
     map.insert('*', Operator::new(BinOp::Mul, 3));
     map.insert('/', Operator::new(BinOp::Div, 3));
-    map.insert('^', Operator::new(BinOp::Pow, 4));
+    map.insert('^', Operator::new(BinOp::Pow, 6));
+    map.insert('@', Operator::new(BinOp::MatMul, 3));
+    // Binds tighter than `+` and `*`, so `1 ± 0.1 + 2 ± 0.2` and `2 * 3 ± 0.1` need no parentheses
+    map.insert('±', Operator::new(BinOp::PlusMinus, 4));
     // Unicode stuff
     map.insert('×', Operator::new(BinOp::Mul, 3));
     map.insert('÷', Operator::new(BinOp::Div, 3));
+    map.insert('·', Operator::new(BinOp::Mul, 3));
+    map.insert('−', Operator::new(BinOp::Sub, 2));
+    map.insert('→', CONVERT);
     map
 });
@@ -1,41 +1,174 @@
 use crate::error::Error;
 use std::{collections::HashMap, iter::Peekable, str::Chars};
 
-use rust_decimal::{prelude::FromPrimitive, Decimal};
+use num_bigint::BigInt;
 
 use crate::{
-    op::OPERATORS,
-    token::{Token, CONSTANTS, FUNCTIONS},
+    angle::{self, AngleMode},
+    calculus, constants,
+    number::Number,
+    op::{CONVERT, DEGREE, IMPLICIT_MUL, INDEX, OPERATORS},
+    token::{Function, Token},
+    value::Value,
 };
 
-const RESTRICTED_CHARS: &[char] = &['(', ')', '+', '-', '*', '×', '/', '÷', '^', ','];
+// Whether a number follows, a digit or a point and a digit as in `.5`
+fn starts_number(iter: &Peekable<Chars>) -> bool {
+    let mut ahead = iter.clone();
+    match ahead.next() {
+        Some('.') => matches!(ahead.next(), Some(c) if c.is_ascii_digit()),
+        Some(c) => c.is_ascii_digit(),
+        None => false,
+    }
+}
 
 // Parse a number from an iterator of chars
 // Kinda unreadable, but it works
-fn parse_number(c: char, iter: &mut Peekable<Chars>) -> Result<Decimal, Error> {
+fn parse_number<N: Number>(
+    c: char,
+    iter: &mut Peekable<Chars>,
+    mode: NumberMode,
+) -> Result<N, Error> {
     let mut num = String::new();
-    num.push(c);
+    let mut first = c;
+    let negative = matches!(c, '-' | '−');
+    if matches!(c, '+' | '-' | '−') {
+        if negative {
+            num.push('-');
+        }
+        if !starts_number(iter) {
+            return Err(Error::NumberParseError);
+        }
+        first = iter.next().unwrap();
+        // A fraction without its leading zero, like `-.5`
+        if first == '.' {
+            num.push('0');
+        }
+    }
+    // Hexadecimal, octal and binary literals
+    if first == '0' {
+        let radix = match iter.peek() {
+            Some('x' | 'X') => Some(16),
+            Some('o' | 'O') => Some(8),
+            Some('b' | 'B') => Some(2),
+            _ => None,
+        };
+        if let Some(radix) = radix {
+            iter.next();
+            let n = parse_radix_digits(radix, iter)?;
+            return N::from_integer(if negative { -n } else { n }, mode);
+        }
+    }
+    num.push(first);
     while let Some(c) = iter.peek() {
         let c = *c;
-        if c.is_numeric() || c == '.' {
+        if c.is_ascii_digit() || c == '.' {
             num.push(c);
             iter.next();
+        } else if c == '_' {
+            // Digit separators have to sit between two digits
+            if !num.ends_with(|c: char| c.is_ascii_digit()) {
+                return Err(Error::MisplacedSeparator);
+            }
+            iter.next();
+            if !matches!(iter.peek(), Some(c) if c.is_ascii_digit()) {
+                return Err(Error::MisplacedSeparator);
+            }
         } else if c == 'e' {
+            // The `e` only belongs to the number if an exponent follows,
+            // otherwise it is the constant, as in `2e`
+            let mut ahead = iter.clone();
+            ahead.next();
+            let exponent = match ahead.next() {
+                Some('+' | '-') => matches!(ahead.next(), Some(x) if x.is_ascii_digit()),
+                Some(x) => x.is_ascii_digit(),
+                None => false,
+            };
+            if !exponent {
+                break;
+            }
             iter.next();
-            if matches!(iter.peek(), Some(&'+') | Some(&'-'))
-                || matches!(iter.peek(), Some(x) if (&'0'..=&'9').contains(&x))
-            {
-                num.push(c);
-                num.push(iter.next().unwrap());
-            } else {
-                return Err(Error::NumberParseError);
+            num.push(c);
+            num.push(iter.next().unwrap());
+        } else {
+            break;
+        }
+    }
+    N::from_literal(&num, mode)
+}
+
+// Parse the digits following a `0x`, `0o` or `0b` prefix
+// Any other letter or digit glued to the literal is reported as an invalid digit
+fn parse_radix_digits(radix: u32, iter: &mut Peekable<Chars>) -> Result<BigInt, Error> {
+    let mut value = BigInt::from(0);
+    let mut digits = 0;
+    let mut separator = false;
+    while let Some(c) = iter.peek() {
+        let c = *c;
+        if c == '_' {
+            if digits == 0 || separator {
+                return Err(Error::MisplacedSeparator);
             }
+            separator = true;
+        } else if let Some(digit) = c.to_digit(radix) {
+            value = value * radix + digit;
+            digits += 1;
+            separator = false;
+        } else if c.is_ascii_alphanumeric() || c == '.' {
+            return Err(Error::InvalidDigit(c, radix));
         } else {
             break;
         }
+        iter.next();
+    }
+    if separator {
+        return Err(Error::MisplacedSeparator);
+    }
+    if digits == 0 {
+        return Err(Error::NumberParseError);
+    }
+    Ok(value)
+}
+
+/// How number literals are read
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum NumberMode {
+    /// As `Decimal`
+    #[default]
+    Decimal,
+    /// As exact fractions, so `1/3*3` is exactly `1`
+    Rational,
+}
+
+/// Options that change how the input is tokenized
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Insert a multiplication between adjacent operands, e.g. `2π` or `(a+b)(a-b)`
+    pub implicit_multiplication: bool,
+    /// How number literals are read
+    pub mode: NumberMode,
+    /// The unit of the angles of the trig functions, and of the `°` postfix
+    pub angle: AngleMode,
+}
+
+impl ParseOptions {
+    /// Strict mode, every multiplication has to be written out
+    pub fn strict() -> Self {
+        Self {
+            implicit_multiplication: false,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            implicit_multiplication: true,
+            mode: NumberMode::Decimal,
+            angle: AngleMode::Radians,
+        }
     }
-    let num = num.parse::<f64>()?;
-    Ok(Decimal::from_f64(num).unwrap())
 }
 
 /// Parses a string and generates a vector of tokens
@@ -47,14 +180,71 @@ fn parse_number(c: char, iter: &mut Peekable<Chars>) -> Result<Decimal, Error> {
 /// let tokens = parse_str("2 + 3", None).unwrap();
 /// assert_eq!(
 ///     tokens,
-///     vec![Token::Number(Decimal::from_f64(2.0).unwrap()), Token::Operator(*OPERATORS.get(&'+').unwrap()), Token::Number(Decimal::from_f64(3.0).unwrap())]
+///     vec![Token::Number(Decimal::from_f64(2.0).unwrap().into()), Token::Operator(*OPERATORS.get(&'+').unwrap()), Token::Number(Decimal::from_f64(3.0).unwrap().into())]
 /// )
 /// ```
-pub fn parse_str(
+pub fn parse_str(s: &str, variables: Option<&HashMap<String, Value>>) -> Result<Vec<Token>, Error> {
+    parse_str_with(s, variables, &ParseOptions::default())
+}
+
+/// Same as `parse_str`, but with explicit options.
+///
+/// With implicit multiplication turned on, a multiplication is inserted
+/// when something that ends an operand is directly followed by something
+/// that starts one:
+/// - a number, a name or `)` followed by a name, a function or `(`: `2π`, `3x`, `2(3+4)`, `(a+b)(a-b)`, `2sin(x)`
+/// - `)` followed by a number: `(1+2)3`
+///
+/// Two numbers in a row (`2 3`) are never multiplied.
+/// A name that is not known is split into known names when possible, so `2πr` is `2 × π × r`.
+/// Names that are not constants, functions or variables can be units, `3 km` is `3 × km`,
+/// and `to` or `in` after an operand converts to a unit, `60 mph to m/s`.
+/// An `in` with no unit after it is the inch, `2 in to cm`.
+/// Implicit multiplication binds tighter than `*` and `/`, but looser than `^`,
+/// so `1/2x` is `1/(2x)` and `2x^2` is `2(x^2)`.
+/// ## Example
+/// ```rust
+/// use calc::{parse::{parse_str_with, ParseOptions}, op::IMPLICIT_MUL, token::Token};
+/// use rust_decimal::prelude::*;
+/// let tokens = parse_str_with("2(3)", None, &ParseOptions::default()).unwrap();
+/// assert_eq!(
+///     tokens,
+///     vec![
+///         Token::Number(Decimal::from(2).into()),
+///         Token::Operator(IMPLICIT_MUL),
+///         Token::ParLeft,
+///         Token::Number(Decimal::from(3).into()),
+///         Token::ParRight,
+///     ]
+/// );
+/// let strict = parse_str_with("2(3)", None, &ParseOptions::strict()).unwrap();
+/// assert_eq!(strict.len(), 4);
+/// ```
+pub fn parse_str_with(
     s: &str,
-    variables: Option<&HashMap<String, Decimal>>,
+    variables: Option<&HashMap<String, Value>>,
+    options: &ParseOptions,
 ) -> Result<Vec<Token>, Error> {
-    let mut tokens: Vec<Token> = Vec::new();
+    parse_as(s, variables, options)
+}
+
+/// Same as `parse_str_with`, for any number type.
+/// The literals, constants and functions are the ones of `N`.
+/// ## Example
+/// ```rust
+/// use calc::{parse::{parse_as, ParseOptions}, token::Token, Value};
+/// use rust_decimal::Decimal;
+/// let tokens = parse_as::<Value>("2", None, &ParseOptions::default()).unwrap();
+/// assert_eq!(tokens, vec![Token::Number(Value::Real(Decimal::from(2)))]);
+/// ```
+pub fn parse_as<N: Number>(
+    s: &str,
+    variables: Option<&HashMap<String, N>>,
+    options: &ParseOptions,
+) -> Result<Vec<Token<N>>, Error> {
+    let mut tokens: Vec<Token<N>> = Vec::new();
+    // Open parentheses and absolute value bars, to tell opening and closing bars apart
+    let mut groups: Vec<char> = Vec::new();
     let mut iter = s.chars().peekable();
     while let Some(c) = iter.next() {
         if c.is_whitespace() {
@@ -62,18 +252,100 @@ pub fn parse_str(
         } else if c == ',' {
             // This is helpful for parsing numbers
             tokens.push(Token::Comma);
+        } else if let Some(sup) = superscript(c) {
+            // Superscripts are exponents, `x²` is `x^2`
+            let mut exponent = String::from(sup);
+            while let Some(sup) = iter.peek().and_then(|c| superscript(*c)) {
+                exponent.push(sup);
+                iter.next();
+            }
+            let exponent = exponent
+                .parse::<BigInt>()
+                .map_err(|_| Error::NumberParseError)?;
+            tokens.push(Token::Operator(*OPERATORS.get(&'^').unwrap()));
+            tokens.push(Token::Number(N::from_integer(exponent, options.mode)?));
+        } else if c == '√' || c == '∛' {
+            // Prefix roots, `√2` is `sqrt(2)`
+            let name = if c == '√' { "sqrt" } else { "cbrt" };
+            implicit_multiplication(&mut tokens, options);
+            tokens.push(Token::Function(
+                N::function(name).ok_or(Error::UnknownToken)?,
+            ));
+        } else if c == '°' {
+            // A postfix that turns degrees into the angle unit, `sin(30°)` in any mode
+            if !ends_operand(&tokens) {
+                return Err(Error::InvalidToken);
+            }
+            tokens.push(Token::Operator(DEGREE));
+            tokens.push(Token::Number(angle::degree(options.angle)?));
+        } else if c == '|' {
+            // A bar closes the innermost absolute value if it ends an operand,
+            // otherwise it opens a new one
+            if groups.last() == Some(&'|') && ends_operand(&tokens) {
+                groups.pop();
+                tokens.push(Token::ParRight);
+            } else {
+                implicit_multiplication(&mut tokens, options);
+                tokens.push(Token::Function(
+                    N::function("abs").ok_or(Error::UnknownToken)?,
+                ));
+                tokens.push(Token::ParLeft);
+                groups.push('|');
+            }
+        } else if matches!(c, '-' | '−')
+            && !starts_number(&iter)
+            && match tokens.last() {
+                None | Some(Token::Comma) | Some(Token::ParLeft) => true,
+                Some(Token::Operator(op)) => *op < IMPLICIT_MUL,
+                _ => false,
+            }
+        {
+            // A minus before anything but a number negates what follows,
+            // binding looser than `^` so `-x^2` is `-(x^2)`
+            tokens.push(Token::Number(N::from_integer(
+                BigInt::from(-1),
+                options.mode,
+            )?));
+            tokens.push(Token::Operator(IMPLICIT_MUL));
         } else if c.is_numeric()
-            || matches!(c, '+' | '-')
+            || matches!(c, '+' | '-' | '−')
                 && matches!(
                     tokens.last(),
-                    Some(Token::Comma) | None | Some(Token::Operator(_)) | Some(Token::ParLeft)
+                    Some(Token::Comma)
+                        | None
+                        | Some(Token::Operator(_))
+                        | Some(Token::ParLeft)
+                        | Some(Token::Function(_))
                 )
         {
-            let num = parse_number(c, &mut iter)?;
+            let num = parse_number(c, &mut iter, options.mode)?;
+            // A number only continues a product after a closing parenthesis
+            if options.implicit_multiplication && tokens.last() == Some(&Token::ParRight) {
+                tokens.push(Token::Operator(IMPLICIT_MUL));
+            }
             tokens.push(Token::Number(num));
         } else if c == '(' {
+            implicit_multiplication(&mut tokens, options);
             tokens.push(Token::ParLeft);
+            groups.push('(');
         } else if c == ')' {
+            if matches!(groups.pop(), Some('|') | Some('[')) {
+                return Err(Error::UnbalancedParens);
+            }
+            tokens.push(Token::ParRight);
+        } else if c == '[' {
+            // A bracket after an operand indexes it, `v[0]`, otherwise it opens a list
+            if ends_operand(&tokens) {
+                tokens.push(Token::Operator(INDEX));
+            } else {
+                tokens.push(Token::Function(Function::Variadic(N::list, 0)));
+            }
+            tokens.push(Token::ParLeft);
+            groups.push('[');
+        } else if c == ']' {
+            if groups.pop() != Some('[') {
+                return Err(Error::UnbalancedParens);
+            }
             tokens.push(Token::ParRight);
         } else if let Some(op) = OPERATORS.get(&c) {
             tokens.push(Token::Operator(*op));
@@ -81,33 +353,216 @@ pub fn parse_str(
             let mut string = String::new();
             string.push(c);
             while let Some(c) = iter.peek() {
-                if !(c.is_numeric()
-                    || c == &'e'
-                    || c == &'.'
-                    || RESTRICTED_CHARS.contains(c)
-                    || c.is_whitespace())
-                {
+                // Library constants are namespaced, `phys.c`
+                let namespace = c == &'.' && constants::is_namespace(&string);
+                if (c.is_alphanumeric() || c == &'_' || namespace) && superscript(*c).is_none() {
                     string.push(*c);
                     iter.next();
                 } else {
                     break;
                 }
             }
-            // Check if the name is a constant, a function or is in the variable map
-            if let Some(f) = CONSTANTS.get(string.as_str()) {
-                tokens.push(Token::Number(*f));
-            } else if let Some(fun) = FUNCTIONS.get(&string.as_str()) {
-                tokens.push(Token::Function(fun.clone()));
-            } else if let Some(variables) = variables {
-                if let Some(v) = variables.get(&string) {
-                    tokens.push(Token::Number(*v));
-                } else {
-                    return Err(Error::InvalidToken);
+            // A function name not followed by a call is a unit if there is one, `5 min`
+            let call = iter.clone().find(|c| !c.is_whitespace()) == Some('(');
+            let unit = N::unit(&string);
+            // `in` with nothing to convert to is the inch, `2 in + 3 cm`
+            let inch = string == "in" && !conversion_target(&iter);
+            if inch && ends_operand(&tokens) {
+                tokens.push(Token::Operator(IMPLICIT_MUL));
+                tokens.push(Token::Number(N::unit("inch").ok_or(Error::InvalidToken)?));
+            } else if matches!(string.as_str(), "to" | "in") && ends_operand(&tokens) {
+                tokens.push(Token::Operator(CONVERT));
+            } else if let Some((args, rest)) =
+                matches!(string.as_str(), "sum" | "prod" | "integrate")
+                    .then(|| bound_arguments(&iter, variables, options.angle))
+                    .flatten()
+            {
+                // `sum(k^2, k, 1, 100)` evaluates its first argument for each value of `k`,
+                // so it is evaluated here rather than with the other calls
+                implicit_multiplication(&mut tokens, options);
+                let value = calculus::special_form(&string, &args, variables, options)?;
+                tokens.push(Token::Number(value));
+                iter = rest;
+            } else if string == "map" && call {
+                // `map(f, v)` applies the function `f` to every element of `v`,
+                // `f` has to be the name of a function of one argument
+                implicit_multiplication(&mut tokens, options);
+                tokens.push(Token::Function(mapped_function(&mut iter, options.angle)?));
+                tokens.push(Token::ParLeft);
+                groups.push('(');
+            } else if let Some(constant) = constants::lookup(&string) {
+                implicit_multiplication(&mut tokens, options);
+                tokens.push(Token::Number(constant.value(options.mode)?));
+            } else if let Some(token) = lookup(&string, variables, options.angle)
+                .filter(|t| call || unit.is_none() || !matches!(t, Token::Function(_)))
+            {
+                implicit_multiplication(&mut tokens, options);
+                tokens.push(token);
+            } else if let Some(unit) = unit {
+                // Units always multiply the number before them, even in strict mode
+                if ends_operand(&tokens) {
+                    tokens.push(Token::Operator(IMPLICIT_MUL));
+                }
+                tokens.push(Token::Number(unit));
+            } else if string == "∞" {
+                return Err(Error::NoInfinity);
+            } else if options.implicit_multiplication {
+                for token in
+                    split_name(&string, variables, options.angle).ok_or(Error::InvalidToken)?
+                {
+                    implicit_multiplication(&mut tokens, options);
+                    tokens.push(token);
                 }
             } else {
                 return Err(Error::InvalidToken);
             }
         }
     }
+    if groups.contains(&'|') {
+        return Err(Error::UnbalancedParens);
+    }
     Ok(tokens)
 }
+
+// The function of `map(f, v)`, reading `(f,` from the input
+fn mapped_function<N: Number>(
+    iter: &mut Peekable<Chars>,
+    angle: AngleMode,
+) -> Result<Function<N>, Error> {
+    let mut rest = iter.by_ref().skip_while(|c| c.is_whitespace());
+    if rest.next() != Some('(') {
+        return Err(Error::UnexpectedToken);
+    }
+    let mut name = String::new();
+    for c in iter.by_ref() {
+        if c == ',' {
+            break;
+        }
+        name.push(c);
+    }
+    match lookup::<N>(name.trim(), None, angle) {
+        Some(Token::Function(f @ Function::OneParam(_))) => Ok(f),
+        Some(_) => Err(Error::InvalidToken),
+        None => Err(Error::UnknownToken),
+    }
+}
+
+// The arguments of a call with a bound variable, like `sum(k^2, k, 1, 100)`, and the input after it.
+// It is one when the second of four arguments is a name that has no value or is used in the first,
+// otherwise `sum(1, 2, 3, 4)` adds up its arguments. A constant used in the first is one too,
+// for the special form to reject, rather than `sum(i^2, i, 1, 3)` adding up `i^2 + i + 1 + 3`.
+fn bound_arguments<'a, N: Number>(
+    iter: &Peekable<Chars<'a>>,
+    variables: Option<&HashMap<String, N>>,
+    angle: AngleMode,
+) -> Option<(Vec<String>, Peekable<Chars<'a>>)> {
+    let mut rest = iter.clone();
+    while rest.next_if(|c| c.is_whitespace()).is_some() {}
+    rest.next().filter(|c| *c == '(')?;
+    let (mut args, mut arg, mut depth) = (Vec::new(), String::new(), 0);
+    loop {
+        match rest.next()? {
+            ')' if depth == 0 => break,
+            ',' if depth == 0 => args.push(std::mem::take(&mut arg)),
+            c => {
+                match c {
+                    '(' | '[' => depth += 1,
+                    ')' | ']' => depth -= 1,
+                    _ => {}
+                }
+                arg.push(c);
+            }
+        }
+    }
+    args.push(arg);
+    let [body, var, _, _] = &args[..] else {
+        return None;
+    };
+    let var = var.trim();
+    let is_name = var.starts_with(char::is_alphabetic)
+        && var.chars().all(|c| c.is_alphanumeric() || c == '_');
+    let used = body
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .any(|word| word.trim_start_matches(|c: char| c.is_ascii_digit()) == var);
+    match lookup::<N>(var, variables, angle) {
+        None if is_name => Some((args, rest)),
+        Some(Token::Number(_)) if used => Some((args, rest)),
+        _ => None,
+    }
+}
+
+// Map a superscript digit or sign to its plain counterpart
+fn superscript(c: char) -> Option<char> {
+    match c {
+        '⁰' => Some('0'),
+        '¹' => Some('1'),
+        '²' => Some('2'),
+        '³' => Some('3'),
+        '⁴'..='⁹' => char::from_digit(c as u32 - '⁰' as u32, 10),
+        '⁺' => Some('+'),
+        '⁻' => Some('-'),
+        _ => None,
+    }
+}
+
+// Whether a unit to convert to follows, rather than the end, an operator or another conversion
+fn conversion_target(iter: &Peekable<Chars>) -> bool {
+    let rest: String = iter.clone().collect();
+    let rest = rest.trim_start();
+    let word: String = rest.chars().take_while(|c| c.is_alphanumeric()).collect();
+    match rest.chars().next() {
+        None => false,
+        Some(c) if c.is_alphabetic() => !matches!(word.as_str(), "to" | "in"),
+        Some(c) => c == '(',
+    }
+}
+
+// Check if the last token ends an operand
+fn ends_operand<N>(tokens: &[Token<N>]) -> bool {
+    matches!(
+        tokens.last(),
+        Some(Token::Number(_)) | Some(Token::ParRight)
+    )
+}
+
+// Insert a multiplication if the last token ends an operand
+fn implicit_multiplication<N>(tokens: &mut Vec<Token<N>>, options: &ParseOptions) {
+    if options.implicit_multiplication && ends_operand(tokens) {
+        tokens.push(Token::Operator(IMPLICIT_MUL));
+    }
+}
+
+// Check if the name is a constant, a function or is in the variable map.
+// The trig functions are the ones of the angle mode.
+fn lookup<N: Number>(
+    name: &str,
+    variables: Option<&HashMap<String, N>>,
+    angle: AngleMode,
+) -> Option<Token<N>> {
+    if let Some(f) = N::constant(name) {
+        Some(Token::Number(f))
+    } else if let Some(fun) = angle::function(name, angle).or_else(|| N::function(name)) {
+        Some(Token::Function(fun))
+    } else {
+        variables?.get(name).map(|v| Token::Number(v.clone()))
+    }
+}
+
+// Split an unknown name into known names, taking the longest match first
+fn split_name<N: Number>(
+    name: &str,
+    variables: Option<&HashMap<String, N>>,
+    angle: AngleMode,
+) -> Option<Vec<Token<N>>> {
+    let mut tokens = Vec::new();
+    let mut rest = name;
+    while !rest.is_empty() {
+        let (len, token) = (1..=rest.len())
+            .rev()
+            .filter(|i| rest.is_char_boundary(*i))
+            .find_map(|i| lookup(&rest[..i], variables, angle).map(|t| (i, t)))?;
+        tokens.push(token);
+        rest = &rest[len..];
+    }
+    Some(tokens)
+}
@@ -0,0 +1,708 @@
+//! Plots of expressions over an interval of a variable, drawn in the terminal or as SVG images.
+//! Each expression is evaluated at evenly spaced points, where it has no real value
+//! the curve has a gap, and where it jumps, like `tan(x)` at its poles, it is not joined up
+//! and the jump is marked. The vertical range fits the curves, leaving out the few points
+//! that shoot off towards a pole.
+
+use std::{collections::HashMap, io::IsTerminal, ops::RangeInclusive};
+
+use rust_decimal::{
+    prelude::{FromPrimitive, ToPrimitive},
+    Decimal,
+};
+
+use crate::{error::Error, expr::Expr, value::Value};
+
+// The share of the vertical range a curve has to jump between two points to be checked for a jump
+const JUMP_FRACTION: f64 = 0.02;
+// The number of times the interval of a possible jump is halved to tell it from a steep slope
+const JUMP_HALVINGS: usize = 20;
+// The share of the points at either end of the values that can be left out of the range
+const OUTLIER_FRACTION: usize = 50;
+
+/// A curve of an expression, sampled at evenly spaced points
+#[derive(Clone, Debug)]
+pub struct Series {
+    pub label: String,
+    /// The points of the curve, without a value where the expression has no real value
+    pub points: Vec<(f64, Option<f64>)>,
+    /// Where the curve jumps, it is not joined up across these
+    pub jumps: Vec<f64>,
+}
+
+impl Series {
+    /// Evaluates the expression at `count` points from the start to the end of the range,
+    /// with values for the other variables
+    pub fn sample(
+        expr: &Expr,
+        var: &str,
+        range: &RangeInclusive<f64>,
+        count: usize,
+        variables: &HashMap<String, Value>,
+    ) -> Series {
+        let mut variables = variables.clone();
+        let mut f = |x: f64| {
+            let x = Value::Real(Decimal::from_f64(x)?);
+            variables.insert(var.to_string(), x);
+            expr.eval(&variables).ok()?.as_real().ok()?.to_f64()
+        };
+        let (from, to) = (*range.start(), *range.end());
+        let step = (to - from) / (count.max(2) - 1) as f64;
+        let points: Vec<(f64, Option<f64>)> = (0..count.max(2))
+            .map(|i| from + step * i as f64)
+            .map(|x| (x, f(x)))
+            .collect();
+        let mut series = Series {
+            label: expr.to_string(),
+            points,
+            jumps: Vec::new(),
+        };
+        let Some((lo, hi)) = series.range() else {
+            return series;
+        };
+        let threshold = (hi - lo) * JUMP_FRACTION;
+        for i in 0..series.points.len() - 1 {
+            let jump = match (series.points[i], series.points[i + 1]) {
+                // A single point without a value between two with one, like the pole of `1 / x`
+                ((_, Some(_)), (x, None)) => {
+                    let next = series.points.get(i + 2).and_then(|(_, y)| *y);
+                    next.map(|_| x)
+                }
+                ((a, Some(fa)), (b, Some(fb))) if (fb - fa).abs() > threshold => {
+                    jump(&mut f, (a, fa), (b, fb))
+                }
+                _ => None,
+            };
+            series.jumps.extend(jump);
+        }
+        series
+    }
+
+    // The range of the values, without the few far from the others
+    fn range(&self) -> Option<(f64, f64)> {
+        let mut ys: Vec<f64> = self
+            .points
+            .iter()
+            .filter_map(|(_, y)| *y)
+            .filter(|y| y.is_finite())
+            .collect();
+        ys.sort_by(f64::total_cmp);
+        let (min, max) = (*ys.first()?, *ys.last()?);
+        let outliers = ys.len() / OUTLIER_FRACTION;
+        let (lo, hi) = (ys[outliers], ys[ys.len() - 1 - outliers]);
+        if hi == lo || max - min <= (hi - lo) * 4.0 {
+            return Some((min, max));
+        }
+        let margin = (hi - lo) / 4.0;
+        Some(((lo - margin).max(min), (hi + margin).min(max)))
+    }
+
+    // The pieces of the curve that are joined up, between neighbouring points with values
+    fn segments(&self) -> impl Iterator<Item = ((f64, f64), (f64, f64))> + '_ {
+        self.points.windows(2).filter_map(|pair| match pair {
+            [(a, Some(fa)), (b, Some(fb))] if !self.jumps.iter().any(|x| (*a..=*b).contains(x)) => {
+                Some(((*a, *fa), (*b, *fb)))
+            }
+            _ => None,
+        })
+    }
+}
+
+// Where the function jumps between two points, halving the interval towards the bigger change.
+// Across a jump the change stays, or grows towards a pole, while a steep slope flattens out.
+fn jump(
+    f: &mut impl FnMut(f64) -> Option<f64>,
+    (mut a, mut fa): (f64, f64),
+    (mut b, mut fb): (f64, f64),
+) -> Option<f64> {
+    let change = (fb - fa).abs();
+    for _ in 0..JUMP_HALVINGS {
+        let m = (a + b) / 2.0;
+        let Some(fm) = f(m) else {
+            return Some(m);
+        };
+        if (fm - fa).abs() > (fb - fm).abs() {
+            (b, fb) = (m, fm);
+        } else {
+            (a, fa) = (m, fm);
+        }
+    }
+    ((fb - fa).abs() >= change / 2.0).then_some((a + b) / 2.0)
+}
+
+// A round step for about `x`, 1, 2 or 5 times a power of 10
+fn step(x: f64) -> f64 {
+    let power = 10f64.powf(x.log10().floor());
+    let m = match x / power {
+        m if m <= 1.0 => 1.0,
+        m if m <= 2.0 => 2.0,
+        m if m <= 5.0 => 5.0,
+        _ => 10.0,
+    };
+    m * power
+}
+
+// The vertical range of all the curves, widened to round numbers, and more when they are flat
+fn range(series: &[Series]) -> (f64, f64) {
+    let ranges = series.iter().filter_map(Series::range);
+    let (lo, hi) = ranges.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), (a, b)| {
+        (lo.min(a), hi.max(b))
+    });
+    let (lo, hi) = if lo > hi {
+        (-1.0, 1.0)
+    } else if lo == hi {
+        let margin = if lo == 0.0 { 1.0 } else { lo.abs() / 10.0 };
+        (lo - margin, hi + margin)
+    } else {
+        (lo, hi)
+    };
+    // Less a little, so that the rounding errors of the division don't add a step
+    let step = step((hi - lo) / 8.0);
+    (
+        (lo / step + 1e-9).floor() * step,
+        (hi / step - 1e-9).ceil() * step,
+    )
+}
+
+// A number for an axis, with 4 significant digits and without trailing zeros
+fn label(x: f64) -> String {
+    if x == 0.0 || !x.is_finite() {
+        return "0".to_string();
+    }
+    let places = (3 - x.abs().log10().floor() as i32).clamp(0, 12) as usize;
+    let s = format!("{:.*}", places, x);
+    let s = match s.contains('.') {
+        true => s.trim_end_matches('0').trim_end_matches('.'),
+        false => &s,
+    };
+    match s {
+        "-0" => "0".to_string(),
+        s => s.to_string(),
+    }
+}
+
+/// The characters a terminal plot is drawn with
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Style {
+    /// Braille dots, 2 by 4 in each character, with the curves in different colours
+    #[default]
+    Braille,
+    /// A character for each point, a different one for each curve
+    Ascii,
+}
+
+/// The size and characters of a terminal plot
+#[derive(Clone, Copy, Debug)]
+pub struct TerminalOptions {
+    /// The width of the plot in characters, without the labels of the axis
+    pub width: usize,
+    /// The height of the plot in lines, without the labels and the legend
+    pub height: usize,
+    pub style: Style,
+    /// Whether the curves of a Braille plot are told apart by colour, by default when
+    /// the standard output is a terminal
+    pub color: bool,
+}
+
+impl Default for TerminalOptions {
+    fn default() -> Self {
+        TerminalOptions {
+            width: 64,
+            height: 16,
+            style: Style::Braille,
+            color: std::io::stdout().is_terminal(),
+        }
+    }
+}
+
+const MARKERS: [char; 6] = ['*', 'o', 'x', '#', '%', '@'];
+const COLORS: [&str; 6] = [
+    "\x1b[34m", "\x1b[31m", "\x1b[32m", "\x1b[33m", "\x1b[35m", "\x1b[36m",
+];
+const RESET: &str = "\x1b[0m";
+
+// The points of the plot, each with the last curve through it
+struct Canvas {
+    dots: Vec<Vec<Option<usize>>>,
+    columns: f64,
+    rows: f64,
+}
+
+impl Canvas {
+    fn new(columns: usize, rows: usize) -> Canvas {
+        Canvas {
+            dots: vec![vec![None; columns]; rows],
+            columns: columns as f64,
+            rows: rows as f64,
+        }
+    }
+
+    fn dot(&mut self, (x, y): (f64, f64), series: usize) {
+        let (x, y) = (x.round(), y.round());
+        if (0.0..self.columns).contains(&x) && (0.0..self.rows).contains(&y) {
+            self.dots[y as usize][x as usize] = Some(series);
+        }
+    }
+
+    // A straight line between two points, the part of it on the canvas
+    fn line(&mut self, a: (f64, f64), b: (f64, f64), series: usize) {
+        let bounds = ((-0.5, self.columns - 0.5), (-0.5, self.rows - 0.5));
+        let Some((a, b)) = clip(a, b, bounds) else {
+            return;
+        };
+        let steps = (b.0 - a.0).abs().max((b.1 - a.1).abs()).ceil().max(1.0);
+        for i in 0..=steps as usize {
+            let t = i as f64 / steps;
+            self.dot((a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t), series);
+        }
+    }
+}
+
+// The part of a line inside the bounds, with the Liang-Barsky algorithm
+fn clip(
+    a: (f64, f64),
+    b: (f64, f64),
+    ((left, right), (top, bottom)): ((f64, f64), (f64, f64)),
+) -> Option<((f64, f64), (f64, f64))> {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let (mut t0, mut t1) = (0.0f64, 1.0f64);
+    for (p, q) in [
+        (-dx, a.0 - left),
+        (dx, right - a.0),
+        (-dy, a.1 - top),
+        (dy, bottom - a.1),
+    ] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else if p < 0.0 {
+            t0 = t0.max(q / p);
+        } else {
+            t1 = t1.min(q / p);
+        }
+    }
+    let start = (a.0 + dx * t0, a.1 + dy * t0);
+    let end = (a.0 + dx * t1, a.1 + dy * t1);
+    (t0 <= t1).then_some((start, end))
+}
+
+// Whether the range of the variable can be plotted
+fn check(range: &RangeInclusive<f64>) -> Result<(), Error> {
+    let (from, to) = (*range.start(), *range.end());
+    match from.is_finite() && to.is_finite() && from < to {
+        true => Ok(()),
+        false => Err(Error::DomainError),
+    }
+}
+
+// The curves of the expressions, an expression without a value anywhere in the range
+// is an error, the one of its value at the start
+fn sample(
+    exprs: &[Expr],
+    var: &str,
+    range: &RangeInclusive<f64>,
+    count: usize,
+    variables: &HashMap<String, Value>,
+) -> Result<Vec<Series>, Error> {
+    exprs
+        .iter()
+        .map(|expr| {
+            let series = Series::sample(expr, var, range, count, variables);
+            if series.points.iter().all(|(_, y)| y.is_none()) {
+                let mut variables = variables.clone();
+                let start = Decimal::from_f64(*range.start()).ok_or(Error::DomainError)?;
+                variables.insert(var.to_string(), Value::Real(start));
+                expr.eval(&variables)?.as_real()?;
+                return Err(Error::DomainError);
+            }
+            Ok(series)
+        })
+        .collect()
+}
+
+/// A plot of the expressions in the variable over the range for the terminal,
+/// with values for the other variables. The curves are labelled below it when there are several.
+/// ## Example
+/// ```rust
+/// use calc::{expr::Expr, plot::{plot_terminal, Style, TerminalOptions}, ParseOptions};
+/// use std::collections::HashMap;
+///
+/// let expr = Expr::parse("x^2", &ParseOptions::default()).unwrap();
+/// let options = TerminalOptions { width: 9, height: 5, style: Style::Ascii, color: false };
+/// let plot = plot_terminal(&[expr], "x", -2.0..=2.0, &HashMap::new(), &options).unwrap();
+/// assert_eq!(
+///     plot,
+///     "\
+/// 4 +*   |   *
+///   | *  |   *
+///   | ** |  *
+///   |  * | *
+/// 0 +---***---
+///   +---------
+///    -2  0   2
+/// "
+/// );
+/// ```
+pub fn plot_terminal(
+    exprs: &[Expr],
+    var: &str,
+    range: RangeInclusive<f64>,
+    variables: &HashMap<String, Value>,
+    options: &TerminalOptions,
+) -> Result<String, Error> {
+    check(&range)?;
+    let (width, height) = (options.width.max(2), options.height.max(2));
+    let (dx, dy) = match options.style {
+        Style::Braille => (2, 4),
+        Style::Ascii => (1, 1),
+    };
+    let (columns, rows) = (width * dx, height * dy);
+    let series = sample(exprs, var, &range, columns, variables)?;
+    let (from, to) = (*range.start(), *range.end());
+    let (lo, hi) = self::range(&series);
+    let column = |x: f64| (x - from) / (to - from) * (columns - 1) as f64;
+    let row = |y: f64| (hi - y) / (hi - lo) * (rows - 1) as f64;
+    let mut canvas = Canvas::new(columns, rows);
+    for (i, series) in series.iter().enumerate() {
+        for (x, y) in &series.points {
+            if let Some(y) = y {
+                canvas.dot((column(*x), row(*y)), i);
+            }
+        }
+        for (a, b) in series.segments() {
+            canvas.line((column(a.0), row(a.1)), (column(b.0), row(b.1)), i);
+        }
+    }
+
+    // The characters of the plot, then the axes and jumps in the empty ones
+    let colored = options.color && options.style == Style::Braille && series.len() > 1;
+    let mut cells: Vec<Vec<Option<String>>> = (0..height)
+        .map(|cy| {
+            (0..width)
+                .map(|cx| cell(&canvas, cx, cy, options.style, colored))
+                .collect()
+        })
+        .collect();
+    let ascii = options.style == Style::Ascii;
+    let (across, down, crossing, jump) = match ascii {
+        true => ("-", "|", "+", ":"),
+        false => ("─", "│", "┼", "┆"),
+    };
+    let axis_row = (lo..=hi)
+        .contains(&0.0)
+        .then(|| row(0.0).round() as usize / dy);
+    let axis_column = (from..=to)
+        .contains(&0.0)
+        .then(|| column(0.0).round() as usize / dx)
+        .filter(|c| *c != 0);
+    if let Some(r) = axis_row {
+        for cell in cells[r].iter_mut().filter(|c| c.is_none()) {
+            *cell = Some(across.to_string());
+        }
+    }
+    if let Some(c) = axis_column {
+        for line in cells.iter_mut() {
+            match line[c].as_deref() {
+                Some(s) if s == across => line[c] = Some(crossing.to_string()),
+                None => line[c] = Some(down.to_string()),
+                Some(_) => {}
+            }
+        }
+    }
+    for x in series.iter().flat_map(|s| &s.jumps) {
+        let c = column(*x).round() as usize / dx;
+        for line in cells.iter_mut() {
+            if line[c]
+                .as_deref()
+                .is_none_or(|s| [across, down, crossing].contains(&s))
+            {
+                line[c] = Some(jump.to_string());
+            }
+        }
+    }
+
+    // The labels of the vertical axis at the top, the bottom and 0
+    let mut labels = vec![String::new(); height];
+    labels[0] = label(hi);
+    labels[height - 1] = label(lo);
+    if let Some(r) = axis_row.filter(|r| *r != 0 && *r != height - 1) {
+        labels[r] = label(0.0);
+    }
+    let margin = labels.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    let mut plot = String::new();
+    for (line, label) in cells.iter().zip(&labels) {
+        let tick = match (ascii, label.is_empty()) {
+            (true, true) => '|',
+            (true, false) => '+',
+            (false, true) => '│',
+            (false, false) => '┤',
+        };
+        let line: String = line.iter().map(|c| c.as_deref().unwrap_or(" ")).collect();
+        plot.push_str(&format!("{:>margin$} {}{}\n", label, tick, line.trim_end()));
+    }
+    let (corner, rule) = if ascii { ('+', "-") } else { ('└', "─") };
+    plot.push_str(&format!(
+        "{:margin$} {}{}\n",
+        "",
+        corner,
+        rule.repeat(width)
+    ));
+
+    // The labels of the horizontal axis at the ends and 0, where they fit
+    let mut axis = vec![' '; width];
+    let mut place = |text: &str, start: usize| {
+        let chars: Vec<char> = text.chars().collect();
+        let end = start + chars.len();
+        let free = |i: usize| axis[i] == ' ' && (i == 0 || axis[i - 1] == ' ');
+        if end <= width && (start..end).all(free) && (end == width || axis[end] == ' ') {
+            axis[start..end].copy_from_slice(&chars);
+        }
+    };
+    let (first, last) = (label(from), label(to));
+    place(&first, 0);
+    place(&last, width.saturating_sub(last.chars().count()));
+    if let Some(c) = axis_column {
+        let zero = label(0.0);
+        place(&zero, c);
+    }
+    let axis: String = axis.into_iter().collect();
+    plot.push_str(&format!("{:margin$}  {}\n", "", axis.trim_end()));
+
+    // The legend, for several curves
+    if series.len() > 1 {
+        let entries: Vec<String> = series
+            .iter()
+            .enumerate()
+            .map(|(i, s)| match options.style {
+                Style::Braille if !colored => format!("⣿ {}", s.label),
+                Style::Braille => format!("{}⣿{} {}", COLORS[i % COLORS.len()], RESET, s.label),
+                Style::Ascii => format!("{} {}", MARKERS[i % MARKERS.len()], s.label),
+            })
+            .collect();
+        plot.push_str(&format!("{:margin$}  {}\n", "", entries.join("   ")));
+    }
+    Ok(plot)
+}
+
+// The character of a cell of the plot, if a curve goes through it
+fn cell(canvas: &Canvas, cx: usize, cy: usize, style: Style, colored: bool) -> Option<String> {
+    match style {
+        Style::Ascii => canvas.dots[cy][cx].map(|s| MARKERS[s % MARKERS.len()].to_string()),
+        Style::Braille => {
+            // The bits of the dots of a braille character, by column then row
+            const BITS: [[u32; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+            let (mut bits, mut series) = (0, None);
+            for (dx, column) in BITS.iter().enumerate() {
+                for (dy, bit) in column.iter().enumerate() {
+                    if let Some(s) = canvas.dots[cy * 4 + dy][cx * 2 + dx] {
+                        bits |= bit;
+                        series = series.max(Some(s));
+                    }
+                }
+            }
+            let c = char::from_u32(0x2800 + bits)?;
+            match (series, colored) {
+                (None, _) => None,
+                (Some(s), true) => Some(format!("{}{}{}", COLORS[s % COLORS.len()], c, RESET)),
+                (Some(_), false) => Some(c.to_string()),
+            }
+        }
+    }
+}
+
+/// The size of an SVG plot
+#[derive(Clone, Copy, Debug)]
+pub struct SvgOptions {
+    /// The width of the image in pixels
+    pub width: u32,
+    /// The height of the image in pixels
+    pub height: u32,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            width: 640,
+            height: 400,
+        }
+    }
+}
+
+const STROKES: [&str; 6] = [
+    "#1f77b4", "#d62728", "#2ca02c", "#ff7f0e", "#9467bd", "#17becf",
+];
+// The space around the plot for the labels of the axes, and below it for the legend
+const LEFT: f64 = 56.0;
+const RIGHT: f64 = 16.0;
+const TOP: f64 = 16.0;
+const BOTTOM: f64 = 56.0;
+// The width of a character of the labels, for the space of the legend
+const CHAR_WIDTH: f64 = 7.0;
+
+// The multiples of a round step in the range, for the grid lines
+fn ticks(lo: f64, hi: f64) -> Vec<f64> {
+    let step = step((hi - lo) / 8.0);
+    let (first, last) = ((lo / step - 1e-9).ceil(), (hi / step + 1e-9).floor());
+    (first as i64..=last as i64)
+        .map(|k| k as f64 * step)
+        .collect()
+}
+
+// Text for an SVG document, with the characters of markup escaped
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// A plot of the expressions in the variable over the range as a self-contained SVG image,
+/// with values for the other variables. It has grid lines, labelled axes and a legend,
+/// and the same plot is always the same text.
+/// ## Example
+/// ```rust
+/// use calc::{expr::Expr, plot::{plot_svg, SvgOptions}, ParseOptions};
+/// use std::collections::HashMap;
+///
+/// let parse = |s: &str| Expr::parse(s, &ParseOptions::default()).unwrap();
+/// let options = SvgOptions { width: 480, height: 320 };
+/// let exprs = [parse("sin(x)"), parse("cos(x)")];
+/// let svg = plot_svg(&exprs, "x", -3.0..=3.0, &HashMap::new(), &options).unwrap();
+/// assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"480\" height=\"320\""));
+/// assert!(svg.contains(">sin(x)</text>") && svg.contains(">cos(x)</text>"));
+/// assert!(svg.trim_end().ends_with("</svg>"));
+/// ```
+pub fn plot_svg(
+    exprs: &[Expr],
+    var: &str,
+    range: RangeInclusive<f64>,
+    variables: &HashMap<String, Value>,
+    options: &SvgOptions,
+) -> Result<String, Error> {
+    check(&range)?;
+    let (width, height) = (options.width as f64, options.height as f64);
+    let (plot_width, plot_height) = (width - LEFT - RIGHT, height - TOP - BOTTOM);
+    if plot_width < 1.0 || plot_height < 1.0 {
+        return Err(Error::DomainError);
+    }
+    // A point for each pixel across
+    let series = sample(exprs, var, &range, plot_width as usize + 1, variables)?;
+    let (from, to) = (*range.start(), *range.end());
+    let (lo, hi) = self::range(&series);
+    let x = |v: f64| LEFT + (v - from) / (to - from) * plot_width;
+    let y = |v: f64| TOP + (hi - v) / (hi - lo) * plot_height;
+    let (left, right, top, bottom) = (LEFT, LEFT + plot_width, TOP, TOP + plot_height);
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+         viewBox=\"0 0 {w} {h}\" font-family=\"sans-serif\" font-size=\"12\">\n",
+        w = options.width,
+        h = options.height,
+    );
+    svg.push_str(&format!(
+        "<rect width=\"{}\" height=\"{}\" fill=\"#ffffff\"/>\n",
+        options.width, options.height
+    ));
+
+    // The grid lines and their labels
+    for v in ticks(from, to) {
+        svg.push_str(&format!(
+            "<line x1=\"{x:.2}\" y1=\"{top:.2}\" x2=\"{x:.2}\" y2=\"{bottom:.2}\" stroke=\"#e0e0e0\"/>\n",
+            x = x(v),
+        ));
+        svg.push_str(&format!(
+            "<text x=\"{:.2}\" y=\"{:.2}\" text-anchor=\"middle\">{}</text>\n",
+            x(v),
+            bottom + 16.0,
+            label(v)
+        ));
+    }
+    for v in ticks(lo, hi) {
+        svg.push_str(&format!(
+            "<line x1=\"{left:.2}\" y1=\"{y:.2}\" x2=\"{right:.2}\" y2=\"{y:.2}\" stroke=\"#e0e0e0\"/>\n",
+            y = y(v),
+        ));
+        svg.push_str(&format!(
+            "<text x=\"{:.2}\" y=\"{:.2}\" text-anchor=\"end\">{}</text>\n",
+            left - 6.0,
+            y(v) + 4.0,
+            label(v)
+        ));
+    }
+
+    // The axes through 0 and the frame
+    if (lo..=hi).contains(&0.0) {
+        svg.push_str(&format!(
+            "<line x1=\"{left:.2}\" y1=\"{y:.2}\" x2=\"{right:.2}\" y2=\"{y:.2}\" stroke=\"#404040\"/>\n",
+            y = y(0.0),
+        ));
+    }
+    if (from..=to).contains(&0.0) {
+        svg.push_str(&format!(
+            "<line x1=\"{x:.2}\" y1=\"{top:.2}\" x2=\"{x:.2}\" y2=\"{bottom:.2}\" stroke=\"#404040\"/>\n",
+            x = x(0.0),
+        ));
+    }
+    svg.push_str(&format!(
+        "<rect x=\"{left:.2}\" y=\"{top:.2}\" width=\"{plot_width:.2}\" height=\"{plot_height:.2}\" \
+         fill=\"none\" stroke=\"#404040\"/>\n"
+    ));
+
+    // The jumps, one line for those at the same pixel, then the curves over them
+    let mut jumps: Vec<f64> = series.iter().flat_map(|s| s.jumps.clone()).collect();
+    jumps.sort_by(f64::total_cmp);
+    jumps.dedup_by(|b, a| (x(*b) - x(*a)).abs() < 1.0);
+    for v in &jumps {
+        svg.push_str(&format!(
+            "<line x1=\"{x:.2}\" y1=\"{top:.2}\" x2=\"{x:.2}\" y2=\"{bottom:.2}\" \
+             stroke=\"#909090\" stroke-dasharray=\"4 4\"/>\n",
+            x = x(*v),
+        ));
+    }
+    for (i, s) in series.iter().enumerate() {
+        let mut path = String::new();
+        let mut end = None;
+        for (a, b) in s.segments() {
+            let bounds = ((left, right), (top, bottom));
+            let Some((a, b)) = clip((x(a.0), y(a.1)), (x(b.0), y(b.1)), bounds) else {
+                continue;
+            };
+            let point = |(x, y): (f64, f64)| format!("{:.2} {:.2}", x, y);
+            let (a, b) = (point(a), point(b));
+            if end.as_ref() != Some(&a) {
+                path.push_str(&format!("M{} ", a));
+            }
+            path.push_str(&format!("L{} ", b));
+            end = Some(b);
+        }
+        svg.push_str(&format!(
+            "<path d=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"2\" stroke-linejoin=\"round\"/>\n",
+            path.trim_end(),
+            STROKES[i % STROKES.len()]
+        ));
+    }
+
+    // The legend, a line in the colour of each curve and its expression
+    let mut position = left;
+    let baseline = height - 12.0;
+    for (i, s) in series.iter().enumerate() {
+        svg.push_str(&format!(
+            "<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\" stroke=\"{}\" stroke-width=\"2\"/>\n",
+            position,
+            baseline - 4.0,
+            position + 20.0,
+            baseline - 4.0,
+            STROKES[i % STROKES.len()]
+        ));
+        svg.push_str(&format!(
+            "<text x=\"{:.2}\" y=\"{:.2}\">{}</text>\n",
+            position + 26.0,
+            baseline,
+            escape(&s.label)
+        ));
+        position += 26.0 + s.label.chars().count() as f64 * CHAR_WIDTH + 24.0;
+    }
+    svg.push_str("</svg>\n");
+    Ok(svg)
+}
@@ -0,0 +1,618 @@
+//! Polynomials in `x` with numbers as coefficients, `poly([1, 0, -2])` is `x^2 - 2`.
+//! They are values like numbers and lists, so `+`, `-`, `*` and `^` work on them,
+//! and `/` when the division leaves no remainder.
+
+use std::fmt::{Display, Formatter};
+
+use num_bigint::BigInt;
+use num_traits::{FromPrimitive, ToPrimitive, Zero};
+use rust_decimal::Decimal;
+
+use crate::{
+    complex::Complex,
+    error::Error,
+    integer::{from_integer, to_integer},
+    matrix,
+    value::Value,
+};
+
+const MAX_ITERATIONS: usize = 1000;
+// The roots are found to this many decimal places, or fewer when rounding them makes them exact
+const PLACES: u32 = 20;
+const TOLERANCE: Decimal = Decimal::from_parts(1, 0, 0, false, 24);
+// How close the polynomial from the roots has to come when they converge slowly
+const CLOSENESS: Decimal = Decimal::from_parts(1, 0, 0, false, 16);
+// Starts from other guesses when the iteration fails
+const MAX_ATTEMPTS: usize = 5;
+const POLISH_STEPS: usize = 10;
+// The rounding in the value of a polynomial, relative to the sizes of its terms
+const NOISE: Decimal = Decimal::from_parts(1, 0, 0, false, 27);
+// Scaled roots smaller than this are found again with the larger ones divided out
+const SMALL: Decimal = Decimal::from_parts(1, 0, 0, false, 4);
+
+/// A polynomial, with its coefficients from the constant term up.
+/// The highest coefficient is never 0, so the zero polynomial has none.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Polynomial {
+    coefficients: Vec<Value>,
+}
+
+fn is_zero(x: &Value) -> bool {
+    match x {
+        Value::Real(x) => x.is_zero(),
+        Value::Rational(r) => r.is_zero(),
+        Value::Complex(z) => z.re.is_zero() && z.im.is_zero(),
+        _ => false,
+    }
+}
+
+fn integer(n: usize) -> Value {
+    from_integer(BigInt::from(n))
+}
+
+impl Polynomial {
+    /// A polynomial from its coefficients, from the constant term up.
+    /// They have to be numbers, complex ones too.
+    pub fn new(coefficients: Vec<Value>) -> Result<Polynomial, Error> {
+        let mut coefficients = coefficients
+            .into_iter()
+            .map(|x| match x {
+                Value::Real(x) => Ok(Value::Real(x.normalize())),
+                Value::Rational(_) | Value::Complex(_) => Ok(x),
+                Value::Quantity(q) => Err(Error::UnitsNotAllowed(q.unit.to_string())),
+                Value::List(_) | Value::Polynomial(_) => Err(Error::ExpectedNumber),
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        while coefficients.last().is_some_and(is_zero) {
+            coefficients.pop();
+        }
+        Ok(Polynomial { coefficients })
+    }
+
+    /// A polynomial from its coefficients from the highest power down, as they are written
+    pub fn from_highest(mut coefficients: Vec<Value>) -> Result<Polynomial, Error> {
+        coefficients.reverse();
+        Polynomial::new(coefficients)
+    }
+
+    fn constant(x: Value) -> Result<Polynomial, Error> {
+        Polynomial::new(vec![x])
+    }
+
+    /// The coefficients from the constant term up
+    pub fn coefficients(&self) -> &[Value] {
+        &self.coefficients
+    }
+
+    /// The coefficients from the highest power down, 0 for the zero polynomial
+    pub fn highest_first(&self) -> Vec<Value> {
+        match self.coefficients.is_empty() {
+            true => vec![integer(0)],
+            false => self.coefficients.iter().rev().cloned().collect(),
+        }
+    }
+
+    /// The highest power, the zero polynomial has degree 0 too
+    pub fn degree(&self) -> usize {
+        self.coefficients.len().saturating_sub(1)
+    }
+
+    // The coefficient of `x^i`, 0 above the degree
+    fn coefficient(&self, i: usize) -> Value {
+        self.coefficients
+            .get(i)
+            .cloned()
+            .unwrap_or_else(|| integer(0))
+    }
+
+    fn combine(
+        &self,
+        other: &Polynomial,
+        f: impl Fn(&Value, &Value) -> Result<Value, Error>,
+    ) -> Result<Polynomial, Error> {
+        let len = self.coefficients.len().max(other.coefficients.len());
+        (0..len)
+            .map(|i| f(&self.coefficient(i), &other.coefficient(i)))
+            .collect::<Result<Vec<_>, Error>>()
+            .and_then(Polynomial::new)
+    }
+
+    pub fn add(&self, other: &Polynomial) -> Result<Polynomial, Error> {
+        self.combine(other, Value::checked_add)
+    }
+
+    pub fn sub(&self, other: &Polynomial) -> Result<Polynomial, Error> {
+        self.combine(other, Value::checked_sub)
+    }
+
+    pub fn mul(&self, other: &Polynomial) -> Result<Polynomial, Error> {
+        if self.coefficients.is_empty() || other.coefficients.is_empty() {
+            return Polynomial::new(Vec::new());
+        }
+        let mut product = vec![integer(0); self.coefficients.len() + other.coefficients.len() - 1];
+        for (i, a) in self.coefficients.iter().enumerate() {
+            for (j, b) in other.coefficients.iter().enumerate() {
+                product[i + j] = product[i + j].checked_add(&a.checked_mul(b)?)?;
+            }
+        }
+        Polynomial::new(product)
+    }
+
+    pub fn pow(&self, n: u32) -> Result<Polynomial, Error> {
+        (0..n).try_fold(Polynomial::constant(integer(1))?, |p, _| p.mul(self))
+    }
+
+    /// The quotient and the remainder of the long division by `divisor`
+    pub fn div_rem(&self, divisor: &Polynomial) -> Result<(Polynomial, Polynomial), Error> {
+        let Some(lead) = divisor.coefficients.last() else {
+            return Err(Error::DivisionByZero);
+        };
+        let mut remainder = self.coefficients.clone();
+        let n = divisor.coefficients.len();
+        if remainder.len() < n {
+            return Ok((Polynomial::new(Vec::new())?, self.clone()));
+        }
+        let mut quotient = vec![integer(0); remainder.len() - n + 1];
+        for i in (0..quotient.len()).rev() {
+            let factor = remainder[i + n - 1].checked_div(lead)?;
+            for (j, d) in divisor.coefficients.iter().enumerate() {
+                remainder[i + j] = remainder[i + j].checked_sub(&factor.checked_mul(d)?)?;
+            }
+            // The top is gone, also when the decimals don't cancel exactly
+            remainder[i + n - 1] = integer(0);
+            quotient[i] = factor;
+        }
+        Ok((Polynomial::new(quotient)?, Polynomial::new(remainder)?))
+    }
+
+    pub fn derivative(&self) -> Result<Polynomial, Error> {
+        self.coefficients
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(i, c)| c.checked_mul(&integer(i)))
+            .collect::<Result<Vec<_>, Error>>()
+            .and_then(Polynomial::new)
+    }
+
+    /// The value at `x`, which can be a list of points or another polynomial
+    pub fn eval(&self, x: &Value) -> Result<Value, Error> {
+        self.coefficients
+            .iter()
+            .rev()
+            .try_fold(integer(0), |sum, c| sum.checked_mul(x)?.checked_add(c))
+    }
+
+    fn eval_complex(&self, z: &Complex) -> Result<Complex, Error> {
+        self.coefficients
+            .iter()
+            .rev()
+            .try_fold(Complex::from(Decimal::ZERO), |sum, c| {
+                sum.checked_mul(z)?.checked_add(&c.to_complex()?)
+            })
+    }
+
+    /// All the roots, real and complex, with the repeated ones as many times as they repeat.
+    /// They are found with the Durand–Kerner method, except the ones at 0 and those of what is left
+    /// when that is linear or quadratic, which come from the formulas.
+    /// ## Example
+    /// ```rust
+    /// use calc::Context;
+    ///
+    /// let context = Context::new();
+    /// let eval = |s: &str| context.eval(s).unwrap().to_string();
+    /// assert_eq!(eval("poly([1, 0, -2])"), "x^2 - 2");
+    /// assert_eq!(eval("roots(poly([1, 0, -2]))"), "[-1.4142135623730950488, 1.4142135623730950488]");
+    /// assert_eq!(eval("roots([1, 0, 1, 0])"), "[-i, 0, i]");
+    /// assert_eq!(eval("(poly([1, 1]) * poly([1, -1]))^2"), "x^4 - 2x^2 + 1");
+    /// assert_eq!(eval("polydiv(poly([1, 0, 0, -1]), poly([1, -1]))"), "[x^2 + x + 1, 0]");
+    /// assert_eq!(eval("polyder(poly([2, 3, 4])) + polyval([1, 2], 5)"), "4x + 10");
+    /// assert_eq!(eval("polyfit([0, 1, 2, 3], [1, 3, 5, 7], 1)"), "2x + 1");
+    /// ```
+    pub fn roots(&self) -> Result<Vec<Value>, Error> {
+        if self.coefficients.is_empty() {
+            return Err(Error::DomainError);
+        }
+        let zeros = self.coefficients.iter().take_while(|c| is_zero(c)).count();
+        let rest = Polynomial::new(self.coefficients[zeros..].to_vec())?;
+        let mut roots = vec![integer(0); zeros];
+        match &rest.coefficients[..] {
+            [_] => {}
+            [a, b] => roots.push(
+                a.checked_div(b)?
+                    .checked_mul(&integer(0).checked_sub(&integer(1))?)?,
+            ),
+            _ => {
+                for z in rest.approximate_roots()? {
+                    roots.push(Value::from(rest.snap(z)?));
+                }
+            }
+        }
+        let mut keyed = roots
+            .into_iter()
+            .map(|root| Ok((root.to_complex()?, root)))
+            .collect::<Result<Vec<_>, Error>>()?;
+        keyed.sort_by_key(|(z, _)| (z.re, z.im));
+        Ok(keyed.into_iter().map(|(_, root)| root).collect())
+    }
+
+    // The roots of a polynomial without any at 0, found for `x` scaled so they are at most
+    // 2 in size, and then polished on the polynomial itself. Roots far smaller than the
+    // largest lose their digits in the scaling, so they come from what is left when the
+    // others are divided out, scaled again.
+    fn approximate_roots(&self) -> Result<Vec<Complex>, Error> {
+        let lead = self.coefficients.last().ok_or(Error::DomainError)?;
+        let monic = self
+            .coefficients
+            .iter()
+            .map(|c| c.checked_div(lead)?.to_complex())
+            .collect::<Result<Vec<_>, Error>>()?;
+        // Every root is within twice this radius of 0 (Fujiwara's bound)
+        let degree = self.degree();
+        let mut radius = Decimal::ZERO;
+        for (k, c) in monic[..degree].iter().rev().enumerate() {
+            let size = c.abs()?.to_f64().ok_or(Error::Overflow)?;
+            let term = Decimal::from_f64(size.powf(1.0 / (k + 1) as f64)).ok_or(Error::Overflow)?;
+            radius = radius.max(term);
+        }
+        if radius.is_zero() {
+            radius = Decimal::ONE;
+        }
+        let shrink = |x: Decimal| x.checked_div(radius).ok_or(Error::Overflow);
+        let scaled = monic
+            .iter()
+            .enumerate()
+            .map(|(k, c)| {
+                let mut z = *c;
+                for _ in k..degree {
+                    z = Complex::new(shrink(z.re)?, shrink(z.im)?);
+                }
+                Ok(Value::from(z))
+            })
+            .collect::<Result<Vec<_>, Error>>()
+            .and_then(Polynomial::new)?;
+        let found = match degree {
+            2 => scaled.quadratic()?,
+            _ => scaled.durand_kerner()?,
+        };
+        let (large, small): (Vec<_>, Vec<_>) = found
+            .into_iter()
+            .partition(|y| y.abs().is_ok_and(|size| size >= SMALL));
+        let mut roots = Vec::with_capacity(degree);
+        let mut factors = Polynomial::constant(integer(1))?;
+        for y in large {
+            let root = y.checked_mul(&Complex::from(radius))?;
+            let minus = Complex::from(Decimal::ZERO).checked_sub(&root)?;
+            factors = factors.mul(&Polynomial::new(vec![Value::from(minus), integer(1)])?)?;
+            roots.push(root);
+        }
+        if !small.is_empty() && !roots.is_empty() {
+            roots.extend(self.div_rem(&factors)?.0.approximate_roots()?);
+        } else {
+            for y in small {
+                roots.push(y.checked_mul(&Complex::from(radius))?);
+            }
+        }
+        self.polish(&mut roots)?;
+        Ok(roots)
+    }
+
+    // The roots of `x^2 + bx + c`, the larger one from the formula with the sign that
+    // adds to `b`, and the other from their product `c` so it keeps its digits
+    fn quadratic(&self) -> Result<Vec<Complex>, Error> {
+        let c = self.coefficient(0).to_complex()?;
+        let b = self.coefficient(1).to_complex()?;
+        let four = Complex::from(Decimal::from(4));
+        let mut d = b
+            .checked_mul(&b)?
+            .checked_sub(&four.checked_mul(&c)?)?
+            .sqrt()?;
+        if b.conj().checked_mul(&d)?.re.is_sign_negative() {
+            d = Complex::from(Decimal::ZERO).checked_sub(&d)?;
+        }
+        let large = b
+            .checked_add(&d)?
+            .checked_div(&Complex::from(-Decimal::TWO))?;
+        if large == Complex::from(Decimal::ZERO) {
+            return Ok(vec![large, large]);
+        }
+        Ok(vec![large, c.checked_div(&large)?])
+    }
+
+    // Moves guesses for all the roots at once towards them, each away from the others.
+    // The polynomial has 1 as its highest coefficient and its roots at most 2 in size,
+    // so only guesses that come too close together can overflow, and then it starts over
+    fn durand_kerner(&self) -> Result<Vec<Complex>, Error> {
+        let degree = self.degree();
+        let mean = self.coefficients[degree - 1]
+            .to_complex()?
+            .checked_div(&Complex::from(-Decimal::from(degree)))?;
+        for attempt in 0..MAX_ATTEMPTS as i64 {
+            // The guesses go around the mean of the roots, on a circle of another size
+            // and turned by another angle each time
+            let size = Decimal::new(9 - attempt, 1);
+            let turn = Decimal::new(4 + 7 * attempt, 1);
+            let mut roots = (0..degree)
+                .map(|k| {
+                    let angle = Decimal::TWO_PI * Decimal::from(k) / Decimal::from(degree);
+                    mean.checked_add(&Complex::from_polar(size, angle + turn)?)
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            match self.iterate(&mut roots) {
+                Ok(true) => return Ok(roots),
+                Ok(false) | Err(Error::Overflow | Error::DivisionByZero) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Err(Error::NoConvergence)
+    }
+
+    // Runs the iteration from the guesses, and tells whether they came to the roots
+    fn iterate(&self, roots: &mut [Complex]) -> Result<bool, Error> {
+        let nudge = Complex::new(Decimal::ZERO, Decimal::new(1, 10));
+        for _ in 0..MAX_ITERATIONS {
+            let mut largest = Decimal::ZERO;
+            for i in 0..roots.len() {
+                let mut denominator = Complex::from(Decimal::ONE);
+                for (j, other) in roots.iter().enumerate() {
+                    if i != j {
+                        denominator = denominator.checked_mul(&roots[i].checked_sub(other)?)?;
+                    }
+                }
+                if denominator == Complex::from(Decimal::ZERO) {
+                    // Two guesses met, so one moves aside instead of dividing by 0
+                    roots[i] = roots[i].checked_add(&nudge)?;
+                    largest = Decimal::MAX;
+                    continue;
+                }
+                let step = self.eval_complex(&roots[i])?.checked_div(&denominator)?;
+                roots[i] = roots[i].checked_sub(&step)?;
+                largest = largest.max(step.abs()?);
+            }
+            if largest <= TOLERANCE {
+                return Ok(true);
+            }
+        }
+        // Repeated roots converge slowly, but what they come to is still close,
+        // which the polynomial with them as roots shows
+        let mut product = Polynomial::constant(integer(1))?;
+        for root in roots.iter() {
+            let minus = Complex::from(Decimal::ZERO).checked_sub(root)?;
+            product = product.mul(&Polynomial::new(vec![Value::from(minus), integer(1)])?)?;
+        }
+        for k in 0..self.coefficients.len() {
+            let difference = product.coefficient(k).checked_sub(&self.coefficient(k))?;
+            if difference.to_complex()?.abs()? > CLOSENESS {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    // Newton steps on the polynomial itself win back the digits the scaling lost,
+    // each one taken only while it brings the value closer to 0 and stays clear of the other roots
+    fn polish(&self, roots: &mut [Complex]) -> Result<(), Error> {
+        let derivative = self.derivative()?;
+        let residual = |z: &Complex| self.eval_complex(z).and_then(|y| y.abs());
+        for i in 0..roots.len() {
+            for _ in 0..POLISH_STEPS {
+                let z = roots[i];
+                let step = (|| {
+                    let step = self
+                        .eval_complex(&z)?
+                        .checked_div(&derivative.eval_complex(&z)?)?;
+                    let moved = z.checked_sub(&step)?;
+                    let mut nearest = Decimal::MAX;
+                    for (j, other) in roots.iter().enumerate() {
+                        if i != j {
+                            nearest = nearest.min(moved.checked_sub(other)?.abs()?);
+                        }
+                    }
+                    let clear = step.abs()? < nearest / Decimal::TWO;
+                    Ok::<_, Error>((clear && residual(&moved)? < residual(&z)?).then_some(moved))
+                })();
+                match step {
+                    Ok(Some(moved)) => roots[i] = moved,
+                    _ => break,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // The root rounded to the fewest places that leave it as close to a root, so roots that
+    // are exact or only off in the last digits come out exact. The rounding may only move it
+    // as far as its root can be, so it doesn't go over to another root.
+    fn snap(&self, z: Complex) -> Result<Complex, Error> {
+        let residual = |z: &Complex| self.eval_complex(z).and_then(|y| y.abs());
+        let rounded = |places| Complex::new(z.re.round_dp(places), z.im.round_dp(places));
+        // Too large a root to take the value at keeps all its places
+        if let (Ok(closeness), Ok(reach)) = (residual(&z), self.reach(&z)) {
+            for places in 0..PLACES {
+                let close = residual(&rounded(places)).is_ok_and(|r| r <= closeness);
+                let near = rounded(places).checked_sub(&z).and_then(|d| d.abs());
+                if close && near.is_ok_and(|d| d <= reach) {
+                    return Ok(normalize(rounded(places)));
+                }
+            }
+        }
+        Ok(normalize(rounded(PLACES)))
+    }
+
+    // How far the root near `z` can be: the Newton step times the multiplicity at most,
+    // with the value no smaller than its rounding in the sum of the sizes of the terms
+    fn reach(&self, z: &Complex) -> Result<Decimal, Error> {
+        let size = z.abs()?;
+        let terms = self
+            .coefficients
+            .iter()
+            .rev()
+            .try_fold(Decimal::ZERO, |sum, c| {
+                let term = sum.checked_mul(size).ok_or(Error::Overflow)?;
+                term.checked_add(c.to_complex()?.abs()?)
+                    .ok_or(Error::Overflow)
+            })?;
+        let value = self.eval_complex(z)?.abs()?.max(terms * NOISE);
+        let slope = self.derivative()?.eval_complex(z)?.abs()?;
+        if slope.is_zero() {
+            return Ok(Decimal::MAX);
+        }
+        let step = value.checked_div(slope).ok_or(Error::Overflow)?;
+        Ok(step.saturating_mul(Decimal::from(2 * self.degree())))
+    }
+
+    /// The polynomial of the given degree closest to the points by least squares
+    pub fn fit(xs: &[Value], ys: &[Value], degree: usize) -> Result<Polynomial, Error> {
+        if xs.is_empty() {
+            return Err(Error::DomainError);
+        } else if xs.len() != ys.len() {
+            return Err(Error::DimensionMismatch(
+                xs.len().to_string(),
+                ys.len().to_string(),
+            ));
+        }
+        // The powers of each x are a row of the Vandermonde matrix `V`,
+        // the coefficients solve `Vᵀ V c = Vᵀ y`
+        let rows = xs
+            .iter()
+            .map(|x| {
+                let mut powers = vec![integer(1)];
+                for i in 0..degree {
+                    powers.push(powers[i].checked_mul(x)?);
+                }
+                Ok(Value::List(powers))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let v = Value::List(rows);
+        let vt = matrix::transpose(&v)?;
+        let a = matrix::multiply(&vt, &v)?;
+        let b = matrix::multiply(&vt, &Value::List(ys.to_vec()))?;
+        match matrix::solve(&a, &b)? {
+            Value::List(coefficients) => Polynomial::new(coefficients),
+            c => Polynomial::constant(c),
+        }
+    }
+
+    /// Writes the polynomial in `x` the usual way, with the highest power first
+    pub fn format(&self, part: impl Fn(&Value) -> String) -> String {
+        let mut s = String::new();
+        for (power, c) in self.coefficients.iter().enumerate().rev() {
+            if is_zero(c) {
+                continue;
+            }
+            let negative = match c {
+                Value::Real(x) => x.is_sign_negative(),
+                Value::Rational(r) => r < &Zero::zero(),
+                _ => false,
+            };
+            let magnitude = if negative {
+                c.abs().unwrap_or(c.clone())
+            } else {
+                c.clone()
+            };
+            match (s.is_empty(), negative) {
+                (true, true) => s.push('-'),
+                (false, true) => s.push_str(" - "),
+                (false, false) => s.push_str(" + "),
+                (true, false) => {}
+            }
+            let one = matches!(&magnitude, Value::Real(x) if *x == Decimal::ONE)
+                || matches!(&magnitude, Value::Rational(r) if r == &num_traits::One::one());
+            let grouped = match &magnitude {
+                Value::Complex(_) => true,
+                Value::Rational(r) => !r.is_integer(),
+                _ => false,
+            };
+            let coefficient = match (power, one, grouped) {
+                (0, _, _) => part(&magnitude),
+                (_, true, _) => String::new(),
+                (_, false, true) => format!("({})", part(&magnitude)),
+                (_, false, false) => part(&magnitude),
+            };
+            s.push_str(&coefficient);
+            match power {
+                0 => {}
+                1 => s.push('x'),
+                _ => s.push_str(&format!("x^{}", power)),
+            }
+        }
+        if s.is_empty() {
+            s.push('0');
+        }
+        s
+    }
+}
+
+fn normalize(z: Complex) -> Complex {
+    Complex::new(z.re.normalize(), z.im.normalize())
+}
+
+impl Display for Polynomial {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.format(Value::to_string))
+    }
+}
+
+impl From<Polynomial> for Value {
+    fn from(p: Polynomial) -> Self {
+        Value::Polynomial(p)
+    }
+}
+
+/// A value as a polynomial, a list of coefficients from the highest power down is one too
+pub fn to_polynomial(value: &Value) -> Result<Polynomial, Error> {
+    match value {
+        Value::Polynomial(p) => Ok(p.clone()),
+        Value::List(items) => Polynomial::from_highest(items.clone()),
+        x => Polynomial::constant(x.clone()),
+    }
+}
+
+/// Both sides as polynomials when either is one, for the arithmetic of `Value`.
+/// Returns `None` when neither is one.
+pub(crate) fn operands(a: &Value, b: &Value) -> Option<Result<(Polynomial, Polynomial), Error>> {
+    match (a, b) {
+        (Value::Polynomial(_), _) | (_, Value::Polynomial(_)) => {
+            Some(to_polynomial(a).and_then(|a| Ok((a, to_polynomial(b)?))))
+        }
+        _ => None,
+    }
+}
+
+/// The quotient, an error if the division leaves a remainder
+pub(crate) fn divide(a: &Polynomial, b: &Polynomial) -> Result<Polynomial, Error> {
+    let (quotient, remainder) = a.div_rem(b)?;
+    match remainder.coefficients.is_empty() {
+        true => Ok(quotient),
+        false => Err(Error::Remainder(remainder.to_string())),
+    }
+}
+
+/// The power to a whole number exponent
+pub(crate) fn pow(p: &Polynomial, n: &Value) -> Result<Polynomial, Error> {
+    if let Value::Polynomial(_) = n {
+        return Err(Error::ExpectedInteger);
+    }
+    let n = to_integer(n)?.to_u32().ok_or(Error::ExpectedInteger)?;
+    p.pow(n)
+}
+
+// The single polynomial argument of a function, a list is its coefficients
+pub(crate) fn argument(args: Vec<Value>) -> Result<Polynomial, Error> {
+    to_polynomial(&matrix::argument(args)?)
+}
+
+// The arguments of `polyfit(xs, ys, degree)`
+pub(crate) fn fit_arguments(args: Vec<Value>) -> Result<Polynomial, Error> {
+    let [xs, ys, degree] = <[Value; 3]>::try_from(args).map_err(|args| match args.len() {
+        0..=2 => Error::NotEnoughArgs,
+        _ => Error::TooManyArgs,
+    })?;
+    let (Value::List(xs), Value::List(ys)) = (xs, ys) else {
+        return Err(Error::ExpectedList);
+    };
+    let degree = to_integer(&degree)?
+        .to_usize()
+        .ok_or(Error::ExpectedInteger)?;
+    Polynomial::fit(&xs, &ys, degree)
+}
@@ -0,0 +1,318 @@
+use std::{
+    cmp::Ordering,
+    fmt::{Display, Formatter},
+};
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, ToPrimitive};
+use rust_decimal::prelude::*;
+
+use crate::{
+    error::Error,
+    rational,
+    units::{self, Dimension},
+    value::Value,
+};
+
+/// A product of named units with integer exponents, like `kg·m/s^2`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Unit {
+    // The named units in the order they were written, with their exponents
+    parts: Vec<(String, i32)>,
+    /// The size of the unit in SI base units, exactly
+    pub factor: BigRational,
+    pub dimension: Dimension,
+}
+
+impl Unit {
+    /// The unit of plain numbers
+    pub fn none() -> Unit {
+        Unit {
+            parts: Vec::new(),
+            factor: BigRational::one(),
+            dimension: [0; 7],
+        }
+    }
+
+    /// Looks up a unit in the unit database, e.g. `km` or `mph`
+    pub fn named(name: &str) -> Option<Unit> {
+        let def = units::lookup(name)?;
+        Some(Unit {
+            parts: vec![(name.to_string(), 1)],
+            factor: def.factor,
+            dimension: def.dimension,
+        })
+    }
+
+    /// Whether the unit is the one of plain numbers
+    pub fn is_none(&self) -> bool {
+        self.parts.is_empty()
+    }
+
+    // The product of two units, or the quotient when `sign` is -1
+    fn combine(&self, rhs: &Unit, sign: i32) -> Unit {
+        let mut parts = self.parts.clone();
+        for (name, exponent) in &rhs.parts {
+            match parts.iter_mut().find(|(n, _)| n == name) {
+                Some((_, e)) => *e += sign * exponent,
+                None => parts.push((name.clone(), sign * exponent)),
+            }
+        }
+        parts.retain(|(_, e)| *e != 0);
+        let factor = if sign < 0 {
+            &self.factor / &rhs.factor
+        } else {
+            &self.factor * &rhs.factor
+        };
+        let mut dimension = self.dimension;
+        for (d, r) in dimension.iter_mut().zip(rhs.dimension) {
+            *d += sign * r;
+        }
+        Unit {
+            parts,
+            factor,
+            dimension,
+        }
+    }
+
+    // The unit raised to a power, which has to leave every exponent an integer
+    fn pow(&self, exponent: &BigRational) -> Result<Unit, Error> {
+        let scale = |e: i32| {
+            let scaled = exponent * BigInt::from(e);
+            scaled
+                .is_integer()
+                .then(|| scaled.to_integer().to_i32())
+                .flatten()
+                .ok_or(Error::DomainError)
+        };
+        let parts = self
+            .parts
+            .iter()
+            .map(|(name, e)| Ok((name.clone(), scale(*e)?)))
+            .collect::<Result<Vec<_>, Error>>()?;
+        let mut dimension = self.dimension;
+        for d in dimension.iter_mut() {
+            *d = scale(*d)?;
+        }
+        let factor = match rational::checked_pow(&self.factor, exponent) {
+            Some(factor) => factor?,
+            // Roots of factors that are not perfect powers, like `sqrt(ft)`
+            None => {
+                let factor = rational::to_decimal(&self.factor)?;
+                let exponent = rational::to_decimal(exponent)?;
+                let power = factor.checked_powd(exponent).ok_or(Error::Overflow)?;
+                rational::from_decimal(power)
+            }
+        };
+        Ok(Unit {
+            parts,
+            factor,
+            dimension,
+        })
+    }
+}
+
+/// Writes the unit so it can be read back, e.g. `m/s`, `kg·m/s^2`, `J/(kg·K)` or `s^-1`
+impl Display for Unit {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let join = |parts: Vec<(&String, i32)>| {
+            parts
+                .iter()
+                .map(|(name, e)| match e {
+                    1 => name.to_string(),
+                    _ => format!("{}^{}", name, e),
+                })
+                .collect::<Vec<_>>()
+                .join("·")
+        };
+        let numer: Vec<_> = self
+            .parts
+            .iter()
+            .filter(|(_, e)| *e > 0)
+            .map(|(n, e)| (n, *e))
+            .collect();
+        let denom: Vec<_> = self
+            .parts
+            .iter()
+            .filter(|(_, e)| *e < 0)
+            .map(|(n, e)| (n, -e))
+            .collect();
+        if self.is_none() {
+            write!(f, "a plain number")
+        } else if numer.is_empty() {
+            let inverse = denom.into_iter().map(|(n, e)| (n, -e)).collect();
+            write!(f, "{}", join(inverse))
+        } else if denom.is_empty() {
+            write!(f, "{}", join(numer))
+        } else if denom.len() == 1 {
+            write!(f, "{}/{}", join(numer), join(denom))
+        } else {
+            write!(f, "{}/({})", join(numer), join(denom))
+        }
+    }
+}
+
+/// A number with a unit, like `3 m` or `1.5 m/s`.
+/// Units that cancel out leave a plain number, so `5 km / 300 m` is about `16.67`.
+/// ## Example
+/// ```rust
+/// use calc::Context;
+///
+/// let context = Context::new();
+/// assert_eq!(context.eval("3 m / 2 s").unwrap().to_string(), "1.5 m/s");
+/// assert_eq!(context.eval("5 km + 300 m").unwrap().to_string(), "5.3 km");
+/// assert_eq!(context.eval("60 mph to m/s").unwrap().to_string(), "26.8224 m/s");
+/// assert_eq!(context.eval("2 kg * 9.81 m/s^2 in N").unwrap().to_string(), "19.62 N");
+/// assert_eq!(
+///     context.eval("1 m + 1 s").unwrap_err().to_string(),
+///     "Cannot add m and s"
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Quantity {
+    pub value: Decimal,
+    pub unit: Unit,
+}
+
+impl Quantity {
+    /// The value in SI base units, e.g. `1000` for `1 km`
+    pub fn si_value(&self) -> BigRational {
+        rational::from_decimal(self.value) * &self.unit.factor
+    }
+}
+
+impl Display for Quantity {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{} {}", self.value.normalize(), self.unit)
+    }
+}
+
+// A value as a magnitude and a unit, plain numbers have none
+fn split(value: &Value) -> Result<(Decimal, Unit), Error> {
+    match value {
+        Value::Quantity(q) => Ok((q.value, q.unit.clone())),
+        _ => Ok((value.as_real()?, Unit::none())),
+    }
+}
+
+// Builds a value, units that cancel out leave a plain number
+fn quantity(value: Decimal, unit: Unit) -> Result<Value, Error> {
+    if unit.is_none() {
+        Ok(Value::Real(value))
+    } else if unit.dimension == [0; 7] {
+        let value = rational::from_decimal(value) * &unit.factor;
+        rational::to_decimal(&value).map(Value::Real)
+    } else {
+        Ok(Value::Quantity(Quantity { value, unit }))
+    }
+}
+
+// The magnitude of a value with unit `from` in unit `to`, which must have the same dimension
+fn rescale(value: Decimal, from: &Unit, to: &Unit) -> Result<Decimal, Error> {
+    if from.factor == to.factor {
+        return Ok(value);
+    }
+    rational::to_decimal(&(rational::from_decimal(value) * &from.factor / &to.factor))
+}
+
+// Adds or subtracts two values of the same dimension, in the unit of the left one
+pub(crate) fn add(a: &Value, b: &Value, negate: bool) -> Result<Value, Error> {
+    let ((x, u), (y, v)) = (split(a)?, split(b)?);
+    if u.dimension != v.dimension {
+        return Err(Error::IncompatibleUnits(
+            if negate { "subtract" } else { "add" },
+            u.to_string(),
+            v.to_string(),
+        ));
+    }
+    let y = rescale(y, &v, &u)?;
+    let sum = if negate {
+        x.checked_sub(y)
+    } else {
+        x.checked_add(y)
+    };
+    quantity(sum.ok_or(Error::Overflow)?, u)
+}
+
+pub(crate) fn mul(a: &Value, b: &Value) -> Result<Value, Error> {
+    let ((x, u), (y, v)) = (split(a)?, split(b)?);
+    quantity(x.checked_mul(y).ok_or(Error::Overflow)?, u.combine(&v, 1))
+}
+
+pub(crate) fn div(a: &Value, b: &Value) -> Result<Value, Error> {
+    let ((x, u), (y, v)) = (split(a)?, split(b)?);
+    if y.is_zero() {
+        return Err(Error::DivisionByZero);
+    }
+    quantity(x.checked_div(y).ok_or(Error::Overflow)?, u.combine(&v, -1))
+}
+
+/// Raises a quantity to a plain power, `(3 m)^2` is `9 m^2` and `sqrt(4 m^2)` is `2 m`
+pub(crate) fn pow(a: &Value, b: &Value) -> Result<Value, Error> {
+    if let Value::Quantity(q) = b {
+        return Err(Error::UnitsNotAllowed(q.unit.to_string()));
+    }
+    let (x, u) = split(a)?;
+    let exponent = match b {
+        Value::Rational(r) => r.clone(),
+        _ => rational::from_decimal(b.as_real()?),
+    };
+    let unit = u.pow(&exponent)?;
+    let value = Value::Real(x).checked_pow(&Value::Rational(exponent).inexact()?)?;
+    quantity(value.as_real()?, unit)
+}
+
+/// Compares two values of the same dimension
+pub(crate) fn cmp(a: &Value, b: &Value) -> Result<Ordering, Error> {
+    let ((x, u), (y, v)) = (split(a)?, split(b)?);
+    if u.dimension != v.dimension {
+        return Err(Error::IncompatibleUnits(
+            "compare",
+            u.to_string(),
+            v.to_string(),
+        ));
+    }
+    let a = rational::from_decimal(x) * &u.factor;
+    let b = rational::from_decimal(y) * &v.factor;
+    Ok(a.cmp(&b))
+}
+
+/// Writes a value in the unit of another one, `60 mph to m/s`.
+/// The target has to be a unit alone, `1 m to 2 m` is an error.
+pub(crate) fn convert(a: &Value, target: &Value) -> Result<Value, Error> {
+    let ((x, u), (scale, v)) = (split(a)?, split(target)?);
+    if u.dimension != v.dimension {
+        return Err(Error::CannotConvert(u.to_string(), v.to_string()));
+    }
+    // A target with a number, like `2 m`, is not a unit to write the value in
+    if scale != Decimal::ONE {
+        return Err(Error::CannotConvert(u.to_string(), target.to_string()));
+    }
+    let value = rescale(x, &u, &v)?;
+    if v.is_none() {
+        Ok(Value::Real(value))
+    } else {
+        Ok(Value::Quantity(Quantity { value, unit: v }))
+    }
+}
+
+/// The absolute value, keeping the unit
+pub(crate) fn abs(q: &Quantity) -> Value {
+    Value::Quantity(Quantity {
+        value: q.value.abs(),
+        unit: q.unit.clone(),
+    })
+}
+
+/// The square or cube root of a quantity, `sqrt(4 m^2)` is `2 m`
+pub(crate) fn root(q: &Quantity, n: i32) -> Result<Value, Error> {
+    let unit = q
+        .unit
+        .pow(&BigRational::new(BigInt::one(), BigInt::from(n)))?;
+    let value = match n {
+        2 => Value::Real(q.value).sqrt()?,
+        _ => Value::Real(q.value).cbrt()?,
+    };
+    quantity(value.as_real()?, unit)
+}
@@ -0,0 +1,100 @@
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{Pow, Signed, ToPrimitive, Zero};
+use rust_decimal::prelude::*;
+
+use crate::error::Error;
+
+// Largest exponent for exact powers, anything above gives numbers too big to be useful
+const MAX_EXPONENT: u32 = 100_000;
+
+/// Parses a decimal literal like `12.5e-3` exactly, without going through floats
+pub fn parse_exact(s: &str) -> Result<BigRational, Error> {
+    let (mantissa, exponent) = match s.split_once('e') {
+        Some((m, e)) => (m, e.parse::<i32>().map_err(|_| Error::NumberParseError)?),
+        None => (s, 0),
+    };
+    let (int, fract) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = format!("{}{}", int, fract);
+    let numer = digits
+        .parse::<BigInt>()
+        .map_err(|_| Error::NumberParseError)?;
+    let exponent = exponent - fract.len() as i32;
+    let scale = BigInt::from(10).pow(exponent.unsigned_abs());
+    Ok(if exponent < 0 {
+        BigRational::new(numer, scale)
+    } else {
+        BigRational::from_integer(numer * scale)
+    })
+}
+
+/// Converts a `Decimal` to a fraction, exactly
+pub fn from_decimal(d: Decimal) -> BigRational {
+    BigRational::new(BigInt::from(d.mantissa()), BigInt::from(10).pow(d.scale()))
+}
+
+/// Converts a fraction to the closest `Decimal`
+pub fn to_decimal(r: &BigRational) -> Result<Decimal, Error> {
+    let numer = r.numer().to_i128().and_then(Decimal::from_i128);
+    let denom = r.denom().to_i128().and_then(Decimal::from_i128);
+    if let (Some(numer), Some(denom)) = (numer, denom) {
+        return numer.checked_div(denom).ok_or(Error::Overflow);
+    }
+    // Too many digits for Decimal, go through a float instead
+    r.to_f64()
+        .and_then(Decimal::from_f64)
+        .ok_or(Error::Overflow)
+}
+
+/// Raises a fraction to a power, if the result is a fraction as well.
+/// This is the case for integer exponents, and for roots of perfect powers like `(4/9)^(1/2)`.
+pub fn checked_pow(base: &BigRational, exp: &BigRational) -> Option<Result<BigRational, Error>> {
+    let root = exp.denom().to_u32()?;
+    let power = exp.numer().abs().to_u32()?;
+    if power > MAX_EXPONENT {
+        return Some(Err(Error::Overflow));
+    }
+    let mut result = if root == 1 {
+        base.clone()
+    } else {
+        nth_root(base, root)?
+    };
+    result = Pow::pow(&result, power);
+    if exp.is_negative() {
+        if result.is_zero() {
+            return Some(Err(Error::DivisionByZero));
+        }
+        result = result.recip();
+    }
+    Some(Ok(result))
+}
+
+/// The exact `n`th root of a fraction, if both of its parts are perfect powers.
+/// Even roots of negative fractions have no real result and give `None`.
+pub fn nth_root(r: &BigRational, n: u32) -> Option<BigRational> {
+    if r.is_negative() && n.is_multiple_of(2) {
+        return None;
+    }
+    let root = |x: &BigInt| {
+        let y = x.nth_root(n);
+        (Pow::pow(&y, n) == *x).then_some(y)
+    };
+    Some(BigRational::new(root(r.numer())?, root(r.denom())?))
+}
+
+/// Writes a fraction as `a/b`, or as a mixed number `q r/b` if `mixed` is set.
+/// Each integer is written with the `int` function.
+pub fn format_fraction(r: &BigRational, mixed: bool, int: impl Fn(&BigInt) -> String) -> String {
+    if r.is_integer() {
+        return int(r.numer());
+    }
+    let sign = if r.is_negative() { "-" } else { "" };
+    let (numer, denom) = (r.numer().abs(), r.denom().clone());
+    if mixed && numer > denom {
+        let whole = &numer / &denom;
+        let rest = &numer % &denom;
+        format!("{}{} {}/{}", sign, int(&whole), int(&rest), int(&denom))
+    } else {
+        format!("{}{}/{}", sign, int(&numer), int(&denom))
+    }
+}
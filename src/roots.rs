@@ -0,0 +1,356 @@
+//! Numeric solving of equations in one variable, the goal seek of spreadsheets.
+//! A root near a guess is found with Newton's method, and when that fails, by widening
+//! an interval around the guess until the function changes sign and closing in on the root
+//! with Brent's method. All the roots in an interval are found from the sign changes
+//! and the dips towards 0 of the function at evenly spaced points.
+
+use std::collections::HashMap;
+
+use rust_decimal::Decimal;
+
+use crate::{diff, error::Error, expr::Expr, op::BinOp, parse::ParseOptions, value::Value};
+
+const MAX_ITERATIONS: usize = 200;
+// The number of times the interval around a guess is doubled looking for a sign change
+const MAX_EXPANSIONS: usize = 64;
+// The number of pieces an interval is split into looking for roots
+const SAMPLES: u32 = 1000;
+// The decimal places of the roots, and of the roots where the function only touches 0,
+// which are only known to about half the digits
+const PLACES: u32 = 20;
+const TOUCHING_PLACES: u32 = 10;
+const TOLERANCE: Decimal = Decimal::from_parts(1, 0, 0, false, 24);
+// A zero of the function is only trusted when it changes sign there, or is at least this large
+// close to it, smaller values could be rounded to 0 like `exp(-100)`
+const SMALLEST: Decimal = Decimal::from_parts(1, 0, 0, false, 20);
+// The number of times the distance to look at the function next to a zero is multiplied by 10,
+// from 10 times the tolerance up to 10^-4
+const MAX_WIDENINGS: usize = 20;
+
+/// The expression of an equation `lhs = rhs`, which is 0 where the equation holds.
+/// An expression without `=` is an equation with 0 on the right.
+pub fn equation(s: &str, options: &ParseOptions) -> Result<Expr, Error> {
+    let Some((lhs, rhs)) = s.split_once('=') else {
+        return Expr::parse(s, options);
+    };
+    if rhs.contains('=') {
+        return Err(Error::UnexpectedToken);
+    }
+    let (lhs, rhs) = (Expr::parse(lhs, options)?, Expr::parse(rhs, options)?);
+    Ok(Expr::Binary(BinOp::Sub, Box::new(lhs), Box::new(rhs)))
+}
+
+// The expression as a function of one variable, with the other variables bound
+struct Function {
+    expr: Expr,
+    derivative: Option<Expr>,
+    var: String,
+    variables: HashMap<String, Value>,
+}
+
+impl Function {
+    fn new(expr: &Expr, var: &str, variables: &HashMap<String, Value>) -> Function {
+        Function {
+            expr: expr.clone(),
+            derivative: diff::derivative(expr, var).ok(),
+            var: var.to_string(),
+            variables: variables.clone(),
+        }
+    }
+
+    fn eval(&mut self, expr: Option<&Expr>, x: Decimal) -> Result<Decimal, Error> {
+        self.variables.insert(self.var.clone(), Value::Real(x));
+        expr.unwrap_or(&self.expr).eval(&self.variables)?.as_real()
+    }
+
+    fn at(&mut self, x: Decimal) -> Option<Decimal> {
+        self.eval(None, x).ok()
+    }
+
+    // The slope from the derivative, or between two close points for the functions without one
+    fn slope(&mut self, x: Decimal) -> Option<Decimal> {
+        match self.derivative.take() {
+            Some(derivative) => {
+                let slope = self.eval(Some(&derivative), x).ok();
+                self.derivative = Some(derivative);
+                slope
+            }
+            None => {
+                let h = Decimal::new(1, 10) * Decimal::ONE.max(x.abs());
+                let (y1, y2) = (self.at(x)?, self.at(x.checked_add(h)?)?);
+                y2.checked_sub(y1)?.checked_div(h)
+            }
+        }
+    }
+}
+
+// Solving for a variable the expression doesn't have would only ever fail to converge
+fn has_variable(expr: &Expr, var: &str) -> Result<(), Error> {
+    match expr.depends_on(var) {
+        true => Ok(()),
+        false => Err(Error::MissingVariable(var.to_string())),
+    }
+}
+
+fn tolerance(x: Decimal) -> Decimal {
+    TOLERANCE * Decimal::ONE.max(x.abs())
+}
+
+fn changes_sign(a: Decimal, b: Decimal) -> bool {
+    a.is_zero() || b.is_zero() || (a < Decimal::ZERO) != (b < Decimal::ZERO)
+}
+
+// Whether a point Newton's or Brent's method stopped at is a root.
+// A value that is not 0 has to be much smaller than the values next to it, not a jump like
+// the one of `floor(x) - 0.5`. A value of 0 has to be one, not the rounding of a small value.
+fn is_root(f: &mut Function, x: Decimal) -> bool {
+    let Some(y) = f.at(x) else {
+        return false;
+    };
+    let mut h = tolerance(x) * Decimal::TEN;
+    for _ in 0..MAX_WIDENINGS {
+        let near = |f: &mut Function, x: Option<Decimal>| x.and_then(|x| f.at(x));
+        let (left, right) = (near(f, x.checked_sub(h)), near(f, x.checked_add(h)));
+        let values: Vec<Decimal> = [left, right].into_iter().flatten().collect();
+        if values.is_empty() {
+            return false;
+        }
+        if !y.is_zero() {
+            return values.iter().all(|v| v.abs() >= y.abs() * Decimal::TWO);
+        }
+        let sign_change = matches!((left, right), (Some(l), Some(r)) if l * r < Decimal::ZERO);
+        if sign_change || values.iter().any(|v| v.abs() >= SMALLEST) {
+            return true;
+        }
+        h *= Decimal::TEN;
+    }
+    false
+}
+
+/// A root of the expression in the variable near `guess`, with values for the other variables.
+/// It is an error if no root is found, or if the expression doesn't have the variable.
+/// ## Example
+/// ```rust
+/// use calc::{roots::{equation, root}, ParseOptions};
+/// use rust_decimal::Decimal;
+/// use std::collections::HashMap;
+///
+/// let options = ParseOptions::default();
+/// let root = |s: &str, guess: i64| {
+///     root(&equation(s, &options).unwrap(), "x", Decimal::from(guess), &HashMap::new())
+///         .map(|x| x.to_string())
+/// };
+/// assert_eq!(root("x^2 = 2", 1).unwrap(), "1.4142135623730950488");
+/// assert_eq!(root("x^2 = 2", -1).unwrap(), "-1.4142135623730950488");
+/// assert_eq!(root("cos(x) = x", 0).unwrap(), "0.73908513321516064166");
+/// assert_eq!(root("x^2 = -1", 0).unwrap_err().to_string(), "The solver did not converge");
+/// ```
+pub fn root(
+    expr: &Expr,
+    var: &str,
+    guess: Decimal,
+    variables: &HashMap<String, Value>,
+) -> Result<Decimal, Error> {
+    has_variable(expr, var)?;
+    let mut f = Function::new(expr, var, variables);
+    if let Some(x) = newton(&mut f, guess) {
+        return Ok(x.round_dp(PLACES).normalize());
+    }
+    let (a, b) = bracket(&mut f, guess).ok_or(Error::NoConvergence)?;
+    let x = brent(|x| f.at(x), a, b)?;
+    match is_root(&mut f, x) {
+        true => Ok(x.round_dp(PLACES).normalize()),
+        false => Err(Error::NoConvergence),
+    }
+}
+
+/// All the roots of the expression in the variable between `from` and `to`, in order.
+/// Roots closer together than a thousandth of the interval may be found as one.
+/// ## Example
+/// ```rust
+/// use calc::{roots::{equation, roots}, ParseOptions};
+/// use rust_decimal::Decimal;
+/// use std::collections::HashMap;
+///
+/// let expr = equation("x^3 - x = 0", &ParseOptions::default()).unwrap();
+/// let roots = roots(&expr, "x", Decimal::from(-5), Decimal::from(5), &HashMap::new()).unwrap();
+/// assert_eq!(roots, [Decimal::from(-1), Decimal::ZERO, Decimal::ONE]);
+/// ```
+pub fn roots(
+    expr: &Expr,
+    var: &str,
+    from: Decimal,
+    to: Decimal,
+    variables: &HashMap<String, Value>,
+) -> Result<Vec<Decimal>, Error> {
+    has_variable(expr, var)?;
+    let (from, to) = (from.min(to), from.max(to));
+    let step = to.checked_sub(from).ok_or(Error::Overflow)? / Decimal::from(SAMPLES);
+    let mut f = Function::new(expr, var, variables);
+    let points = (0..=SAMPLES)
+        .map(|i| {
+            let x = if i == SAMPLES {
+                to
+            } else {
+                step.checked_mul(Decimal::from(i))
+                    .and_then(|offset| from.checked_add(offset))
+                    .ok_or(Error::Overflow)?
+            };
+            Ok((x, f.at(x)))
+        })
+        .collect::<Result<Vec<(Decimal, Option<Decimal>)>, Error>>()?;
+    let values: Vec<Decimal> = points.iter().filter_map(|(_, y)| *y).collect();
+    if !values.is_empty() && values.iter().all(|y| y.is_zero()) {
+        return Err(Error::Identity);
+    }
+    let mut roots = Vec::new();
+    for (x, y) in &points {
+        if y.is_some_and(|y| y.is_zero()) && is_root(&mut f, *x) {
+            roots.push(x.normalize());
+        }
+    }
+    for pair in points.windows(2) {
+        let [(a, Some(fa)), (b, Some(fb))] = pair else {
+            continue;
+        };
+        if !fa.is_zero() && !fb.is_zero() && changes_sign(*fa, *fb) {
+            if let Some(x) = brent(|x| f.at(x), *a, *b)
+                .ok()
+                .filter(|x| is_root(&mut f, *x))
+            {
+                roots.push(x.round_dp(PLACES).normalize());
+            }
+        }
+    }
+    // The function can touch 0 without changing sign, like `x^2`
+    for triple in points.windows(3) {
+        let [(a, Some(fa)), (x, Some(fx)), (b, Some(fb))] = triple else {
+            continue;
+        };
+        let dips = fx.abs() < fa.abs() && fx.abs() < fb.abs();
+        if dips && !changes_sign(*fa, *fx) && !changes_sign(*fx, *fb) {
+            if let Some(x) = newton(&mut f, *x).filter(|x| a <= x && x <= b) {
+                roots.push(x.round_dp(TOUCHING_PLACES).normalize());
+            }
+        }
+    }
+    roots.sort();
+    roots.dedup_by(|b, a| (*b - *a).abs() <= step / Decimal::TWO);
+    Ok(roots)
+}
+
+// Newton's method from a guess, if it converges
+fn newton(f: &mut Function, guess: Decimal) -> Option<Decimal> {
+    let mut x = guess;
+    for _ in 0..MAX_ITERATIONS {
+        let y = f.at(x)?;
+        if y.is_zero() {
+            return is_root(f, x).then_some(x);
+        }
+        let step = y.checked_div(f.slope(x)?)?;
+        x = x.checked_sub(step)?;
+        if step.abs() <= tolerance(x) {
+            return is_root(f, x).then_some(x);
+        }
+    }
+    None
+}
+
+// An interval around the guess where the function changes sign, doubling it until there is one
+fn bracket(f: &mut Function, guess: Decimal) -> Option<(Decimal, Decimal)> {
+    let y = f.at(guess);
+    let (mut right, mut left) = ((guess, y), (guess, y));
+    let mut h = Decimal::ONE.max(guess.abs()) / Decimal::TEN;
+    for _ in 0..MAX_EXPANSIONS {
+        for (side, x) in [
+            (&mut right, guess.checked_add(h)?),
+            (&mut left, guess.checked_sub(h)?),
+        ] {
+            let y = f.at(x);
+            if let (Some(a), Some(b)) = (side.1, y) {
+                if changes_sign(a, b) {
+                    return Some((side.0, x));
+                }
+            }
+            *side = (x, y);
+        }
+        h = h.checked_mul(Decimal::TWO)?;
+    }
+    None
+}
+
+// Brent's method on an interval where the function changes sign.
+// Poles change sign too, so the result has to be closer to 0 than the ends.
+pub(crate) fn brent(
+    mut f: impl FnMut(Decimal) -> Option<Decimal>,
+    mut a: Decimal,
+    mut b: Decimal,
+) -> Result<Decimal, Error> {
+    let mut value = |x| f(x).ok_or(Error::NoConvergence);
+    let (mut fa, mut fb) = (value(a)?, value(b)?);
+    let bound = fa.abs().min(fb.abs());
+    if fa.abs() < fb.abs() {
+        (a, b, fa, fb) = (b, a, fb, fa);
+    }
+    let (mut c, mut fc, mut d) = (a, fa, a);
+    let mut bisected = true;
+    for _ in 0..MAX_ITERATIONS {
+        let width = b.checked_sub(a).ok_or(Error::Overflow)?;
+        if fb.is_zero() || width.abs() <= tolerance(b) {
+            return match fb.abs() <= bound {
+                true => Ok(b),
+                false => Err(Error::NoConvergence),
+            };
+        }
+        // Inverse quadratic interpolation through the last three points, or the secant line
+        let interpolated = || -> Option<Decimal> {
+            let term = |x: Decimal, fx: Decimal, fy: Decimal, fz: Decimal| {
+                let p = fy.checked_div(fx.checked_sub(fy)?)?;
+                let q = fz.checked_div(fx.checked_sub(fz)?)?;
+                x.checked_mul(p)?.checked_mul(q)
+            };
+            if fa != fc && fb != fc {
+                term(a, fa, fb, fc)?
+                    .checked_add(term(b, fb, fa, fc)?)?
+                    .checked_add(term(c, fc, fa, fb)?)
+            } else {
+                b.checked_sub(fb.checked_div(fb.checked_sub(fa)?)?.checked_mul(width)?)
+            }
+        };
+        // The width is known to fit, so a quarter and a half of it from `a` do too
+        let quarter = a + width / Decimal::from(4);
+        let midpoint = a + width / Decimal::TWO;
+        let tol = tolerance(b);
+        // Interpolation is used only when it moves at least half as far as the step before
+        let accepted = |s: Decimal| -> Option<bool> {
+            let moved = s.checked_sub(b)?.abs().checked_mul(Decimal::TWO)?;
+            let (last, before) = (b.checked_sub(c)?.abs(), c.checked_sub(d)?.abs());
+            let slow = match bisected {
+                true => moved >= last || last < tol,
+                false => moved >= before || before < tol,
+            };
+            Some(quarter.min(b) < s && s < quarter.max(b) && !slow)
+        };
+        let s = match interpolated().filter(|s| accepted(*s) == Some(true)) {
+            Some(s) => {
+                bisected = false;
+                s
+            }
+            None => {
+                bisected = true;
+                midpoint
+            }
+        };
+        let fs = value(s)?;
+        (d, c, fc) = (c, b, fb);
+        if changes_sign(fa, fs) {
+            (b, fb) = (s, fs);
+        } else {
+            (a, fa) = (s, fs);
+        }
+        if fa.abs() < fb.abs() {
+            (a, b, fa, fb) = (b, a, fb, fa);
+        }
+    }
+    Err(Error::NoConvergence)
+}
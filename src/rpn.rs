@@ -1,28 +1,40 @@
 use std::collections::VecDeque;
 
-use crate::{error::Error, op::BinOp, token::Token};
+use crate::{
+    error::Error,
+    op::BinOp,
+    token::{Function, Token},
+};
 
 /// Shunting-yard algorithm for converting infix to postfix
-/// Check https://en.wikipedia.org/wiki/Shunting-yard_algorithm for details
+/// Check https://en.wikipedia.org/wiki/Shunting-yard_algorithm for details.
+/// The arguments of every call are counted, variadic functions keep the count in the output.
 /// ## Examples
 /// ```
 /// use calc::{token::Token, rpn::shunting_yard, op::OPERATORS};
 /// use rust_decimal::prelude::*;
-/// let infix = vec![Token::Number(Decimal::from_f64(1.0).unwrap()), Token::Operator(*OPERATORS.get(&'+').unwrap()), Token::Number(Decimal::from_f64(2.0).unwrap())];
+/// let infix: Vec<Token> = vec![Token::Number(Decimal::from_f64(1.0).unwrap().into()), Token::Operator(*OPERATORS.get(&'+').unwrap()), Token::Number(Decimal::from_f64(2.0).unwrap().into())];
 /// let postfix = shunting_yard(infix).unwrap();
-/// assert_eq!(postfix, vec![Token::Number(Decimal::from_f64(1.0).unwrap()), Token::Number(Decimal::from_f64(2.0).unwrap()), Token::Operator(*OPERATORS.get(&'+').unwrap())]);
+/// assert_eq!(postfix, vec![Token::Number(Decimal::from_f64(1.0).unwrap().into()), Token::Number(Decimal::from_f64(2.0).unwrap().into()), Token::Operator(*OPERATORS.get(&'+').unwrap())]);
 /// ```
-pub fn shunting_yard(tokens: Vec<Token>) -> Result<Vec<Token>, Error> {
+pub fn shunting_yard<N: PartialEq>(tokens: Vec<Token<N>>) -> Result<Vec<Token<N>>, Error> {
     let mut output = Vec::with_capacity(tokens.len());
     let mut operator_stack = VecDeque::new();
+    // The number of arguments so far in each open parenthesis
+    let mut arguments: Vec<usize> = Vec::new();
+    let mut previous_left = false;
     for token in tokens {
+        let left = token == Token::ParLeft;
         match token {
             // If the token is a number, then add it to the output queue.
             Token::Number(n) => output.push(Token::Number(n)),
             // If the token is a function, then push it onto the operation stack.
             Token::Function(_) => operator_stack.push_back(token),
             // If the token is a left parenthesis, then push it onto the operation stack.
-            Token::ParLeft => operator_stack.push_back(token),
+            Token::ParLeft => {
+                arguments.push(1);
+                operator_stack.push_back(token)
+            }
             Token::Operator(op1) => {
                 while !operator_stack.is_empty() {
                     let token = operator_stack.back().unwrap();
@@ -45,12 +57,23 @@ pub fn shunting_yard(tokens: Vec<Token>) -> Result<Vec<Token>, Error> {
             }
             // If the token is a right parenthesis, then
             Token::ParRight => {
+                // Empty parentheses have no arguments, `count()`
+                let count = arguments.pop().filter(|_| !previous_left).unwrap_or(0);
                 let mut ok = false;
                 // While the operator token at the top of the stack is not a left parenthesis,
                 // pop operators off the stack onto the output queue.
                 while let Some(token) = operator_stack.pop_back() {
                     if token == Token::ParLeft {
                         ok = true;
+                        // A function before the parenthesis applies to its contents
+                        match operator_stack.pop_back() {
+                            Some(Token::Function(Function::Variadic(f, _))) => {
+                                output.push(Token::Function(Function::Variadic(f, count)))
+                            }
+                            Some(Token::Function(f)) => output.push(Token::Function(f)),
+                            Some(token) => operator_stack.push_back(token),
+                            None => {}
+                        }
                         break;
                     } else {
                         output.push(token);
@@ -61,9 +84,20 @@ pub fn shunting_yard(tokens: Vec<Token>) -> Result<Vec<Token>, Error> {
                     return Err(Error::UnbalancedParens);
                 }
             }
-            // If the token is a comma, discard it
-            Token::Comma => (),
+            // If the token is a comma, finish the current argument and discard it
+            Token::Comma => {
+                if let Some(count) = arguments.last_mut() {
+                    *count += 1;
+                }
+                while let Some(token) = operator_stack.back() {
+                    if token == &Token::ParLeft {
+                        break;
+                    }
+                    output.push(operator_stack.pop_back().unwrap());
+                }
+            }
         }
+        previous_left = left;
     }
     while let Some(token) = operator_stack.pop_back() {
         if token == Token::ParLeft {
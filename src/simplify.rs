@@ -0,0 +1,361 @@
+//! Algebraic simplification of expression trees.
+//! An expression is brought to a sum of terms, each a number times a product of powers,
+//! which collects like terms and combines powers of the same base. Sums are only
+//! multiplied out by numbers, so `x * (x + 1)` stays factored.
+
+use std::{cmp::Ordering, collections::HashMap};
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Signed, ToPrimitive, Zero};
+
+use crate::{
+    expr::{add, call, div, mul, number, pow, undefined, Expr},
+    op::BinOp,
+    rational,
+    value::Value,
+};
+
+// Most decimal places of a folded function value, more are the rounding of an irrational one
+const EXACT_PLACES: u32 = 10;
+
+/// Simplifies an expression: numbers are folded, `x * 1` and `x + 0` disappear,
+/// like terms are collected, powers of the same base are combined
+/// and common factors of fractions cancel.
+/// ## Example
+/// ```rust
+/// use calc::{expr::Expr, simplify::simplify, ParseOptions};
+///
+/// let s = |s: &str| simplify(&Expr::parse(s, &ParseOptions::default()).unwrap()).to_string();
+/// assert_eq!(s("x * x * 1 + 0"), "x^2");
+/// assert_eq!(s("3x + 2y - x + 4 * 5"), "2 * x + 2 * y + 20");
+/// assert_eq!(s("6 * x^3 * y / (4 * x * y^2)"), "1.5 * x^2 / y");
+/// assert_eq!(s("(x + 1) * (x + 1) / (x + 1)"), "x + 1");
+/// assert_eq!(s("sin(0) + sqrt(16) * a"), "4 * a");
+/// ```
+pub fn simplify(expr: &Expr) -> Expr {
+    let simplifier = Simplifier {
+        decimal: has_decimals(expr),
+    };
+    simplifier.simplify(expr)
+}
+
+// Whether the numbers of the expression are decimals, which the results are written as too
+fn has_decimals(expr: &Expr) -> bool {
+    match expr {
+        Expr::Number(x) => matches!(x, Value::Real(_)),
+        Expr::Binary(_, a, b) => has_decimals(a) || has_decimals(b),
+        Expr::Call(_, args) => args.iter().any(has_decimals),
+        Expr::Constant(_) | Expr::Variable(_) => false,
+    }
+}
+
+// A product of powers with a number in front, `3 * x^2 * y`
+#[derive(Clone, Debug)]
+struct Term {
+    coefficient: BigRational,
+    factors: Vec<(Expr, BigRational)>,
+}
+
+impl Term {
+    fn number(coefficient: BigRational) -> Term {
+        Term {
+            coefficient,
+            factors: Vec::new(),
+        }
+    }
+
+    fn factor(base: Expr, exponent: BigRational) -> Term {
+        Term {
+            coefficient: BigRational::one(),
+            factors: vec![(base, exponent)],
+        }
+    }
+
+    fn times(&self, other: &Term) -> Term {
+        let mut term = Term {
+            coefficient: &self.coefficient * &other.coefficient,
+            factors: self.factors.clone(),
+        };
+        for (base, exponent) in &other.factors {
+            match term.factors.iter_mut().find(|(b, _)| b == base) {
+                Some((_, e)) => *e += exponent,
+                None => term.factors.push((base.clone(), exponent.clone())),
+            }
+        }
+        term.factors.retain(|(_, e)| !e.is_zero());
+        term
+    }
+
+    // The term to an integer power, if the coefficient has it
+    fn powi(&self, n: &BigInt) -> Option<Term> {
+        let n = n.to_i32()?;
+        if self.coefficient.is_zero() && n < 0 {
+            return None;
+        }
+        Some(Term {
+            coefficient: self.coefficient.pow(n),
+            factors: self
+                .factors
+                .iter()
+                .map(|(base, e)| (base.clone(), e * BigInt::from(n)))
+                .collect(),
+        })
+    }
+
+    // The sum of the exponents of the variable factors, to write the highest powers first
+    fn degree(&self) -> BigRational {
+        self.factors
+            .iter()
+            .filter(|(base, _)| !matches!(base, Expr::Constant(_) | Expr::Number(_)))
+            .map(|(_, e)| e.clone())
+            .sum()
+    }
+}
+
+// The order of the factors of a product, constants then variables then everything else
+fn factor_order(a: &(Expr, BigRational), b: &(Expr, BigRational)) -> Ordering {
+    let rank = |e: &Expr| match e {
+        Expr::Number(_) | Expr::Constant(_) => 0,
+        Expr::Variable(_) => 1,
+        Expr::Call(..) => 2,
+        Expr::Binary(..) => 3,
+    };
+    (rank(&a.0), a.0.to_string()).cmp(&(rank(&b.0), b.0.to_string()))
+}
+
+fn rational(x: &Value) -> Option<BigRational> {
+    match x {
+        Value::Real(x) => Some(rational::from_decimal(*x)),
+        Value::Rational(r) => Some(r.clone()),
+        _ => None,
+    }
+}
+
+// Whether a computed value is exact, rather than the rounding of an irrational number
+fn is_exact(x: &Value) -> bool {
+    match x {
+        Value::Real(x) => x.normalize().scale() <= EXACT_PLACES,
+        Value::Rational(_) => true,
+        _ => false,
+    }
+}
+
+struct Simplifier {
+    decimal: bool,
+}
+
+impl Simplifier {
+    fn simplify(&self, expr: &Expr) -> Expr {
+        self.rebuild(self.sum(expr))
+    }
+
+    // The expression as a sum of collected terms
+    fn sum(&self, expr: &Expr) -> Vec<Term> {
+        match expr {
+            Expr::Number(x) => match rational(x) {
+                Some(r) => collect(vec![Term::number(r)]),
+                None => vec![Term::factor(expr.clone(), BigRational::one())],
+            },
+            Expr::Constant(_) | Expr::Variable(_) => {
+                vec![Term::factor(expr.clone(), BigRational::one())]
+            }
+            Expr::Binary(BinOp::Add, a, b) => collect([self.sum(a), self.sum(b)].concat()),
+            Expr::Binary(BinOp::Sub, a, b) => {
+                let negated = scale(self.sum(b), &-BigRational::one());
+                collect([self.sum(a), negated].concat())
+            }
+            Expr::Binary(BinOp::Mul, a, b) => self.product(self.sum(a), self.sum(b)),
+            Expr::Binary(BinOp::Div, a, b) => {
+                let (a, b) = (self.sum(a), self.sum(b));
+                match self.term(b).powi(&-BigInt::one()) {
+                    Some(inverse) => self.product(a, vec![inverse]),
+                    // Division by zero, left for the evaluation to report
+                    None => vec![Term::factor(
+                        div(self.rebuild(a), number(0)),
+                        BigRational::one(),
+                    )],
+                }
+            }
+            Expr::Binary(BinOp::Pow, a, b) => self.power(a, &self.simplify(b)),
+            Expr::Binary(op, a, b) => {
+                let (a, b) = (self.simplify(a), self.simplify(b));
+                let expr = Expr::Binary(*op, Box::new(a), Box::new(b));
+                vec![Term::factor(expr, BigRational::one())]
+            }
+            Expr::Call(name, args) => self.call(name, args),
+        }
+    }
+
+    // A sum as a single term, sums of several terms become a factor
+    fn term(&self, sum: Vec<Term>) -> Term {
+        match sum.len() {
+            0 => Term::number(BigRational::zero()),
+            1 => sum.into_iter().next().unwrap(),
+            _ => Term::factor(self.rebuild(sum), BigRational::one()),
+        }
+    }
+
+    // Multiplies two sums, multiplying out only by plain numbers
+    fn product(&self, a: Vec<Term>, b: Vec<Term>) -> Vec<Term> {
+        let number = |sum: &[Term]| match sum {
+            [] => Some(BigRational::zero()),
+            [term] if term.factors.is_empty() => Some(term.coefficient.clone()),
+            _ => None,
+        };
+        // `0 * x` is only 0 where `x` has a value, `0 * (1 / 0)` is left for the evaluation
+        let defined = |sum: &[Term]| {
+            !sum.iter()
+                .any(|t| t.factors.iter().any(|(e, _)| undefined(e)))
+        };
+        match (number(&a), number(&b)) {
+            (Some(n), _) if !n.is_zero() || defined(&b) => return collect(scale(b, &n)),
+            (_, Some(n)) if !n.is_zero() || defined(&a) => return collect(scale(a, &n)),
+            (Some(_), _) | (_, Some(_)) => {
+                let product = mul(self.rebuild(a), self.rebuild(b));
+                return vec![Term::factor(product, BigRational::one())];
+            }
+            _ => {}
+        }
+        collect(vec![self.term(a).times(&self.term(b))])
+    }
+
+    fn power(&self, base: &Expr, exponent: &Expr) -> Vec<Term> {
+        let n = match exponent {
+            Expr::Number(n) => rational(n),
+            _ => None,
+        };
+        match n {
+            Some(n) if n.is_integer() => {
+                let sum = self.sum(base);
+                match self.term(sum.clone()).powi(n.numer()) {
+                    Some(term) if sum.len() <= 1 => collect(vec![term]),
+                    _ => vec![Term::factor(self.rebuild(sum), n)],
+                }
+            }
+            Some(n) => {
+                // Roots of numbers are folded when they are exact, `4^(1/2)` is 2
+                let base = self.simplify(base);
+                if let Expr::Number(x) = &base {
+                    let root = x.checked_pow(&Value::Rational(n.clone()));
+                    if let Some(root) = root.ok().filter(is_exact).and_then(|r| rational(&r)) {
+                        return collect(vec![Term::number(root)]);
+                    }
+                }
+                vec![Term::factor(base, n)]
+            }
+            None => {
+                let power = pow(self.simplify(base), exponent.clone());
+                vec![Term::factor(power, BigRational::one())]
+            }
+        }
+    }
+
+    // Calls of numbers are folded when the result is exact, like `sqrt(16)` but not `sqrt(2)`
+    fn call(&self, name: &str, args: &[Expr]) -> Vec<Term> {
+        let args: Vec<Expr> = args.iter().map(|arg| self.simplify(arg)).collect();
+        let call = call(name, args);
+        let Expr::Call(_, args) = &call else {
+            unreachable!()
+        };
+        if args.iter().all(|arg| matches!(arg, Expr::Number(_))) {
+            if let Some(r) = call
+                .eval(&HashMap::new())
+                .ok()
+                .filter(is_exact)
+                .and_then(|x| rational(&x))
+            {
+                return collect(vec![Term::number(r)]);
+            }
+        }
+        match (name, &args[..]) {
+            ("ln", [Expr::Constant(e)]) if e == "e" => {
+                collect(vec![Term::number(BigRational::one())])
+            }
+            _ => vec![Term::factor(call, BigRational::one())],
+        }
+    }
+
+    // A number of the coefficients, as a decimal for expressions written with decimals
+    fn number(&self, r: BigRational) -> Expr {
+        if r.is_integer() {
+            return Expr::Number(Value::Rational(r));
+        }
+        match rational::to_decimal(&r) {
+            Ok(x) if self.decimal && rational::from_decimal(x) == r => {
+                Expr::Number(Value::Real(x.normalize()))
+            }
+            _ => Expr::Number(Value::Rational(r)),
+        }
+    }
+
+    // The term written out, with the negative powers in a denominator
+    fn term_expr(&self, mut term: Term) -> Expr {
+        term.factors.sort_by(factor_order);
+        let (mut numerator, mut denominator) = (number(1), number(1));
+        for (base, e) in term.factors {
+            let power = |e: BigRational| match e.is_one() {
+                true => base.clone(),
+                false => pow(base.clone(), self.number(e)),
+            };
+            if e.is_positive() {
+                numerator = mul(numerator, power(e));
+            } else {
+                denominator = mul(denominator, power(-e));
+            }
+        }
+        let c = term.coefficient;
+        if c.is_integer() || matches!(self.number(c.clone()), Expr::Number(Value::Real(_))) {
+            div(mul(self.number(c), numerator), denominator)
+        } else {
+            let (p, q) = (
+                BigRational::from(c.numer().clone()),
+                BigRational::from(c.denom().clone()),
+            );
+            div(
+                mul(self.number(p), numerator),
+                mul(self.number(q), denominator),
+            )
+        }
+    }
+
+    fn rebuild(&self, mut sum: Vec<Term>) -> Expr {
+        // The highest powers first, then in the order of their factors
+        sum.sort_by_cached_key(|term| {
+            let mut factors = term.factors.clone();
+            factors.sort_by(factor_order);
+            let names: Vec<String> = factors
+                .iter()
+                .map(|(b, e)| format!("{}^{}", b, e))
+                .collect();
+            (std::cmp::Reverse(term.degree()), names)
+        });
+        sum.into_iter()
+            .map(|term| self.term_expr(term))
+            .reduce(add)
+            .unwrap_or_else(|| number(0))
+    }
+}
+
+fn scale(sum: Vec<Term>, n: &BigRational) -> Vec<Term> {
+    sum.into_iter()
+        .map(|term| Term {
+            coefficient: term.coefficient * n,
+            factors: term.factors,
+        })
+        .collect()
+}
+
+// Adds up the terms with the same factors and drops the ones that are 0
+fn collect(terms: Vec<Term>) -> Vec<Term> {
+    let mut sum: Vec<Term> = Vec::new();
+    for mut term in terms {
+        term.factors.sort_by(factor_order);
+        match sum.iter_mut().find(|t| t.factors == term.factors) {
+            Some(t) => t.coefficient += term.coefficient,
+            None => sum.push(term),
+        }
+    }
+    sum.retain(|term| !term.coefficient.is_zero());
+    sum
+}
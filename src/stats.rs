@@ -0,0 +1,222 @@
+use rust_decimal::{prelude::*, MathematicalOps};
+
+use crate::{error::Error, list, value::Value};
+
+/// Applies a function of `Decimal`s, like a statistic, to the arguments of a call,
+/// which have to be plain real numbers or lists of them
+pub(crate) fn apply(
+    args: Vec<Value>,
+    f: impl Fn(&[Decimal]) -> Result<Decimal, Error>,
+) -> Result<Value, Error> {
+    let values = list::elements(args)
+        .iter()
+        .map(Value::as_real)
+        .collect::<Result<Vec<_>, Error>>()?;
+    f(&values).map(|x| Value::Real(x.normalize()))
+}
+
+fn non_empty(values: &[Decimal]) -> Result<(), Error> {
+    if values.is_empty() {
+        Err(Error::NotEnoughArgs)
+    } else {
+        Ok(())
+    }
+}
+
+fn sum(values: &[Decimal]) -> Result<Decimal, Error> {
+    values.iter().try_fold(Decimal::ZERO, |sum, x| {
+        sum.checked_add(*x).ok_or(Error::Overflow)
+    })
+}
+
+fn sorted(values: &[Decimal]) -> Vec<Decimal> {
+    let mut sorted = values.to_vec();
+    sorted.sort();
+    sorted
+}
+
+/// The number of values
+pub fn count(values: &[Decimal]) -> Result<Decimal, Error> {
+    Ok(Decimal::from(values.len()))
+}
+
+/// The arithmetic mean.
+/// Like every statistic it is computed with `Decimal`, so `mean(0.1, 0.2)` is exactly `0.15`.
+/// ## Example
+/// ```rust
+/// use calc::Context;
+///
+/// let context = Context::new();
+/// assert_eq!(context.eval("mean(1, 2, 3, 4)").unwrap().to_string(), "2.5");
+/// assert_eq!(context.eval("median(3, 1, 2)").unwrap().to_string(), "2");
+/// assert_eq!(context.eval("percentile(50, 1, 2, 3, 4)").unwrap().to_string(), "2.5");
+/// assert_eq!(context.eval("mean()").unwrap_err().to_string(), "Not enough arguments");
+/// ```
+pub fn mean(values: &[Decimal]) -> Result<Decimal, Error> {
+    non_empty(values)?;
+    let n = Decimal::from(values.len());
+    match sum(values) {
+        Ok(sum) => sum.checked_div(n).ok_or(Error::Overflow),
+        // Values near the limit of `Decimal` whose sum is too large, divided first
+        Err(_) => sum(&values.iter().map(|x| x / n).collect::<Vec<_>>()),
+    }
+}
+
+/// The middle value, or the mean of the two middle ones
+pub fn median(values: &[Decimal]) -> Result<Decimal, Error> {
+    percentile(Decimal::from(50), values)
+}
+
+/// The most common value, the first one of them when there are several
+pub fn mode(values: &[Decimal]) -> Result<Decimal, Error> {
+    non_empty(values)?;
+    let frequency = |x: &Decimal| values.iter().filter(|y| *y == x).count();
+    let mut mode = values[0];
+    let mut most = frequency(&mode);
+    for x in values {
+        let count = frequency(x);
+        if count > most {
+            mode = *x;
+            most = count;
+        }
+    }
+    Ok(mode)
+}
+
+// The sum of the squared deviations from the mean, divided by the number of values less `ddof`
+fn squared_deviations(values: &[Decimal], ddof: usize) -> Result<Decimal, Error> {
+    if values.len() <= ddof {
+        return Err(Error::NotEnoughArgs);
+    }
+    let mean = mean(values)?;
+    let squares = values
+        .iter()
+        .map(|x| {
+            let deviation = x.checked_sub(mean).ok_or(Error::Overflow)?;
+            deviation.checked_mul(deviation).ok_or(Error::Overflow)
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    sum(&squares)?
+        .checked_div(Decimal::from(values.len() - ddof))
+        .ok_or(Error::Overflow)
+}
+
+/// The sample variance, which needs at least two values
+pub fn variance(values: &[Decimal]) -> Result<Decimal, Error> {
+    squared_deviations(values, 1)
+}
+
+/// The population variance
+pub fn pvariance(values: &[Decimal]) -> Result<Decimal, Error> {
+    squared_deviations(values, 0)
+}
+
+/// The sample standard deviation, which needs at least two values
+/// ## Example
+/// ```rust
+/// use calc::Context;
+///
+/// let context = Context::new();
+/// assert_eq!(context.eval("pstdev(2, 4, 4, 4, 5, 5, 7, 9)").unwrap().to_string(), "2");
+/// assert_eq!(context.eval("variance(2, 4, 4, 4, 5, 5, 7, 9)").unwrap().to_string(), "4.5714285714285714285714285714");
+/// ```
+pub fn stdev(values: &[Decimal]) -> Result<Decimal, Error> {
+    variance(values)?.sqrt().ok_or(Error::DomainError)
+}
+
+/// The population standard deviation
+pub fn pstdev(values: &[Decimal]) -> Result<Decimal, Error> {
+    pvariance(values)?.sqrt().ok_or(Error::DomainError)
+}
+
+/// The value below which `p` percent of the values are, interpolating linearly between them.
+/// `p` goes from 0 for the smallest value to 100 for the largest.
+pub fn percentile(p: Decimal, values: &[Decimal]) -> Result<Decimal, Error> {
+    non_empty(values)?;
+    if p < Decimal::ZERO || p > Decimal::ONE_HUNDRED {
+        return Err(Error::DomainError);
+    }
+    let sorted = sorted(values);
+    let rank = p * Decimal::from(sorted.len() - 1) / Decimal::ONE_HUNDRED;
+    let lower = rank.floor();
+    let index = lower.to_usize().ok_or(Error::Overflow)?;
+    let low = sorted[index];
+    let Some(high) = sorted.get(index + 1) else {
+        return Ok(low);
+    };
+    let t = rank - lower;
+    high.checked_sub(low)
+        .and_then(|difference| difference.checked_mul(t))
+        .and_then(|step| low.checked_add(step))
+        // Values of opposite signs too far apart for their difference, weighted instead
+        .or_else(|| (low * (Decimal::ONE - t)).checked_add(*high * t))
+        .ok_or(Error::Overflow)
+}
+
+// The positive `n`th root, with Newton's method from a float estimate
+fn root(x: Decimal, n: usize) -> Option<Decimal> {
+    let degree = Decimal::from(n);
+    let mut guess = Decimal::from_f64(x.to_f64()?.powf(1.0 / n as f64))?;
+    for _ in 0..100 {
+        let power = guess.checked_powu(n as u64 - 1)?;
+        let step = power
+            .checked_mul(guess)?
+            .checked_sub(x)?
+            .checked_div(power.checked_mul(degree)?)?;
+        let next = guess.checked_sub(step)?;
+        if next == guess {
+            break;
+        }
+        guess = next;
+    }
+    Some(guess)
+}
+
+/// The geometric mean of positive values
+/// ## Example
+/// ```rust
+/// use calc::Context;
+///
+/// let context = Context::new();
+/// assert_eq!(context.eval("geomean(2, 8)").unwrap().to_string(), "4");
+/// assert_eq!(context.eval("harmean(1, 4, 4)").unwrap().to_string(), "2");
+/// ```
+pub fn geomean(values: &[Decimal]) -> Result<Decimal, Error> {
+    non_empty(values)?;
+    if values.iter().any(|x| *x <= Decimal::ZERO) {
+        return Err(Error::DomainError);
+    }
+    let product = values
+        .iter()
+        .try_fold(Decimal::ONE, |product, x| product.checked_mul(*x));
+    if let Some(root) = product.and_then(|product| root(product, values.len())) {
+        return Ok(root);
+    }
+    // Too large a product, through the mean of the logarithms instead
+    let logs = values.iter().map(|x| x.ln()).collect::<Vec<_>>();
+    mean(&logs)?.checked_exp().ok_or(Error::Overflow)
+}
+
+/// The harmonic mean of positive values
+pub fn harmean(values: &[Decimal]) -> Result<Decimal, Error> {
+    non_empty(values)?;
+    if values.iter().any(|x| *x <= Decimal::ZERO) {
+        return Err(Error::DomainError);
+    }
+    let reciprocals = values
+        .iter()
+        .map(|x| Decimal::ONE.checked_div(*x).ok_or(Error::Overflow))
+        .collect::<Result<Vec<_>, Error>>()?;
+    Decimal::from(values.len())
+        .checked_div(sum(&reciprocals)?)
+        .ok_or(Error::Overflow)
+}
+
+/// The difference between the largest and the smallest value
+pub fn range(values: &[Decimal]) -> Result<Decimal, Error> {
+    non_empty(values)?;
+    let sorted = sorted(values);
+    sorted[sorted.len() - 1]
+        .checked_sub(sorted[0])
+        .ok_or(Error::Overflow)
+}
@@ -2,49 +2,482 @@ use std::collections::HashMap;
 
 use once_cell::sync::Lazy;
 
-use crate::op::Operator;
+use crate::{
+    angle::{self, AngleMode},
+    complex::Complex,
+    error::Error,
+    finance,
+    integer::{self, from_integer, to_integer},
+    list, matrix,
+    number::Number,
+    op::Operator,
+    polynomial::{self, Polynomial},
+    stats,
+    value::Value,
+};
 
 use rust_decimal::prelude::*;
 
 // TODO: Add more constants
 /// List of inbuilt constants
-pub static CONSTANTS: Lazy<HashMap<&'static str, Decimal>> = Lazy::new(|| {
+pub static CONSTANTS: Lazy<HashMap<&'static str, Value>> = Lazy::new(|| {
     let mut map = HashMap::new();
-    map.insert("e", Decimal::E);
-    map.insert("pi", Decimal::PI);
-    map.insert("π", Decimal::PI);
+    map.insert("e", Value::Real(Decimal::E));
+    map.insert("pi", Value::Real(Decimal::PI));
+    map.insert("π", Value::Real(Decimal::PI));
+    // The imaginary unit, `j` is the electrical engineering spelling
+    map.insert("i", Value::Complex(Complex::I));
+    map.insert("j", Value::Complex(Complex::I));
     map
 });
 
 /// A function enum for keeping track of the different types of functions
-/// with one or two parameters (for now).
-#[derive(PartialEq, Debug, Clone)]
-pub enum Function {
-    OneParam(fn(Decimal) -> Decimal),
-    TwoParam(fn(Decimal, Decimal) -> Decimal),
+/// with one, two or any number of parameters.
+#[derive(Debug, Clone)]
+pub enum Function<N = Value> {
+    OneParam(fn(N) -> Result<N, Error>),
+    /// A function of two arguments, which it gets in reverse order, `atan2(y, x)` is `f(x, y)`
+    TwoParam(fn(N, N) -> Result<N, Error>),
+    /// A function of any number of arguments, which it gets in order, like `mean(1, 2, 3)`.
+    /// The number of arguments of a call is counted by `shunting_yard`.
+    Variadic(fn(Vec<N>) -> Result<N, Error>, usize),
+}
+
+impl<N> PartialEq for Function<N> {
+    fn eq(&self, other: &Self) -> bool {
+        use std::ptr::fn_addr_eq;
+        match (self, other) {
+            (Function::OneParam(a), Function::OneParam(b)) => fn_addr_eq(*a, *b),
+            (Function::TwoParam(a), Function::TwoParam(b)) => fn_addr_eq(*a, *b),
+            (Function::Variadic(a, n), Function::Variadic(b, m)) => fn_addr_eq(*a, *b) && n == m,
+            _ => false,
+        }
+    }
+}
+
+impl<N: Number> Function<N> {
+    /// Calls the function with its arguments in order, which have to be as many as it takes
+    pub fn call(&self, mut args: Vec<N>) -> Result<N, Error> {
+        let count = match self {
+            Function::OneParam(_) => 1,
+            Function::TwoParam(_) => 2,
+            Function::Variadic(..) => args.len(),
+        };
+        if args.len() < count {
+            return Err(Error::NotEnoughArgs);
+        } else if args.len() > count {
+            return Err(Error::TooManyArgs);
+        }
+        match self {
+            Function::OneParam(f) => args.pop().unwrap().map(*f),
+            Function::TwoParam(f) => {
+                let second = args.pop().unwrap();
+                f(second, args.pop().unwrap())
+            }
+            Function::Variadic(f, _) => f(args),
+        }
+    }
+}
+
+/// The functions every number type has.
+pub fn functions<N: Number>() -> HashMap<&'static str, Function<N>> {
+    let mut map = HashMap::new();
+    map.insert("sin", Function::OneParam(|v: N| v.sin()));
+    map.insert("cos", Function::OneParam(|v: N| v.cos()));
+    map.insert("tan", Function::OneParam(|v: N| v.tan()));
+    map.insert(
+        "ctan",
+        Function::OneParam(|v: N| {
+            v.cos()?
+                .checked_div(&v.sin()?)
+                .map_err(|_| Error::DomainError)
+        }),
+    );
+    map.insert("sec", Function::OneParam(|v: N| v.sec()));
+    map.insert("csc", Function::OneParam(|v: N| v.csc()));
+    map.insert("asin", Function::OneParam(|v: N| v.asin()));
+    map.insert("acos", Function::OneParam(|v: N| v.acos()));
+    map.insert("atan", Function::OneParam(|v: N| v.atan()));
+    map.insert("atan2", Function::TwoParam(|x: N, y: N| y.atan2(&x)));
+    // Conversions between radians and degrees, whatever the angle mode
+    map.insert(
+        "deg",
+        Function::OneParam(|v: N| angle::from_radians(&v, AngleMode::Degrees)),
+    );
+    map.insert(
+        "rad",
+        Function::OneParam(|v: N| angle::to_radians(&v, AngleMode::Degrees)),
+    );
+    map.insert("sinh", Function::OneParam(|v: N| v.sinh()));
+    map.insert("cosh", Function::OneParam(|v: N| v.cosh()));
+    map.insert("tanh", Function::OneParam(|v: N| v.tanh()));
+    map.insert("asinh", Function::OneParam(|v: N| v.asinh()));
+    map.insert("acosh", Function::OneParam(|v: N| v.acosh()));
+    map.insert("atanh", Function::OneParam(|v: N| v.atanh()));
+    map.insert("exp", Function::OneParam(|v: N| v.exp()));
+    map.insert("ln", Function::OneParam(|v: N| v.ln()));
+    map.insert("log10", Function::OneParam(|v: N| v.log10()));
+    map.insert("log", Function::TwoParam(|x: N, base: N| x.log(&base)));
+    map.insert("sqrt", Function::OneParam(|v: N| v.sqrt()));
+    map.insert("cbrt", Function::OneParam(|v: N| v.cbrt()));
+    map.insert("abs", Function::OneParam(|v: N| v.abs()));
+    map.insert("sign", Function::OneParam(|v: N| v.sign()));
+    map.insert("floor", Function::OneParam(|v: N| v.floor()));
+    map.insert("ceil", Function::OneParam(|v: N| v.ceil()));
+    map.insert("trunc", Function::OneParam(|v: N| v.trunc()));
+    map.insert(
+        "round",
+        Function::TwoParam(|places: N, x: N| x.round(&places)),
+    );
+    map.insert(
+        "max",
+        Function::TwoParam(|a: N, b: N| Ok(if a.cmp_real(&b)?.is_ge() { a } else { b })),
+    );
+    map.insert(
+        "min",
+        Function::TwoParam(|a: N, b: N| Ok(if a.cmp_real(&b)?.is_le() { a } else { b })),
+    );
+    map
 }
 
 // TODO: Add more functions
-/// List of inbuilt functions
+/// List of inbuilt functions, the common ones and those for complex numbers.
 pub static FUNCTIONS: Lazy<HashMap<&str, Function>> = Lazy::new(|| {
+    let mut map = functions::<Value>();
+    map.insert("re", Function::OneParam(|v| Ok(v.re())));
+    map.insert("im", Function::OneParam(|v| Ok(v.im())));
+    map.insert(
+        "arg",
+        Function::OneParam(|v| Ok(Value::Real(v.to_complex()?.arg()))),
+    );
+    map.insert("conj", Function::OneParam(|v| Ok(v.conj())));
+    // Statistics over any number of arguments, `mean(1, 2, 3)`
+    map.insert(
+        "count",
+        Function::Variadic(|args| Ok(Value::Real(Decimal::from(list::count(&args)))), 0),
+    );
+    map.insert(
+        "mean",
+        Function::Variadic(|args| stats::apply(args, stats::mean), 0),
+    );
+    map.insert(
+        "median",
+        Function::Variadic(|args| stats::apply(args, stats::median), 0),
+    );
+    map.insert(
+        "mode",
+        Function::Variadic(|args| stats::apply(args, stats::mode), 0),
+    );
+    map.insert(
+        "variance",
+        Function::Variadic(|args| stats::apply(args, stats::variance), 0),
+    );
+    map.insert(
+        "pvariance",
+        Function::Variadic(|args| stats::apply(args, stats::pvariance), 0),
+    );
+    map.insert(
+        "stdev",
+        Function::Variadic(|args| stats::apply(args, stats::stdev), 0),
+    );
+    map.insert(
+        "pstdev",
+        Function::Variadic(|args| stats::apply(args, stats::pstdev), 0),
+    );
+    map.insert(
+        "geomean",
+        Function::Variadic(|args| stats::apply(args, stats::geomean), 0),
+    );
+    map.insert(
+        "harmean",
+        Function::Variadic(|args| stats::apply(args, stats::harmean), 0),
+    );
+    map.insert(
+        "range",
+        Function::Variadic(|args| stats::apply(args, stats::range), 0),
+    );
+    map.insert(
+        "percentile",
+        Function::Variadic(
+            |args| {
+                let (p, values) = args.split_first().ok_or(Error::NotEnoughArgs)?;
+                let p = p.as_real()?;
+                stats::apply(values.to_vec(), |values| stats::percentile(p, values))
+            },
+            0,
+        ),
+    );
+    // Time value of money, the optional arguments at the end default to 0
+    map.insert(
+        "fv",
+        Function::Variadic(
+            |args| {
+                stats::apply(args, |a| {
+                    let [rate, nper, pmt, pv, when] = finance::arguments(a, 3)?;
+                    finance::fv(rate, nper, pmt, pv, when)
+                })
+            },
+            0,
+        ),
+    );
+    map.insert(
+        "pv",
+        Function::Variadic(
+            |args| {
+                stats::apply(args, |a| {
+                    let [rate, nper, pmt, fv, when] = finance::arguments(a, 3)?;
+                    finance::pv(rate, nper, pmt, fv, when)
+                })
+            },
+            0,
+        ),
+    );
+    map.insert(
+        "pmt",
+        Function::Variadic(
+            |args| {
+                stats::apply(args, |a| {
+                    let [rate, nper, pv, fv, when] = finance::arguments(a, 3)?;
+                    finance::pmt(rate, nper, pv, fv, when)
+                })
+            },
+            0,
+        ),
+    );
+    map.insert(
+        "nper",
+        Function::Variadic(
+            |args| {
+                stats::apply(args, |a| {
+                    let [rate, pmt, pv, fv, when] = finance::arguments(a, 3)?;
+                    finance::nper(rate, pmt, pv, fv, when)
+                })
+            },
+            0,
+        ),
+    );
+    map.insert(
+        "rate",
+        Function::Variadic(
+            |args| {
+                stats::apply(args, |a| {
+                    let [nper, pmt, pv, fv, when, guess] = finance::arguments(a, 3)?;
+                    finance::rate(nper, pmt, pv, fv, when, guess)
+                })
+            },
+            0,
+        ),
+    );
+    map.insert(
+        "npv",
+        Function::Variadic(
+            |args| {
+                stats::apply(args, |a| {
+                    let (rate, cashflows) = a.split_first().ok_or(Error::NotEnoughArgs)?;
+                    finance::npv(*rate, cashflows)
+                })
+            },
+            0,
+        ),
+    );
+    map.insert(
+        "irr",
+        Function::Variadic(|args| stats::apply(args, finance::irr), 0),
+    );
+    map.insert(
+        "xnpv",
+        Function::Variadic(
+            |args| {
+                stats::apply(args, |a| {
+                    let (rate, cashflows) = a.split_first().ok_or(Error::NotEnoughArgs)?;
+                    finance::xnpv(*rate, cashflows)
+                })
+            },
+            0,
+        ),
+    );
+    map.insert(
+        "polar",
+        Function::TwoParam(|theta, r| {
+            Complex::from_polar(r.as_real()?, theta.as_real()?).map(Value::from)
+        }),
+    );
+    // Lists, whose elements are taken one by one like separate arguments
+    map.insert(
+        "len",
+        Function::Variadic(|args| Ok(Value::Real(Decimal::from(list::count(&args)))), 0),
+    );
+    map.insert(
+        "sum",
+        Function::Variadic(|args| list::sum(&list::elements(args)), 0),
+    );
+    map.insert(
+        "prod",
+        Function::Variadic(|args| list::prod(&list::elements(args)), 0),
+    );
+    map.insert(
+        "norm",
+        Function::Variadic(|args| list::norm(&list::elements(args)), 0),
+    );
+    map.insert("dot", Function::TwoParam(|b, a| list::dot(&a, &b)));
+    map.insert("cross", Function::TwoParam(|b, a| list::cross(&a, &b)));
+    // Matrices, as lists of rows
+    map.insert(
+        "transpose",
+        Function::Variadic(|args| matrix::transpose(&matrix::argument(args)?), 0),
+    );
+    map.insert(
+        "det",
+        Function::Variadic(|args| matrix::det(&matrix::argument(args)?), 0),
+    );
+    map.insert(
+        "inv",
+        Function::Variadic(|args| matrix::inv(&matrix::argument(args)?), 0),
+    );
+    map.insert(
+        "rank",
+        Function::Variadic(|args| matrix::rank(&matrix::argument(args)?), 0),
+    );
+    map.insert("solve", Function::TwoParam(|b, a| matrix::solve(&a, &b)));
+    map
+});
+
+/// Combinatorics and number theory, with exact integers.
+/// Arguments with a fractional part are errors.
+pub static INTEGER_FUNCTIONS: Lazy<HashMap<&str, Function>> = Lazy::new(|| {
+    let mut map = HashMap::new();
+    map.insert(
+        "gcd",
+        Function::Variadic(
+            |args| Ok(from_integer(integer::gcd(&integer::integers(args)?))),
+            0,
+        ),
+    );
+    map.insert(
+        "lcm",
+        Function::Variadic(
+            |args| Ok(from_integer(integer::lcm(&integer::integers(args)?))),
+            0,
+        ),
+    );
+    let ncr = Function::TwoParam(|k, n| {
+        integer::ncr(&to_integer(&n)?, &to_integer(&k)?).map(from_integer)
+    });
+    map.insert("ncr", ncr.clone());
+    map.insert("choose", ncr);
+    map.insert(
+        "npr",
+        Function::TwoParam(|k, n| {
+            integer::npr(&to_integer(&n)?, &to_integer(&k)?).map(from_integer)
+        }),
+    );
+    map.insert(
+        "isprime",
+        Function::OneParam(|n| {
+            let prime = integer::is_prime(&to_integer(&n)?);
+            Ok(from_integer(u8::from(prime).into()))
+        }),
+    );
+    map.insert(
+        "nextprime",
+        Function::OneParam(|n| Ok(from_integer(integer::next_prime(&to_integer(&n)?)))),
+    );
+    map.insert(
+        "totient",
+        Function::OneParam(|n| integer::totient(&to_integer(&n)?).map(from_integer)),
+    );
+    map.insert(
+        "factor",
+        Function::OneParam(|n| Ok(integer::factor(&to_integer(&n)?)?.to_list())),
+    );
+    map.insert(
+        "modpow",
+        Function::Variadic(
+            |args| {
+                let [base, exponent, modulus] = integer::arguments(args)?;
+                integer::modpow(&base, &exponent, &modulus).map(from_integer)
+            },
+            0,
+        ),
+    );
+    map.insert(
+        "modinv",
+        Function::TwoParam(|m, a| {
+            integer::modinv(&to_integer(&a)?, &to_integer(&m)?).map(from_integer)
+        }),
+    );
+    map
+});
+
+/// Polynomials in `x`, written with their coefficients from the highest power down.
+/// Their arguments can be lists of coefficients too, `roots([1, 0, -2])`.
+pub static POLYNOMIAL_FUNCTIONS: Lazy<HashMap<&str, Function>> = Lazy::new(|| {
     let mut map = HashMap::new();
-    map.insert("sin", Function::OneParam(|f| f.sin()));
-    map.insert("cos", Function::OneParam(|f| f.cos()));
-    map.insert("tan", Function::OneParam(|f| f.tan()));
-    map.insert("ctan", Function::OneParam(|f| f.cos() / f.sin()));
-    map.insert("max", Function::TwoParam(Decimal::max));
-    map.insert("min", Function::TwoParam(Decimal::min));
+    map.insert(
+        "poly",
+        Function::Variadic(
+            |args| Polynomial::from_highest(list::elements(args)).map(Value::from),
+            0,
+        ),
+    );
+    map.insert(
+        "polyfit",
+        Function::Variadic(|args| polynomial::fit_arguments(args).map(Value::from), 0),
+    );
+    map.insert(
+        "polyval",
+        Function::TwoParam(|x, p| polynomial::to_polynomial(&p)?.eval(&x)),
+    );
+    map.insert(
+        "polyder",
+        Function::Variadic(
+            |args| polynomial::argument(args)?.derivative().map(Value::from),
+            0,
+        ),
+    );
+    map.insert(
+        "polydiv",
+        Function::TwoParam(|b, a| {
+            let (a, b) = (
+                polynomial::to_polynomial(&a)?,
+                polynomial::to_polynomial(&b)?,
+            );
+            let (quotient, remainder) = a.div_rem(&b)?;
+            Ok(Value::List(vec![quotient.into(), remainder.into()]))
+        }),
+    );
+    map.insert(
+        "roots",
+        Function::Variadic(
+            |args| polynomial::argument(args)?.roots().map(Value::List),
+            0,
+        ),
+    );
+    map.insert(
+        "coeffs",
+        Function::Variadic(
+            |args| Ok(Value::List(polynomial::argument(args)?.highest_first())),
+            0,
+        ),
+    );
+    map.insert(
+        "degree",
+        Function::Variadic(
+            |args| Ok(from_integer(polynomial::argument(args)?.degree().into())),
+            0,
+        ),
+    );
     map
 });
 
 /// A token can be a number, a left parenthesis, a right parenthesis,
 /// an operator, a function, a constant, or a comma (only for parsing ease).
-#[derive(PartialEq, Debug)]
-pub enum Token {
-    Number(Decimal),
+#[derive(Clone, PartialEq, Debug)]
+pub enum Token<N = Value> {
+    Number(N),
     ParLeft,
     ParRight,
     Operator(Operator),
-    Function(Function),
+    Function(Function<N>),
     Comma,
 }
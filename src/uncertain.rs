@@ -0,0 +1,264 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap},
+    fmt::{Display, Formatter},
+    sync::atomic::{AtomicUsize, Ordering as AtomicOrdering},
+};
+
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+use once_cell::sync::Lazy;
+
+use crate::{
+    error::Error,
+    number::Number,
+    parse::NumberMode,
+    token::{functions, Function},
+};
+
+// Every `±` creates a new independent source of uncertainty
+static NEXT_SOURCE: AtomicUsize = AtomicUsize::new(0);
+
+static FUNCTIONS: Lazy<HashMap<&str, Function<Uncertain>>> = Lazy::new(functions);
+
+/// A measured value with its standard uncertainty, like `9.81 ± 0.02`.
+/// Uncertainties are propagated to first order: the result keeps how much each
+/// independent `±` contributes to it, so a variable that appears more than once
+/// is correlated with itself and `x - x` is exactly `0`.
+/// ## Example
+/// ```rust
+/// use calc::{uncertain::Uncertain, Context};
+///
+/// let mut context = Context::<Uncertain>::default();
+/// let g = context.eval("9.81 ± 0.02").unwrap();
+/// context.set("g", g);
+/// let doubled = context.eval("2g").unwrap();
+/// assert!((doubled.uncertainty() - 0.04).abs() < 1e-12);
+/// assert_eq!(context.eval("g - g").unwrap().uncertainty(), 0.0);
+/// // Independent measurements add in quadrature
+/// let sum = context.eval("3 ± 0.3 + 4 ± 0.4").unwrap();
+/// assert!((sum.uncertainty() - 0.5).abs() < 1e-12);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Uncertain {
+    pub value: f64,
+    // The contribution of each source, its standard uncertainty times
+    // the derivative of the value with respect to it
+    components: BTreeMap<usize, f64>,
+}
+
+impl Uncertain {
+    /// A value without uncertainty
+    pub fn exact(value: f64) -> Self {
+        Self {
+            value,
+            components: BTreeMap::new(),
+        }
+    }
+
+    /// A value with an uncertainty that is independent of every other one
+    pub fn new(value: f64, uncertainty: f64) -> Self {
+        let source = NEXT_SOURCE.fetch_add(1, AtomicOrdering::Relaxed);
+        Self {
+            value,
+            components: BTreeMap::from([(source, uncertainty.abs())]),
+        }
+    }
+
+    /// The standard uncertainty, the contributions added in quadrature
+    pub fn uncertainty(&self) -> f64 {
+        self.components.values().map(|c| c * c).sum::<f64>().sqrt()
+    }
+
+    // The result of a function of two values, given its partial derivatives
+    fn combine(&self, rhs: &Uncertain, value: f64, da: f64, db: f64) -> Result<Uncertain, Error> {
+        if value.is_nan() {
+            return Err(Error::DomainError);
+        }
+        let mut components = BTreeMap::new();
+        for (source, c) in &self.components {
+            *components.entry(*source).or_insert(0.0) += da * c;
+        }
+        for (source, c) in &rhs.components {
+            *components.entry(*source).or_insert(0.0) += db * c;
+        }
+        Ok(Uncertain { value, components })
+    }
+
+    // The result of a function of one value, given its derivative
+    fn apply(&self, value: f64, derivative: f64) -> Result<Uncertain, Error> {
+        self.combine(&Uncertain::exact(0.0), value, derivative, 0.0)
+    }
+}
+
+impl Number for Uncertain {
+    fn from_literal(s: &str, _: NumberMode) -> Result<Self, Error> {
+        Ok(Uncertain::exact(s.parse::<f64>()?))
+    }
+
+    fn from_integer(n: BigInt, _: NumberMode) -> Result<Self, Error> {
+        n.to_f64()
+            .map(Uncertain::exact)
+            .ok_or(Error::NumberParseError)
+    }
+
+    fn constant(name: &str) -> Option<Self> {
+        match name {
+            "e" => Some(Uncertain::exact(std::f64::consts::E)),
+            "pi" | "π" => Some(Uncertain::exact(std::f64::consts::PI)),
+            "∞" => Some(Uncertain::exact(f64::INFINITY)),
+            _ => None,
+        }
+    }
+
+    fn function(name: &str) -> Option<Function<Self>> {
+        FUNCTIONS.get(name).cloned()
+    }
+
+    fn checked_add(&self, rhs: &Self) -> Result<Self, Error> {
+        self.combine(rhs, self.value + rhs.value, 1.0, 1.0)
+    }
+
+    fn checked_sub(&self, rhs: &Self) -> Result<Self, Error> {
+        self.combine(rhs, self.value - rhs.value, 1.0, -1.0)
+    }
+
+    fn checked_mul(&self, rhs: &Self) -> Result<Self, Error> {
+        self.combine(rhs, self.value * rhs.value, rhs.value, self.value)
+    }
+
+    fn checked_div(&self, rhs: &Self) -> Result<Self, Error> {
+        if rhs.value == 0.0 {
+            return Err(Error::DivisionByZero);
+        }
+        let (a, b) = (self.value, rhs.value);
+        self.combine(rhs, a / b, 1.0 / b, -a / (b * b))
+    }
+
+    fn checked_pow(&self, rhs: &Self) -> Result<Self, Error> {
+        let (a, b) = (self.value, rhs.value);
+        if a == 0.0 && b < 0.0 {
+            return Err(Error::DivisionByZero);
+        }
+        let value = a.powf(b);
+        // The exponent only contributes when it is uncertain, which needs a positive base
+        let db = if rhs.components.is_empty() {
+            0.0
+        } else if a > 0.0 {
+            value * a.ln()
+        } else {
+            return Err(Error::DomainError);
+        };
+        let da = if b == 0.0 { 0.0 } else { b * a.powf(b - 1.0) };
+        self.combine(rhs, value, da, db)
+    }
+
+    fn plus_minus(&self, rhs: &Self) -> Result<Self, Error> {
+        if !rhs.components.is_empty() || rhs.value < 0.0 {
+            return Err(Error::DomainError);
+        }
+        let mut result = Uncertain::new(self.value, rhs.value);
+        result.components.extend(self.components.iter());
+        Ok(result)
+    }
+
+    fn sqrt(&self) -> Result<Self, Error> {
+        if self.value < 0.0 {
+            return Err(Error::DomainError);
+        }
+        let root = self.value.sqrt();
+        self.apply(root, 0.5 / root)
+    }
+
+    fn cbrt(&self) -> Result<Self, Error> {
+        let root = self.value.cbrt();
+        self.apply(root, 1.0 / (3.0 * root * root))
+    }
+
+    fn abs(&self) -> Result<Self, Error> {
+        let sign = if self.value < 0.0 { -1.0 } else { 1.0 };
+        self.apply(self.value.abs(), sign)
+    }
+
+    fn sin(&self) -> Result<Self, Error> {
+        self.apply(self.value.sin(), self.value.cos())
+    }
+
+    fn cos(&self) -> Result<Self, Error> {
+        self.apply(self.value.cos(), -self.value.sin())
+    }
+
+    fn tan(&self) -> Result<Self, Error> {
+        let cos = self.value.cos();
+        self.apply(self.value.tan(), 1.0 / (cos * cos))
+    }
+
+    fn cmp_real(&self, other: &Self) -> Result<Ordering, Error> {
+        self.value
+            .partial_cmp(&other.value)
+            .ok_or(Error::DomainError)
+    }
+
+    fn exp(&self) -> Result<Self, Error> {
+        let e = self.value.exp();
+        self.apply(e, e)
+    }
+
+    fn ln(&self) -> Result<Self, Error> {
+        if self.value <= 0.0 {
+            return Err(Error::DomainError);
+        }
+        self.apply(self.value.ln(), 1.0 / self.value)
+    }
+
+    fn log10(&self) -> Result<Self, Error> {
+        self.log(&Uncertain::exact(10.0))
+    }
+
+    // Only exact powers of an exact base snap to the exponent, the others keep their uncertainty
+    fn log(&self, base: &Self) -> Result<Self, Error> {
+        if self.value <= 0.0 || base.value <= 0.0 || base.value == 1.0 {
+            return Err(Error::DomainError);
+        }
+        let (ln, ln_base) = (self.value.ln(), base.value.ln());
+        let mut value = ln / ln_base;
+        if self.components.is_empty()
+            && base.components.is_empty()
+            && base.value.powf(value.round()) == self.value
+        {
+            value = value.round();
+        }
+        let da = 1.0 / (self.value * ln_base);
+        let db = -ln / (base.value * ln_base * ln_base);
+        self.combine(base, value, da, db)
+    }
+
+    fn atan(&self) -> Result<Self, Error> {
+        self.apply(self.value.atan(), 1.0 / (1.0 + self.value * self.value))
+    }
+
+    // Steps are flat everywhere else, so the uncertainty is lost
+    fn floor(&self) -> Result<Self, Error> {
+        self.apply(self.value.floor(), 0.0)
+    }
+}
+
+/// Writes the uncertainty with two significant digits,
+/// and the value with as many decimals, e.g. `9.810 ± 0.020`
+impl Display for Uncertain {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let uncertainty = self.uncertainty();
+        if uncertainty == 0.0 {
+            return write!(f, "{}", self.value);
+        }
+        if !uncertainty.is_finite() {
+            return write!(f, "{} ± {}", self.value, uncertainty);
+        }
+        let decimals = (1 - uncertainty.log10().floor() as i32).max(0) as usize;
+        write!(
+            f,
+            "{:.*} ± {:.*}",
+            decimals, self.value, decimals, uncertainty
+        )
+    }
+}
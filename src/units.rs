@@ -0,0 +1,186 @@
+use std::{collections::HashMap, sync::RwLock};
+
+use num_rational::BigRational;
+use once_cell::sync::Lazy;
+
+use crate::{context::Context, error::Error, quantity::Unit, rational, value::Value};
+
+/// Exponents of the SI base units, in the order m, kg, s, A, K, mol, cd
+pub type Dimension = [i32; 7];
+
+/// A named unit, defined by its size in SI base units
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnitDef {
+    /// How many SI base units one of it is, e.g. `1000` for `km`
+    pub factor: BigRational,
+    pub dimension: Dimension,
+    /// Whether SI prefixes like `k` or `m` can be put in front of it
+    pub prefixable: bool,
+}
+
+// SI prefixes, from the largest to the smallest
+const PREFIXES: &[(&str, &str)] = &[
+    ("Y", "1e24"),
+    ("Z", "1e21"),
+    ("E", "1e18"),
+    ("P", "1e15"),
+    ("T", "1e12"),
+    ("G", "1e9"),
+    ("M", "1e6"),
+    ("k", "1e3"),
+    ("h", "1e2"),
+    ("da", "1e1"),
+    ("d", "1e-1"),
+    ("c", "1e-2"),
+    ("m", "1e-3"),
+    ("µ", "1e-6"),
+    ("μ", "1e-6"),
+    ("u", "1e-6"),
+    ("n", "1e-9"),
+    ("p", "1e-12"),
+    ("f", "1e-15"),
+    ("a", "1e-18"),
+];
+
+// Name, size in SI base units as a decimal or a fraction, exponents of m, kg, s, A, K, mol, cd, and whether it takes prefixes
+#[rustfmt::skip]
+const BUILTIN: &[(&str, &str, Dimension, bool)] = &[
+    // Base units, the kilogram is a prefixed gram
+    ("m", "1", [1, 0, 0, 0, 0, 0, 0], true),
+    ("g", "0.001", [0, 1, 0, 0, 0, 0, 0], true),
+    ("s", "1", [0, 0, 1, 0, 0, 0, 0], true),
+    ("A", "1", [0, 0, 0, 1, 0, 0, 0], true),
+    ("K", "1", [0, 0, 0, 0, 1, 0, 0], true),
+    ("mol", "1", [0, 0, 0, 0, 0, 1, 0], true),
+    ("cd", "1", [0, 0, 0, 0, 0, 0, 1], true),
+    // Derived units
+    ("Hz", "1", [0, 0, -1, 0, 0, 0, 0], true),
+    ("N", "1", [1, 1, -2, 0, 0, 0, 0], true),
+    ("Pa", "1", [-1, 1, -2, 0, 0, 0, 0], true),
+    ("J", "1", [2, 1, -2, 0, 0, 0, 0], true),
+    ("W", "1", [2, 1, -3, 0, 0, 0, 0], true),
+    ("C", "1", [0, 0, 1, 1, 0, 0, 0], true),
+    ("V", "1", [2, 1, -3, -1, 0, 0, 0], true),
+    ("Ω", "1", [2, 1, -3, -2, 0, 0, 0], true),
+    ("ohm", "1", [2, 1, -3, -2, 0, 0, 0], true),
+    ("F", "1", [-2, -1, 4, 2, 0, 0, 0], true),
+    ("T", "1", [0, 1, -2, -1, 0, 0, 0], true),
+    ("Wb", "1", [2, 1, -2, -1, 0, 0, 0], true),
+    ("H", "1", [2, 1, -2, -2, 0, 0, 0], true),
+    ("L", "0.001", [3, 0, 0, 0, 0, 0, 0], true),
+    ("eV", "1.602176634e-19", [2, 1, -2, 0, 0, 0, 0], true),
+    ("Wh", "3600", [2, 1, -2, 0, 0, 0, 0], true),
+    ("cal", "4.184", [2, 1, -2, 0, 0, 0, 0], true),
+    ("bar", "100000", [-1, 1, -2, 0, 0, 0, 0], true),
+    ("t", "1000", [0, 1, 0, 0, 0, 0, 0], true),
+    // Time
+    ("min", "60", [0, 0, 1, 0, 0, 0, 0], false),
+    ("h", "3600", [0, 0, 1, 0, 0, 0, 0], false),
+    ("d", "86400", [0, 0, 1, 0, 0, 0, 0], false),
+    ("year", "31557600", [0, 0, 1, 0, 0, 0, 0], false),
+    // Imperial and US units, `in` is also the conversion keyword so inches are `inch`,
+    // the parser reads an `in` without a unit after it as one
+    ("inch", "0.0254", [1, 0, 0, 0, 0, 0, 0], false),
+    ("ft", "0.3048", [1, 0, 0, 0, 0, 0, 0], false),
+    ("yd", "0.9144", [1, 0, 0, 0, 0, 0, 0], false),
+    ("mi", "1609.344", [1, 0, 0, 0, 0, 0, 0], false),
+    ("nmi", "1852", [1, 0, 0, 0, 0, 0, 0], false),
+    ("mph", "0.44704", [1, 0, -1, 0, 0, 0, 0], false),
+    ("kn", "1852/3600", [1, 0, -1, 0, 0, 0, 0], false),
+    ("lb", "0.45359237", [0, 1, 0, 0, 0, 0, 0], false),
+    ("oz", "0.028349523125", [0, 1, 0, 0, 0, 0, 0], false),
+    ("gal", "0.003785411784", [3, 0, 0, 0, 0, 0, 0], false),
+    ("psi", "4.4482216152605/0.00064516", [-1, 1, -2, 0, 0, 0, 0], false),
+    ("atm", "101325", [-1, 1, -2, 0, 0, 0, 0], false),
+];
+
+// Reads the exact size of a builtin unit, like `0.3048` or `1852/3600`
+fn exact(s: &str) -> BigRational {
+    match s.split_once('/') {
+        Some((a, b)) => exact(a) / exact(b),
+        None => rational::parse_exact(s).unwrap(),
+    }
+}
+
+static UNITS: Lazy<RwLock<HashMap<String, UnitDef>>> = Lazy::new(|| {
+    let mut map = HashMap::new();
+    for (name, factor, dimension, prefixable) in BUILTIN {
+        let def = UnitDef {
+            factor: exact(factor),
+            dimension: *dimension,
+            prefixable: *prefixable,
+        };
+        map.insert(name.to_string(), def);
+    }
+    RwLock::new(map)
+});
+
+/// Looks up a unit by name, with an optional SI prefix like the `k` of `km`.
+/// Names without a prefix are tried first, so `min` is a minute and `cd` a candela.
+pub fn lookup(name: &str) -> Option<UnitDef> {
+    let units = UNITS.read().unwrap();
+    if let Some(def) = units.get(name) {
+        return Some(def.clone());
+    }
+    PREFIXES.iter().find_map(|(prefix, size)| {
+        let def = units.get(name.strip_prefix(prefix)?)?;
+        if !def.prefixable {
+            return None;
+        }
+        Some(UnitDef {
+            factor: &def.factor * exact(size),
+            ..def.clone()
+        })
+    })
+}
+
+/// Adds a unit, or replaces one with the same name.
+/// The definition is an expression of numbers and other units.
+/// ## Example
+/// ```rust
+/// use calc::{units, Context};
+///
+/// units::define("furlong", "201.168 m").unwrap();
+/// let speed = Context::new().eval("1 furlong/min to km/h").unwrap();
+/// assert_eq!(speed.to_string(), "12.07008 km/h");
+/// ```
+pub fn define(name: &str, definition: &str) -> Result<(), Error> {
+    let valid = name
+        .chars()
+        .all(|c| c.is_alphabetic() || c == '_' || c == 'Ω');
+    if name.is_empty() || !valid {
+        return Err(Error::InvalidUnitDefinition(format!(
+            "{} = {}",
+            name, definition
+        )));
+    }
+    let (factor, unit) = match Context::new().eval(definition)? {
+        Value::Quantity(q) => (q.si_value(), q.unit),
+        value => (rational::from_decimal(value.as_real()?), Unit::none()),
+    };
+    let def = UnitDef {
+        factor,
+        dimension: unit.dimension,
+        prefixable: false,
+    };
+    UNITS.write().unwrap().insert(name.to_string(), def);
+    Ok(())
+}
+
+/// Loads unit definitions, one `name = definition` per line.
+/// Empty lines and lines starting with `#` are skipped.
+/// Returns the number of units defined.
+pub fn load(source: &str) -> Result<usize, Error> {
+    let mut count = 0;
+    for line in source.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (name, definition) = line
+            .split_once('=')
+            .ok_or_else(|| Error::InvalidUnitDefinition(line.to_string()))?;
+        define(name.trim(), definition.trim())?;
+        count += 1;
+    }
+    Ok(count)
+}
@@ -0,0 +1,472 @@
+use std::{
+    cmp::Ordering,
+    fmt::{Display, Formatter},
+};
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{Signed, Zero};
+use rust_decimal::{prelude::*, MathematicalOps};
+
+use crate::{
+    complex::{self, Complex},
+    error::Error,
+    list, number,
+    parse::NumberMode,
+    polynomial::{self, Polynomial},
+    quantity::{self, Quantity},
+    rational,
+};
+
+/// A value produced by evaluating an expression.
+/// Complex results with no imaginary part are always stored as real numbers.
+/// Exact fractions only come from literals in rational mode, and stay exact
+/// through `+`, `-`, `*`, `/` and integer powers.
+/// Quantities are real numbers with a unit, like `3 m`.
+/// Lists, like `[1, 2, 3]`, combine element by element with every operator.
+/// Polynomials in `x`, like `poly([1, 0, -2])`, combine with numbers as constant polynomials.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Real(Decimal),
+    Complex(Complex),
+    Rational(BigRational),
+    Quantity(Quantity),
+    List(Vec<Value>),
+    Polynomial(Polynomial),
+}
+
+impl Value {
+    /// Reads a decimal literal like `12.5e-3`, to the 28 digits of `Decimal` or exactly in rational mode
+    pub fn from_literal(s: &str, mode: NumberMode) -> Result<Value, Error> {
+        match mode {
+            NumberMode::Decimal => {
+                // All the digits `Decimal` holds, or through a float when it is out of its range
+                let digits = match s.contains(['e', 'E']) {
+                    true => Decimal::from_scientific(s),
+                    false => Decimal::from_str(s),
+                };
+                if let Ok(d) = digits {
+                    return Ok(Value::Real(d.normalize()));
+                }
+                let num = s.parse::<f64>()?;
+                Decimal::from_f64(num)
+                    .map(Value::Real)
+                    .ok_or(Error::NumberParseError)
+            }
+            NumberMode::Rational => rational::parse_exact(s).map(Value::Rational),
+        }
+    }
+
+    /// Converts an integer to a value of the given mode
+    pub fn from_integer(n: BigInt, mode: NumberMode) -> Result<Value, Error> {
+        match mode {
+            NumberMode::Decimal => n
+                .to_i128()
+                .and_then(Decimal::from_i128)
+                .map(Value::Real)
+                .ok_or(Error::NumberParseError),
+            NumberMode::Rational => Ok(Value::Rational(BigRational::from_integer(n))),
+        }
+    }
+
+    /// Returns the real number, or an error if the value is complex or has a unit
+    pub fn as_real(&self) -> Result<Decimal, Error> {
+        match self {
+            Value::Real(x) => Ok(*x),
+            Value::Rational(r) => rational::to_decimal(r),
+            Value::Complex(_) => Err(Error::ExpectedReal),
+            Value::Quantity(q) => Err(Error::UnitsNotAllowed(q.unit.to_string())),
+            Value::List(_) => Err(Error::ExpectedNumber),
+            Value::Polynomial(_) => Err(Error::ExpectedReal),
+        }
+    }
+
+    /// Writes the value in the unit of `target`, as in `60 mph to m/s`
+    pub fn convert(&self, target: &Value) -> Result<Value, Error> {
+        if let Value::List(items) = self {
+            return items
+                .iter()
+                .map(|x| x.convert(target))
+                .collect::<Result<Vec<_>, _>>()
+                .map(Value::List);
+        }
+        quantity::convert(self, target)
+    }
+
+    // Whether either side has a unit, which has its own arithmetic
+    fn has_unit(&self, rhs: &Value) -> bool {
+        matches!(self, Value::Quantity(_)) || matches!(rhs, Value::Quantity(_))
+    }
+
+    pub fn to_complex(&self) -> Result<Complex, Error> {
+        match self {
+            Value::Complex(z) => Ok(*z),
+            _ => Ok(Complex::from(self.as_real()?)),
+        }
+    }
+
+    /// Turns exact fractions into `Decimal`, other values are returned as they are
+    pub fn inexact(&self) -> Result<Value, Error> {
+        match self {
+            Value::Rational(r) => rational::to_decimal(r).map(Value::Real),
+            _ => Ok(self.clone()),
+        }
+    }
+
+    /// Compares two real values, exactly if both are fractions
+    pub fn cmp_real(&self, other: &Value) -> Result<Ordering, Error> {
+        match (self, other) {
+            (Value::Rational(a), Value::Rational(b)) => Ok(a.cmp(b)),
+            _ if self.has_unit(other) => quantity::cmp(self, other),
+            _ => Ok(self.as_real()?.cmp(&other.as_real()?)),
+        }
+    }
+
+    // Applies a binary operation to the most precise representation both sides share.
+    // The exact and real versions return `None` to fall back to the next representation.
+    fn binary(
+        &self,
+        rhs: &Value,
+        exact: impl Fn(&BigRational, &BigRational) -> Option<Result<BigRational, Error>>,
+        real: impl Fn(Decimal, Decimal) -> Option<Result<Decimal, Error>>,
+        complex: impl Fn(&Complex, &Complex) -> Result<Complex, Error>,
+    ) -> Result<Value, Error> {
+        if let (Value::Rational(a), Value::Rational(b)) = (self, rhs) {
+            if let Some(result) = exact(a, b) {
+                return result.map(Value::Rational);
+            }
+        }
+        if let (Ok(a), Ok(b)) = (self.as_real(), rhs.as_real()) {
+            if let Some(result) = real(a, b) {
+                return result.map(Value::Real);
+            }
+        }
+        complex(&self.to_complex()?, &rhs.to_complex()?).map(Value::from)
+    }
+
+    pub fn checked_add(&self, rhs: &Value) -> Result<Value, Error> {
+        if let Some(result) = list::broadcast(self, rhs, Value::checked_add) {
+            return result;
+        }
+        if let Some(operands) = polynomial::operands(self, rhs) {
+            return operands.and_then(|(a, b)| a.add(&b)).map(Value::from);
+        }
+        if self.has_unit(rhs) {
+            return quantity::add(self, rhs, false);
+        }
+        self.binary(
+            rhs,
+            |a, b| Some(Ok(a + b)),
+            |a, b| Some(a.checked_add(b).ok_or(Error::Overflow)),
+            Complex::checked_add,
+        )
+    }
+
+    pub fn checked_sub(&self, rhs: &Value) -> Result<Value, Error> {
+        if let Some(result) = list::broadcast(self, rhs, Value::checked_sub) {
+            return result;
+        }
+        if let Some(operands) = polynomial::operands(self, rhs) {
+            return operands.and_then(|(a, b)| a.sub(&b)).map(Value::from);
+        }
+        if self.has_unit(rhs) {
+            return quantity::add(self, rhs, true);
+        }
+        self.binary(
+            rhs,
+            |a, b| Some(Ok(a - b)),
+            |a, b| Some(a.checked_sub(b).ok_or(Error::Overflow)),
+            Complex::checked_sub,
+        )
+    }
+
+    pub fn checked_mul(&self, rhs: &Value) -> Result<Value, Error> {
+        if let Some(result) = list::broadcast(self, rhs, Value::checked_mul) {
+            return result;
+        }
+        if let Some(operands) = polynomial::operands(self, rhs) {
+            return operands.and_then(|(a, b)| a.mul(&b)).map(Value::from);
+        }
+        if self.has_unit(rhs) {
+            return quantity::mul(self, rhs);
+        }
+        self.binary(
+            rhs,
+            |a, b| Some(Ok(a * b)),
+            |a, b| Some(a.checked_mul(b).ok_or(Error::Overflow)),
+            Complex::checked_mul,
+        )
+    }
+
+    pub fn checked_div(&self, rhs: &Value) -> Result<Value, Error> {
+        if let Some(result) = list::broadcast(self, rhs, Value::checked_div) {
+            return result;
+        }
+        if let Some(operands) = polynomial::operands(self, rhs) {
+            return operands
+                .and_then(|(a, b)| polynomial::divide(&a, &b))
+                .map(Value::from);
+        }
+        if self.has_unit(rhs) {
+            return quantity::div(self, rhs);
+        }
+        self.binary(
+            rhs,
+            |a, b| {
+                Some(if b.is_zero() {
+                    Err(Error::DivisionByZero)
+                } else {
+                    Ok(a / b)
+                })
+            },
+            |a, b| {
+                Some(if b.is_zero() {
+                    Err(Error::DivisionByZero)
+                } else {
+                    a.checked_div(b).ok_or(Error::Overflow)
+                })
+            },
+            Complex::checked_div,
+        )
+    }
+
+    /// Raises to a power. A negative base with a fractional exponent
+    /// gives the principal complex root, e.g. `(-8)^(1/3)` is `1 + 1.732…i`.
+    pub fn checked_pow(&self, rhs: &Value) -> Result<Value, Error> {
+        if let Some(result) = list::broadcast(self, rhs, Value::checked_pow) {
+            return result;
+        }
+        match (self, rhs) {
+            (Value::Polynomial(p), n) => return polynomial::pow(p, n).map(Value::from),
+            (_, Value::Polynomial(_)) => return Err(Error::ExpectedInteger),
+            _ => {}
+        }
+        if self.has_unit(rhs) {
+            return quantity::pow(self, rhs);
+        }
+        self.binary(
+            rhs,
+            rational::checked_pow,
+            |a, b| {
+                if a.is_zero() && b.is_sign_negative() {
+                    Some(Err(Error::DivisionByZero))
+                } else if a.is_sign_negative() && !b.fract().is_zero() {
+                    None
+                } else {
+                    Some(number::pow_decimal(a, b))
+                }
+            },
+            Complex::checked_pow,
+        )
+    }
+
+    /// The square root, imaginary for negative numbers
+    pub fn sqrt(&self) -> Result<Value, Error> {
+        match self {
+            Value::Real(x) if x.is_sign_negative() => {
+                let im = (-*x).sqrt().ok_or(Error::DomainError)?;
+                Ok(Value::from(Complex::new(Decimal::ZERO, im)))
+            }
+            Value::Real(x) => x.sqrt().map(Value::Real).ok_or(Error::DomainError),
+            Value::Complex(z) => z.sqrt().map(Value::from),
+            Value::Rational(r) => match rational::nth_root(r, 2) {
+                Some(root) => Ok(Value::Rational(root)),
+                _ => self.inexact()?.sqrt(),
+            },
+            Value::Quantity(q) => quantity::root(q, 2),
+            Value::List(_) => list::map(self.clone(), |x| x.sqrt()),
+            Value::Polynomial(_) => Err(Error::ExpectedReal),
+        }
+    }
+
+    /// The cube root, real for negative real numbers
+    pub fn cbrt(&self) -> Result<Value, Error> {
+        match self {
+            Value::Rational(r) => match rational::nth_root(r, 3) {
+                Some(root) => Ok(Value::Rational(root)),
+                None => number::Number::cbrt(&rational::to_decimal(r)?).map(Value::Real),
+            },
+            Value::Real(x) => number::Number::cbrt(x).map(Value::Real),
+            Value::Complex(z) => z
+                .checked_pow(&Complex::from(Decimal::ONE / Decimal::from(3)))
+                .map(Value::from),
+            Value::Quantity(q) => quantity::root(q, 3),
+            Value::List(_) => list::map(self.clone(), |x| x.cbrt()),
+            Value::Polynomial(_) => Err(Error::ExpectedReal),
+        }
+    }
+
+    /// The absolute value of a real number, or the modulus of a complex one
+    pub fn abs(&self) -> Result<Value, Error> {
+        match self {
+            Value::Real(x) => Ok(Value::Real(x.abs())),
+            Value::Complex(z) => z.abs().map(Value::Real),
+            Value::Rational(r) => Ok(Value::Rational(r.abs())),
+            Value::Quantity(q) => Ok(quantity::abs(q)),
+            Value::List(_) => list::map(self.clone(), |x| x.abs()),
+            Value::Polynomial(_) => Err(Error::ExpectedReal),
+        }
+    }
+
+    /// The real part
+    pub fn re(&self) -> Value {
+        match self {
+            Value::Complex(z) => Value::Real(z.re),
+            _ => self.clone(),
+        }
+    }
+
+    /// The imaginary part
+    pub fn im(&self) -> Value {
+        match self {
+            Value::Complex(z) => Value::Real(z.im),
+            Value::Rational(_) => Value::Rational(BigRational::zero()),
+            Value::Real(_) | Value::Quantity(_) => Value::Real(Decimal::ZERO),
+            Value::List(items) => Value::List(items.iter().map(Value::im).collect()),
+            Value::Polynomial(p) => {
+                let parts = p.coefficients().iter().map(Value::im).collect();
+                Polynomial::new(parts).map_or_else(|_| self.clone(), Value::from)
+            }
+        }
+    }
+
+    pub fn conj(&self) -> Value {
+        match self {
+            Value::Complex(z) => Value::Complex(z.conj()),
+            _ => self.clone(),
+        }
+    }
+
+    pub fn sin(&self) -> Result<Value, Error> {
+        match self.inexact()? {
+            Value::Complex(z) => z.sin().map(Value::from),
+            v => v
+                .as_real()?
+                .checked_sin()
+                .map(Value::Real)
+                .ok_or(Error::Overflow),
+        }
+    }
+
+    pub fn cos(&self) -> Result<Value, Error> {
+        match self.inexact()? {
+            Value::Complex(z) => z.cos().map(Value::from),
+            v => v
+                .as_real()?
+                .checked_cos()
+                .map(Value::Real)
+                .ok_or(Error::Overflow),
+        }
+    }
+
+    pub fn tan(&self) -> Result<Value, Error> {
+        match self.inexact()? {
+            Value::Complex(z) => z.tan().map(Value::from),
+            v => v
+                .as_real()?
+                .checked_tan()
+                .map(Value::Real)
+                .ok_or(Error::DomainError),
+        }
+    }
+}
+
+impl Value {
+    pub fn exp(&self) -> Result<Value, Error> {
+        match self.inexact()? {
+            Value::Complex(z) => z.exp().map(Value::from),
+            v => number::exp_decimal(v.as_real()?).map(Value::Real),
+        }
+    }
+
+    /// The natural logarithm, complex for negative numbers, e.g. `ln(-1)` is `πi`
+    pub fn ln(&self) -> Result<Value, Error> {
+        match self.inexact()? {
+            Value::Complex(z) => z.ln().map(Value::from),
+            v => {
+                let x = v.as_real()?;
+                if x.is_zero() {
+                    Err(Error::DomainError)
+                } else if x.is_sign_negative() {
+                    Complex::from(x).ln().map(Value::from)
+                } else {
+                    x.checked_ln().map(Value::Real).ok_or(Error::DomainError)
+                }
+            }
+        }
+    }
+
+    /// The logarithm in base 10, exact for powers of 10
+    pub fn log10(&self) -> Result<Value, Error> {
+        match self.inexact()? {
+            Value::Real(x) if x.is_sign_positive() && !x.is_zero() => {
+                x.checked_log10().map(Value::Real).ok_or(Error::DomainError)
+            }
+            v => v.ln()?.checked_div(&Value::Real(Decimal::TEN.ln())),
+        }
+    }
+
+    pub fn atan(&self) -> Result<Value, Error> {
+        Ok(Value::Real(complex::atan2(self.as_real()?, Decimal::ONE)))
+    }
+
+    /// The largest integer less than or equal to a real value, exactly for fractions
+    pub fn floor(&self) -> Result<Value, Error> {
+        match self {
+            Value::Rational(r) => Ok(Value::Rational(r.floor())),
+            _ => Ok(Value::Real(self.as_real()?.floor())),
+        }
+    }
+}
+
+impl From<Decimal> for Value {
+    fn from(x: Decimal) -> Self {
+        Value::Real(x)
+    }
+}
+
+impl From<Complex> for Value {
+    fn from(z: Complex) -> Self {
+        if z.im.is_zero() {
+            Value::Real(z.re)
+        } else {
+            Value::Complex(z)
+        }
+    }
+}
+
+impl From<BigRational> for Value {
+    fn from(r: BigRational) -> Self {
+        Value::Rational(r)
+    }
+}
+
+impl PartialEq<Decimal> for Value {
+    fn eq(&self, other: &Decimal) -> bool {
+        match self {
+            Value::Real(x) => x == other,
+            Value::Rational(r) => *r == rational::from_decimal(*other),
+            Value::Complex(_) | Value::Quantity(_) | Value::List(_) | Value::Polynomial(_) => false,
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Value::Real(x) => write!(f, "{}", x),
+            Value::Complex(z) => write!(f, "{}", z),
+            Value::Rational(r) => write!(
+                f,
+                "{}",
+                rational::format_fraction(r, false, |n| n.to_string())
+            ),
+            Value::Quantity(q) => write!(f, "{}", q),
+            Value::List(items) => {
+                let items: Vec<String> = items.iter().map(Value::to_string).collect();
+                write!(f, "[{}]", items.join(", "))
+            }
+            Value::Polynomial(p) => write!(f, "{}", p),
+        }
+    }
+}